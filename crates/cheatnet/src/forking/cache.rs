@@ -10,7 +10,7 @@ use starknet::core::types::ContractClass;
 use starknet_api::block::BlockNumber;
 use starknet_api::core::{ClassHash, ContractAddress, Nonce};
 use starknet_api::state::StorageKey;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::fs::OpenOptions;
 use std::io::{Read, Write};
@@ -25,6 +25,9 @@ struct ForkCacheContent {
     nonce_at: HashMap<ContractAddress, Nonce>,
     class_hash_at: HashMap<ContractAddress, ClassHash>,
     compiled_contract_class: HashMap<ClassHash, ContractClass>,
+    /// Class hashes the fork node reported as not declared at the cached block
+    #[serde(default)]
+    undeclared_class_hashes: HashSet<ClassHash>,
     block_info: Option<SerializableBlockInfo>,
 }
 
@@ -36,6 +39,7 @@ impl Default for ForkCacheContent {
             nonce_at: Default::default(),
             class_hash_at: Default::default(),
             compiled_contract_class: Default::default(),
+            undeclared_class_hashes: Default::default(),
             block_info: Default::default(),
         }
     }
@@ -69,6 +73,8 @@ impl ForkCacheContent {
         self.class_hash_at.extend(other.class_hash_at.clone());
         self.compiled_contract_class
             .extend(other.compiled_contract_class.clone());
+        self.undeclared_class_hashes
+            .extend(other.undeclared_class_hashes.iter().copied());
         if other.block_info.is_some() {
             self.block_info.clone_from(&other.block_info);
         }
@@ -228,6 +234,18 @@ impl ForkCache {
             .or_insert(contract_class)
     }
 
+    pub(crate) fn is_class_hash_undeclared(&self, class_hash: &ClassHash) -> bool {
+        self.fork_cache_content
+            .undeclared_class_hashes
+            .contains(class_hash)
+    }
+
+    pub(crate) fn cache_undeclared_class_hash(&mut self, class_hash: ClassHash) {
+        self.fork_cache_content
+            .undeclared_class_hashes
+            .insert(class_hash);
+    }
+
     pub(crate) fn get_block_info(&self) -> Option<BlockInfo> {
         Some(self.fork_cache_content.block_info.clone()?.into())
     }
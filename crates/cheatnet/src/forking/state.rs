@@ -1,6 +1,6 @@
 use crate::forking::cache::ForkCache;
 use crate::state::BlockInfoReader;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use blockifier::blockifier::block::BlockInfo;
 use blockifier::execution::contract_class::{
     ContractClass as ContractClassBlockifier, ContractClassV0, ContractClassV1,
@@ -11,60 +11,370 @@ use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use cairo_lang_utils::bigint::BigUintAsHex;
 use cairo_vm::Felt252;
 use camino::Utf8Path;
+use conversions::string::IntoHexStr;
 use conversions::{FromConv, IntoConv};
 use flate2::read::GzDecoder;
 use num_bigint::BigUint;
 use runtime::starknet::context::SerializableGasPrices;
+use scarb_api::StarknetContractArtifacts;
+use shared::rpc_transport::{RpcTransport, RpcTransportError};
 use starknet::core::types::{
     BlockId, ContractClass as ContractClassStarknet, Felt, MaybePendingBlockWithTxHashes,
     StarknetError,
 };
 use starknet::core::utils::parse_cairo_short_string;
-use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::jsonrpc::JsonRpcClientError;
 use starknet::providers::{JsonRpcClient, Provider, ProviderError};
 use starknet_api::block::{BlockNumber, BlockTimestamp};
-use starknet_api::core::{ChainId, ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::core::{
+    ChainId, ClassHash, CompiledClassHash, ContractAddress, Nonce, PatriciaKey,
+};
 use starknet_api::deprecated_contract_class::{
     ContractClass as DeprecatedContractClass, EntryPoint, EntryPointType,
 };
+use starknet_api::hash::StarkHash;
 use starknet_api::state::StorageKey;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::future::Future;
 use std::io::Read;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
 use tokio::runtime::Runtime;
+use tokio::sync::Semaphore;
 use universal_sierra_compiler_api::{compile_sierra, SierraType};
 use url::Url;
 
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+
 #[derive(Debug)]
 pub struct ForkStateReader {
-    client: JsonRpcClient<HttpTransport>,
+    /// Client of the fork url followed by clients of the fallback urls,
+    /// shared with other readers of the same endpoints
+    clients: Vec<Arc<JsonRpcClient<RpcTransport>>>,
+    /// Urls of `clients`, in the same order
+    urls: Vec<Url>,
+    /// Headers sent with every request by all `clients`
+    headers: HashMap<String, String>,
+    /// Index of the client that responded last, requests are sent to it first
+    active_client: Cell<usize>,
     block_number: BlockNumber,
-    runtime: Runtime,
+    runtime: &'static Runtime,
     cache: RefCell<ForkCache>,
+    /// Limiters of requests to `urls`, in the same order
+    requests_limiters: Vec<Arc<Semaphore>>,
+    max_concurrent_requests: usize,
+    /// Artifacts of classes declared on the fork, compiled on the first request
+    contract_artifacts: RefCell<HashMap<ClassHash, StarknetContractArtifacts>>,
 }
 
 impl ForkStateReader {
-    pub fn new(url: Url, block_number: BlockNumber, cache_dir: &Utf8Path) -> Result<Self> {
+    pub fn new(
+        url: Url,
+        block_number: BlockNumber,
+        cache_dir: &Utf8Path,
+        max_concurrent_requests: usize,
+    ) -> Result<Self> {
         Ok(ForkStateReader {
             cache: RefCell::new(
                 ForkCache::load_or_new(&url, block_number, cache_dir)
                     .context("Could not create fork cache")?,
             ),
-            client: JsonRpcClient::new(HttpTransport::new(url)),
+            requests_limiters: vec![requests_limiter_for_url(&url, max_concurrent_requests)],
+            max_concurrent_requests,
+            clients: vec![client_for_url(&url, &HashMap::new())],
+            urls: vec![url],
+            headers: HashMap::new(),
+            active_client: Cell::new(0),
             block_number,
-            runtime: Runtime::new().expect("Could not instantiate Runtime"),
+            runtime: shared_runtime(),
+            contract_artifacts: RefCell::default(),
         })
     }
 
+    /// Add endpoints used in order when the fork url is unreachable.
+    /// Each of them has its own limit of concurrent requests, equal to the one of the fork url
+    #[must_use]
+    pub fn with_fallback_urls(mut self, urls: Vec<Url>) -> Self {
+        self.clients
+            .extend(urls.iter().map(|url| client_for_url(url, &self.headers)));
+        self.requests_limiters.extend(
+            urls.iter()
+                .map(|url| requests_limiter_for_url(url, self.max_concurrent_requests)),
+        );
+        self.urls.extend(urls);
+        self
+    }
+
+    /// Send `headers` with every request to the fork url and the fallback urls
+    #[must_use]
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.clients = self
+            .urls
+            .iter()
+            .map(|url| client_for_url(url, &headers))
+            .collect();
+        self.headers = headers;
+        self
+    }
+
+    /// Number of the block the state is read from
+    #[must_use]
+    pub fn block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
     pub fn chain_id(&self) -> Result<ChainId> {
-        let id = self.runtime.block_on(self.client.chain_id())?;
+        let id = self.request("starknet_chainId", &(), |client| client.chain_id())?;
         let id = parse_cairo_short_string(&id)?;
         Ok(ChainId::from(id))
     }
 
+    /// Fetches class, nonce and given storage of the contract into the fork cache,
+    /// so following reads are served from the cache instead of the RPC node
+    pub fn prefetch(&self, target: ContractAddress, storage_addresses: &[Felt252]) -> Result<()> {
+        self.get_nonce_at(target)?;
+
+        let class_hash = self.get_class_hash_at(target)?;
+        if class_hash != ClassHash::default() {
+            self.get_compiled_contract_class(class_hash)?;
+        }
+
+        for storage_address in storage_addresses {
+            let key = StorageKey(PatriciaKey::try_from(StarkHash::from_(*storage_address))?);
+            self.get_storage_at(target, key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Artifacts of the sierra class declared on the fork under `class_hash`, with casm compiled
+    /// from the fetched class. Later calls for the same class hash reuse the compiled artifacts.
+    pub fn get_contract_artifacts(
+        &self,
+        class_hash: ClassHash,
+    ) -> Result<StarknetContractArtifacts> {
+        if let Some(artifacts) = self.contract_artifacts.borrow().get(&class_hash) {
+            return Ok(artifacts.clone());
+        }
+
+        let ContractClassStarknet::Sierra(flattened_class) = self.get_contract_class(class_hash)?
+        else {
+            bail!(
+                "Class with hash = {} is a Cairo 0 class, only sierra classes can be loaded as artifacts",
+                class_hash.into_hex_string()
+            );
+        };
+
+        let abi: serde_json::Value = serde_json::from_str(&flattened_class.abi)
+            .context("Failed to parse ABI of the class fetched from fork")?;
+        let sierra_contract_class = serde_json::json!({
+            "sierra_program": to_biguint_hex(&flattened_class.sierra_program),
+            "contract_class_version": flattened_class.contract_class_version,
+            "entry_points_by_type": flattened_class.entry_points_by_type,
+            "abi": abi,
+        });
+
+        let artifacts = StarknetContractArtifacts::from_sierra(sierra_contract_class.to_string())
+            .with_context(|| {
+            format!(
+                "Failed to compile class with hash = {} fetched from fork",
+                class_hash.into_hex_string()
+            )
+        })?;
+
+        self.contract_artifacts
+            .borrow_mut()
+            .insert(class_hash, artifacts.clone());
+
+        Ok(artifacts)
+    }
+
+    /// Whether a class with `class_hash` is declared on the fork at the forked block.
+    /// Both positive and negative answers are cached, so only the first check sends a request.
+    pub fn is_class_declared(&self, class_hash: ClassHash) -> Result<bool> {
+        match self.get_contract_class(class_hash) {
+            Ok(_) => Ok(true),
+            Err(UndeclaredClassHash(_)) => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Class declared under `class_hash`, served from the fork cache if it was fetched before
+    fn get_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClassStarknet> {
+        let mut cache = self.cache.borrow_mut();
+
+        if let Some(cache_hit) = cache.get_compiled_contract_class(&class_hash) {
+            log_cache_hit("starknet_getClass", &class_hash);
+            return Ok(cache_hit.clone());
+        }
+        if cache.is_class_hash_undeclared(&class_hash) {
+            log_cache_hit("starknet_getClass", &class_hash);
+            return Err(UndeclaredClassHash(class_hash));
+        }
+
+        match self.request("starknet_getClass", &class_hash, |client| {
+            client.get_class(self.block_id(), Felt::from_(class_hash))
+        }) {
+            Ok(contract_class) => Ok(cache
+                .insert_compiled_contract_class(class_hash, contract_class)
+                .clone()),
+            Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => {
+                cache.cache_undeclared_class_hash(class_hash);
+                Err(UndeclaredClassHash(class_hash))
+            }
+            Err(ProviderError::Other(boxed)) => other_provider_error(boxed),
+            Err(x) => Err(StateReadError(format!(
+                "Unable to get compiled class at {class_hash} from fork ({x})"
+            ))),
+        }
+    }
+
     fn block_id(&self) -> BlockId {
         BlockId::Number(self.block_number.0)
     }
+
+    /// Sends the request to the active client, failing over to the next ones if the endpoint
+    /// is unreachable. Requests beyond the limit of the endpoint wait for a free slot.
+    /// Every attempt is logged at debug level with its `method`, `params` and latency.
+    fn request<'a, T, F, Fut>(
+        &'a self,
+        method: &str,
+        params: &dyn Debug,
+        request: F,
+    ) -> Result<T, ProviderError>
+    where
+        F: Fn(&'a JsonRpcClient<RpcTransport>) -> Fut,
+        Fut: Future<Output = Result<T, ProviderError>>,
+    {
+        let first_client = self.active_client.get();
+        let mut result = None;
+
+        for offset in 0..self.clients.len() {
+            let index = (first_client + offset) % self.clients.len();
+            let started = Instant::now();
+
+            let response = self.runtime.block_on(async {
+                let _permit = self.requests_limiters[index]
+                    .acquire()
+                    .await
+                    .expect("Fork requests limiter should never be closed");
+
+                request(self.clients[index].as_ref()).await
+            });
+
+            tracing::debug!(
+                method,
+                params = ?params,
+                client = index,
+                latency_ms = started.elapsed().as_millis(),
+                success = response.is_ok(),
+                "Fork RPC request"
+            );
+
+            if !is_unreachable_endpoint_error(&response) {
+                self.active_client.set(index);
+                return response;
+            }
+
+            result.get_or_insert(response);
+        }
+
+        result.expect("Fork should have at least one client")
+    }
+}
+
+/// Runtime executing requests of all readers. Connections pooled by shared clients are driven
+/// by it, so it has to outlive a single test.
+fn shared_runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+    RUNTIME.get_or_init(|| Runtime::new().expect("Could not instantiate Runtime"))
+}
+
+/// Returns client shared by all readers of the given endpoint sending the same headers,
+/// so connections opened by one test are reused by the next ones.
+fn client_for_url(
+    url: &Url,
+    headers: &HashMap<String, String>,
+) -> Arc<JsonRpcClient<RpcTransport>> {
+    type ClientKey = (Url, BTreeMap<String, String>);
+    static CLIENTS: OnceLock<Mutex<HashMap<ClientKey, Arc<JsonRpcClient<RpcTransport>>>>> =
+        OnceLock::new();
+
+    let key = (url.clone(), headers.clone().into_iter().collect());
+
+    CLIENTS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("Fork clients lock should not be poisoned")
+        .entry(key)
+        .or_insert_with(|| Arc::new(JsonRpcClient::new(RpcTransport::new(url.clone(), headers))))
+        .clone()
+}
+
+/// Returns limiter shared by all readers of the given endpoint.
+/// If readers set different limits for the endpoint, the lowest one applies to all of them.
+fn requests_limiter_for_url(url: &Url, max_concurrent_requests: usize) -> Arc<Semaphore> {
+    static LIMITERS: OnceLock<Mutex<HashMap<Url, (Arc<Semaphore>, usize)>>> = OnceLock::new();
+
+    let mut limiters = LIMITERS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("Fork requests limiters lock should not be poisoned");
+    let (limiter, limit) = limiters.entry(url.clone()).or_insert_with(|| {
+        (
+            Arc::new(Semaphore::new(max_concurrent_requests)),
+            max_concurrent_requests,
+        )
+    });
+
+    if max_concurrent_requests < *limit {
+        let excess = *limit - max_concurrent_requests;
+        *limit = max_concurrent_requests;
+
+        let forgotten = limiter.forget_permits(excess);
+        if forgotten < excess {
+            // Permits held by requests in flight are removed once they are released
+            let limiter = limiter.clone();
+            let remaining = u32::try_from(excess - forgotten)
+                .expect("Number of concurrent requests should fit in u32");
+            shared_runtime().spawn(async move {
+                if let Ok(permits) = limiter.acquire_many_owned(remaining).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+
+    limiter.clone()
+}
+
+fn log_cache_hit(method: &str, params: &dyn Debug) {
+    tracing::debug!(method, params = ?params, "Fork RPC request served from cache");
+}
+
+fn is_unreachable_endpoint_error<T>(response: &Result<T, ProviderError>) -> bool {
+    match response {
+        Err(ProviderError::RateLimited) => true,
+        Err(ProviderError::Other(error)) => matches!(
+            error
+                .as_any()
+                .downcast_ref::<JsonRpcClientError<RpcTransportError>>(),
+            Some(JsonRpcClientError::TransportError(error)) if error.is_unreachable_endpoint()
+        ),
+        _ => false,
+    }
+}
+
+fn to_biguint_hex(sierra_program: &[Felt]) -> Vec<BigUintAsHex> {
+    sierra_program
+        .iter()
+        .map(|field_element| BigUintAsHex {
+            value: BigUint::from_bytes_be(&field_element.to_bytes_be()),
+        })
+        .collect()
 }
 
 #[allow(clippy::needless_pass_by_value)]
@@ -81,15 +391,17 @@ fn other_provider_error<T>(boxed: impl ToString) -> Result<T, StateError> {
 }
 
 impl BlockInfoReader for ForkStateReader {
-    fn get_block_info(&mut self) -> StateResult<BlockInfo> {
+    fn get_block_info(&self) -> StateResult<BlockInfo> {
         if let Some(cache_hit) = self.cache.borrow().get_block_info() {
+            log_cache_hit("starknet_getBlockWithTxHashes", &self.block_number);
             return Ok(cache_hit);
         }
 
-        match self
-            .runtime
-            .block_on(self.client.get_block_with_tx_hashes(self.block_id()))
-        {
+        match self.request(
+            "starknet_getBlockWithTxHashes",
+            &self.block_number,
+            |client| client.get_block_with_tx_hashes(self.block_id()),
+        ) {
             Ok(MaybePendingBlockWithTxHashes::Block(block)) => {
                 let block_info = BlockInfo {
                     block_number: BlockNumber(block.block_number),
@@ -123,14 +435,17 @@ impl StateReader for ForkStateReader {
         key: StorageKey,
     ) -> StateResult<Felt252> {
         if let Some(cache_hit) = self.cache.borrow().get_storage_at(&contract_address, &key) {
+            log_cache_hit("starknet_getStorageAt", &(contract_address, key));
             return Ok(cache_hit);
         }
 
-        match self.runtime.block_on(self.client.get_storage_at(
-            Felt::from_(contract_address),
-            Felt::from_(*key.0.key()),
-            self.block_id(),
-        )) {
+        match self.request("starknet_getStorageAt", &(contract_address, key), |client| {
+            client.get_storage_at(
+                Felt::from_(contract_address),
+                Felt::from_(*key.0.key()),
+                self.block_id(),
+            )
+        }) {
             Ok(value) => {
                 let value_sf = value.into_();
                 self.cache.borrow_mut()
@@ -147,13 +462,13 @@ impl StateReader for ForkStateReader {
 
     fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
         if let Some(cache_hit) = self.cache.borrow().get_nonce_at(&contract_address) {
+            log_cache_hit("starknet_getNonce", &contract_address);
             return Ok(cache_hit);
         }
 
-        match self.runtime.block_on(
-            self.client
-                .get_nonce(self.block_id(), Felt::from_(contract_address)),
-        ) {
+        match self.request("starknet_getNonce", &contract_address, |client| {
+            client.get_nonce(self.block_id(), Felt::from_(contract_address))
+        }) {
             Ok(nonce) => {
                 let nonce = nonce.into_();
                 self.cache
@@ -173,13 +488,13 @@ impl StateReader for ForkStateReader {
 
     fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
         if let Some(cache_hit) = self.cache.borrow().get_class_hash_at(&contract_address) {
+            log_cache_hit("starknet_getClassHashAt", &contract_address);
             return Ok(cache_hit);
         }
 
-        match self.runtime.block_on(
-            self.client
-                .get_class_hash_at(self.block_id(), Felt::from_(contract_address)),
-        ) {
+        match self.request("starknet_getClassHashAt", &contract_address, |client| {
+            client.get_class_hash_at(self.block_id(), Felt::from_(contract_address))
+        }) {
             Ok(class_hash) => {
                 let class_hash = class_hash.into_();
                 self.cache
@@ -201,42 +516,10 @@ impl StateReader for ForkStateReader {
         &self,
         class_hash: ClassHash,
     ) -> StateResult<ContractClassBlockifier> {
-        let mut cache = self.cache.borrow_mut();
-
-        let contract_class = {
-            if let Some(cache_hit) = cache.get_compiled_contract_class(&class_hash) {
-                Ok(cache_hit)
-            } else {
-                match self.runtime.block_on(
-                    self.client
-                        .get_class(self.block_id(), Felt::from_(class_hash)),
-                ) {
-                    Ok(contract_class) => {
-                        Ok(cache.insert_compiled_contract_class(class_hash, contract_class))
-                    }
-                    Err(ProviderError::StarknetError(StarknetError::ClassHashNotFound)) => {
-                        Err(UndeclaredClassHash(class_hash))
-                    }
-                    Err(ProviderError::Other(boxed)) => other_provider_error(boxed),
-                    Err(x) => Err(StateReadError(format!(
-                        "Unable to get compiled class at {class_hash} from fork ({x})"
-                    ))),
-                }
-            }
-        };
-
-        match contract_class? {
+        match self.get_contract_class(class_hash)? {
             ContractClassStarknet::Sierra(flattened_class) => {
-                let converted_sierra_program: Vec<BigUintAsHex> = flattened_class
-                    .sierra_program
-                    .iter()
-                    .map(|field_element| BigUintAsHex {
-                        value: BigUint::from_bytes_be(&field_element.to_bytes_be()),
-                    })
-                    .collect();
-
                 let sierra_contract_class = serde_json::json!({
-                    "sierra_program": converted_sierra_program,
+                    "sierra_program": to_biguint_hex(&flattened_class.sierra_program),
                     "contract_class_version": "",
                     "entry_points_by_type": flattened_class.entry_points_by_type
                 });
@@ -284,3 +567,34 @@ impl StateReader for ForkStateReader {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_limiter_takes_lowest_limit() {
+        let url = Url::parse("http://limiter.example.com").unwrap();
+
+        let limiter = requests_limiter_for_url(&url, 8);
+        assert_eq!(limiter.available_permits(), 8);
+
+        let same_limiter = requests_limiter_for_url(&url, 4);
+        assert!(Arc::ptr_eq(&limiter, &same_limiter));
+        assert_eq!(limiter.available_permits(), 4);
+
+        requests_limiter_for_url(&url, 6);
+        assert_eq!(limiter.available_permits(), 4);
+    }
+
+    #[test]
+    fn fallback_urls_have_own_limiters() {
+        let url = Url::parse("http://primary.example.com").unwrap();
+        let fallback_url = Url::parse("http://fallback.example.com").unwrap();
+
+        let primary_limiter = requests_limiter_for_url(&url, 2);
+        let fallback_limiter = requests_limiter_for_url(&fallback_url, 2);
+
+        assert!(!Arc::ptr_eq(&primary_limiter, &fallback_limiter));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_messages_to_l1::MessageToL1;
+use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_side_effects::SideEffectIndex;
 use crate::{
     runtime_extensions::forge_runtime_extension::cheatcodes::spy_events::Event,
     state::CheatnetState,
@@ -47,6 +48,9 @@ pub fn emit_event_hook(
 ) {
     let contract_address = syscall_handler.contract_address();
     let last_event = syscall_handler.last_event();
+    cheatnet_state
+        .detected_side_effects
+        .push(SideEffectIndex::Event(cheatnet_state.detected_events.len()));
     cheatnet_state
         .detected_events
         .push(Event::from_ordered_event(last_event, contract_address));
@@ -59,6 +63,11 @@ pub fn send_message_to_l1_syscall_hook(
     let contract_address = syscall_handler.contract_address();
     let last_message = syscall_handler.last_l2_to_l1_message();
 
+    cheatnet_state
+        .detected_side_effects
+        .push(SideEffectIndex::MessageToL1(
+            cheatnet_state.detected_messages_to_l1.len(),
+        ));
     cheatnet_state
         .detected_messages_to_l1
         .push(MessageToL1::from_ordered_message(
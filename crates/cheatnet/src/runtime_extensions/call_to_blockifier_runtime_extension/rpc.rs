@@ -11,7 +11,9 @@ use blockifier::execution::{
     errors::{EntryPointExecutionError, PreExecutionError},
     syscalls::hint_processor::{SyscallCounter, SyscallHintProcessor},
 };
+use blockifier::state::cached_state::CachedState;
 use blockifier::state::errors::StateError;
+use blockifier::state::state_api::StateReader;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use cairo_vm::Felt252;
 use conversions::{
@@ -19,7 +21,7 @@ use conversions::{
 };
 use serde::{Deserialize, Serialize};
 use shared::utils::build_readable_text;
-use starknet_api::{core::EntryPointSelector, transaction::EventContent};
+use starknet_api::{core::EntryPointSelector, state::StorageKey, transaction::EventContent};
 use starknet_api::{
     core::{ClassHash, ContractAddress},
     deprecated_contract_class::EntryPointType,
@@ -168,26 +170,120 @@ pub fn call_l1_handler(
     entry_point_selector: EntryPointSelector,
     calldata: &[Felt252],
 ) -> CallResult {
-    let calldata = create_execute_calldata(calldata);
+    let entry_point = l1_handler_entry_point(contract_address, entry_point_selector, calldata);
 
-    let entry_point = CallEntryPoint {
+    call_entry_point(
+        syscall_handler,
+        cheatnet_state,
+        entry_point,
+        &AddressOrClassHash::ContractAddress(*contract_address),
+    )
+}
+
+/// Same as [`call_l1_handler`], but changes made to the state by the handler are discarded
+/// once it finishes, together with events and messages to L1 it emitted
+pub fn call_l1_handler_isolated(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: &ContractAddress,
+    entry_point_selector: EntryPointSelector,
+    calldata: &[Felt252],
+) -> CallResult {
+    let mut entry_point = l1_handler_entry_point(contract_address, entry_point_selector, calldata);
+    let mut transactional_state = CachedState::create_transactional(&mut *syscall_handler.state);
+    let detected_events_count = cheatnet_state.detected_events.len();
+    let detected_messages_to_l1_count = cheatnet_state.detected_messages_to_l1.len();
+    let detected_side_effects_count = cheatnet_state.detected_side_effects.len();
+
+    let exec_result = execute_call_entry_point(
+        &mut entry_point,
+        &mut transactional_state,
+        cheatnet_state,
+        syscall_handler.resources,
+        syscall_handler.context,
+    );
+    transactional_state.abort();
+    cheatnet_state
+        .detected_events
+        .truncate(detected_events_count);
+    cheatnet_state
+        .detected_messages_to_l1
+        .truncate(detected_messages_to_l1_count);
+    cheatnet_state
+        .detected_side_effects
+        .truncate(detected_side_effects_count);
+
+    CallResult::from_execution_result(
+        &exec_result,
+        &AddressOrClassHash::ContractAddress(*contract_address),
+    )
+}
+
+/// Same as [`call_l1_handler`], but also returns storage slots whose values were changed by the
+/// handler, as `(contract address, storage key, old value, new value)` sorted by address and key
+pub fn call_l1_handler_tracking_storage(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: &ContractAddress,
+    entry_point_selector: EntryPointSelector,
+    calldata: &[Felt252],
+) -> Result<
+    (
+        CallResult,
+        Vec<(ContractAddress, StorageKey, Felt252, Felt252)>,
+    ),
+    StateError,
+> {
+    let mut entry_point = l1_handler_entry_point(contract_address, entry_point_selector, calldata);
+    let mut transactional_state = CachedState::create_transactional(&mut *syscall_handler.state);
+
+    let exec_result = execute_call_entry_point(
+        &mut entry_point,
+        &mut transactional_state,
+        cheatnet_state,
+        syscall_handler.resources,
+        syscall_handler.context,
+    );
+
+    let mut storage_writes = vec![];
+    for (address, storage_updates) in transactional_state.to_state_diff()?.storage_updates {
+        for (key, new_value) in storage_updates {
+            let old_value = transactional_state.state.get_storage_at(address, key)?;
+            storage_writes.push((address, key, old_value, new_value));
+        }
+    }
+    storage_writes.sort_by_key(|(address, key, _, _)| (*address, *key));
+    // Changes are kept regardless of the result, the same as in `call_l1_handler`
+    transactional_state.commit();
+
+    let result = CallResult::from_execution_result(
+        &exec_result,
+        &AddressOrClassHash::ContractAddress(*contract_address),
+    );
+
+    if let Ok(call_info) = exec_result {
+        syscall_handler.inner_calls.push(call_info);
+    };
+
+    Ok((result, storage_writes))
+}
+
+fn l1_handler_entry_point(
+    contract_address: &ContractAddress,
+    entry_point_selector: EntryPointSelector,
+    calldata: &[Felt252],
+) -> CallEntryPoint {
+    CallEntryPoint {
         class_hash: None,
         code_address: Some(*contract_address),
         entry_point_type: EntryPointType::L1Handler,
         entry_point_selector,
-        calldata,
+        calldata: create_execute_calldata(calldata),
         storage_address: *contract_address,
         caller_address: ContractAddress::default(),
         call_type: CallType::Call,
         initial_gas: u64::MAX,
-    };
-
-    call_entry_point(
-        syscall_handler,
-        cheatnet_state,
-        entry_point,
-        &AddressOrClassHash::ContractAddress(*contract_address),
-    )
+    }
 }
 
 pub fn call_entry_point(
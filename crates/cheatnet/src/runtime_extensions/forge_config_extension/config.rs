@@ -36,6 +36,7 @@ pub enum RawForkConfig {
     Inline(InlineForkConfig),
     Named(ByteArray),
     Overridden(OverriddenForkConfig),
+    Multiple(Vec<ByteArray>),
 }
 
 // fuzzer
@@ -5,6 +5,7 @@ use crate::runtime_extensions::call_to_blockifier_runtime_extension::rpc::{
 use anyhow::Result;
 use blockifier::execution::entry_point::ConstructorContext;
 use blockifier::execution::syscalls::hint_processor::SyscallHintProcessor;
+use blockifier::state::errors::StateError;
 use runtime::EnhancedHintError;
 use std::sync::Arc;
 
@@ -17,7 +18,7 @@ use starknet_api::transaction::Calldata;
 
 use super::CheatcodeError;
 use crate::state::CheatnetState;
-use conversions::string::TryFromHexStr;
+use conversions::string::{IntoHexStr, TryFromHexStr};
 
 pub fn deploy_at(
     syscall_handler: &mut SyscallHintProcessor,
@@ -86,3 +87,39 @@ pub fn deploy(
         contract_address,
     )
 }
+
+/// Deploys a contract of a class that is already declared, without declaring it again.
+/// The class is looked up in the state, so classes declared in the test and the ones present
+/// on the fork are handled the same way.
+pub fn deploy_from_class_hash(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    class_hash: &ClassHash,
+    calldata: &[Felt252],
+) -> Result<(ContractAddress, Vec<Felt252>), CheatcodeError> {
+    match syscall_handler
+        .state
+        .get_compiled_contract_class(*class_hash)
+    {
+        Ok(_) => {}
+        Err(StateError::UndeclaredClassHash(_)) => {
+            return Err(CheatcodeError::Unrecoverable(EnhancedHintError::from(
+                CustomHint(Box::from(format!(
+                    "Class with hash = {} is not declared",
+                    class_hash.into_hex_string()
+                ))),
+            )));
+        }
+        Err(err) => return Err(CheatcodeError::Unrecoverable(EnhancedHintError::State(err))),
+    }
+
+    let contract_address = cheatnet_state.precalculate_address(class_hash, calldata);
+
+    deploy_at(
+        syscall_handler,
+        cheatnet_state,
+        class_hash,
+        calldata,
+        contract_address,
+    )
+}
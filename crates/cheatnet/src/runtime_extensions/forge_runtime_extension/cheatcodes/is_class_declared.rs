@@ -0,0 +1,35 @@
+use crate::state::AttachedFork;
+use blockifier::state::errors::StateError;
+use blockifier::state::state_api::StateReader;
+use starknet_api::core::ClassHash;
+
+///
+/// # Arguments
+///
+/// * `state`: State of the test
+/// * `fork`: Fork the test runs on, `None` if the test is not forked
+/// * `class_hash`: Hash of the class to look for
+///
+/// returns: Result<bool, Error> - whether the class is declared, or the error if the read failed
+///
+/// The fork is asked first, its answers (including the negative ones) are cached, so checking
+/// the same class hash again does not send a request. Otherwise, classes declared in the test
+/// itself are looked up, none of the classes is compiled
+pub fn is_class_declared<S: StateReader + ?Sized>(
+    state: &S,
+    fork: Option<&AttachedFork>,
+    class_hash: ClassHash,
+) -> Result<bool, anyhow::Error> {
+    if let Some(fork) = fork {
+        if fork.state_reader.is_class_declared(class_hash)? {
+            return Ok(true);
+        }
+    }
+
+    // the fork reader answers from its cache here, as the class is known not to be on the fork
+    match state.get_compiled_contract_class(class_hash) {
+        Ok(_) => Ok(true),
+        Err(StateError::UndeclaredClassHash(_)) => Ok(false),
+        Err(err) => Err(err.into()),
+    }
+}
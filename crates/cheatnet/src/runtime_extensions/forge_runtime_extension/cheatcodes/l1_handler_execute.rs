@@ -1,11 +1,145 @@
+use super::storage::store;
 use crate::{
-    runtime_extensions::call_to_blockifier_runtime_extension::rpc::{call_l1_handler, CallResult},
-    state::CheatnetState,
+    runtime_extensions::call_to_blockifier_runtime_extension::rpc::{
+        call_l1_handler, call_l1_handler_isolated, call_l1_handler_tracking_storage, CallFailure,
+        CallResult,
+    },
+    state::{CheatSpan, CheatStatus, CheatnetState},
 };
+use anyhow::bail;
+use blockifier::execution::contract_class::ContractClass;
 use blockifier::execution::syscalls::hint_processor::SyscallHintProcessor;
+use blockifier::state::errors::StateError;
+use blockifier::state::state_api::{State, StateReader};
 use cairo_vm::Felt252;
-use starknet_api::core::{ContractAddress, EntryPointSelector};
+use conversions::{
+    serde::{deserialize::CairoDeserialize, serialize::CairoSerialize},
+    string::IntoHexStr,
+    IntoConv,
+};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, EthAddress};
+use starknet_api::deprecated_contract_class::EntryPointType;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+/// Single L1 -> L2 message handled by an L1 handler function
+#[derive(Debug, Clone, CairoDeserialize, PartialEq)]
+pub struct L1Message {
+    pub from_address: Felt252,
+    pub payload: Vec<Felt252>,
+    pub nonce: Option<Felt252>,
+}
+
+/// Block info visible to the L1 handler for the duration of a single call,
+/// fields set to `None` keep the values seen by the contract outside the call
+#[derive(Debug, Clone, Copy, Default, CairoDeserialize, PartialEq)]
+pub struct L1HandlerBlockContext {
+    pub block_number: Option<u64>,
+    pub block_timestamp: Option<u64>,
+}
+
+/// Storage slot whose value was changed by the L1 handler
+#[derive(Debug, Clone, Copy, CairoSerialize, CairoDeserialize, PartialEq)]
+pub struct StorageWrite {
+    pub contract_address: ContractAddress,
+    pub key: Felt252,
+    pub old_value: Felt252,
+    pub new_value: Felt252,
+}
+
+/// L1 -> L2 message waiting to be consumed by an L1 handler, identified by its sender,
+/// recipient, handler selector and payload the same way the Starknet core contract does
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PendingL1Message {
+    pub from_address: Felt252,
+    pub to_address: ContractAddress,
+    pub selector: EntryPointSelector,
+    pub payload: Vec<Felt252>,
+}
+
+/// Registers `message` as sent from L1, so it can be consumed by `l1_handler_execute`.
+/// Once the first message is registered, L1 handlers fail for messages which were not
+pub fn register_l1_message(cheatnet_state: &mut CheatnetState, message: PendingL1Message) {
+    *cheatnet_state
+        .pending_l1_messages
+        .get_or_insert_with(HashMap::new)
+        .entry(message)
+        .or_default() += 1;
+}
+
+/// Number of times `message` was registered and not consumed yet
+#[must_use]
+pub fn pending_l1_message_count(
+    cheatnet_state: &CheatnetState,
+    message: &PendingL1Message,
+) -> usize {
+    cheatnet_state
+        .pending_l1_messages
+        .as_ref()
+        .and_then(|pending_messages| pending_messages.get(message))
+        .copied()
+        .unwrap_or_default()
+}
+
+fn consume_l1_message(cheatnet_state: &mut CheatnetState, message: &PendingL1Message) {
+    if let Some(pending_messages) = cheatnet_state.pending_l1_messages.as_mut() {
+        if let Some(count) = pending_messages.get_mut(message) {
+            *count -= 1;
+            if *count == 0 {
+                pending_messages.remove(message);
+            }
+        }
+    }
+}
+
+/// Shared by all the ways of executing an L1 handler: fails the call when messages are registered
+/// and `message` is not pending, otherwise runs `execute`. Like on Starknet, the message is
+/// consumed only if the handler succeeds, `consume` is unset when changes of the handler are discarded
+fn execute_l1_message<T: Default, E>(
+    cheatnet_state: &mut CheatnetState,
+    message: &PendingL1Message,
+    consume: bool,
+    execute: impl FnOnce(&mut CheatnetState) -> Result<(CallResult, T), E>,
+) -> Result<(CallResult, T), E> {
+    if cheatnet_state.pending_l1_messages.is_some()
+        && pending_l1_message_count(cheatnet_state, message) == 0
+    {
+        let result = CallResult::Failure(CallFailure::Error {
+            msg: format!(
+                "No pending L1 -> L2 message from address = {} to contract at address = {} with selector = {} and the given payload",
+                message.from_address.into_hex_string(),
+                message.to_address.into_hex_string(),
+                message.selector.0.into_hex_string()
+            ),
+        });
+        return Ok((result, T::default()));
+    }
+
+    let (result, output) = execute(cheatnet_state)?;
+
+    if consume && matches!(result, CallResult::Success { .. }) {
+        consume_l1_message(cheatnet_state, message);
+    }
+
+    Ok((result, output))
+}
+
+/// Calldata the L1 handler function is called with: the message sender followed by the payload
+#[must_use]
+pub fn build_l1_handler_calldata(from_address: Felt252, payload: &[Felt252]) -> Vec<Felt252> {
+    let mut calldata = Vec::with_capacity(payload.len() + 1);
+    calldata.push(from_address);
+    calldata.extend_from_slice(payload);
+    calldata
+}
+
+/// Panic data the L1 handler call fails with when the message was marked as rejected
+pub const REJECTED_L1_MESSAGE: &str = "L1 message rejected";
 
+/// Executes the L1 handler for the message. When `rejected` is set, the message is treated as
+/// rejected on Starknet, e.g. for not being paid enough fee on L1, without checking any fee.
+/// The handler is not executed and the call fails
+#[allow(clippy::too_many_arguments)]
 pub fn l1_handler_execute(
     syscall_handler: &mut SyscallHintProcessor,
     cheatnet_state: &mut CheatnetState,
@@ -13,15 +147,317 @@ pub fn l1_handler_execute(
     function_selector: EntryPointSelector,
     from_address: Felt252,
     payload: &[Felt252],
+    nonce: Option<Felt252>,
+    block_context: L1HandlerBlockContext,
+    rejected: bool,
 ) -> CallResult {
-    let mut calldata = vec![from_address];
-    calldata.extend_from_slice(payload);
+    let message = PendingL1Message {
+        from_address,
+        to_address: contract_address,
+        selector: function_selector,
+        payload: payload.to_vec(),
+    };
+
+    execute_l1_message(cheatnet_state, &message, true, |cheatnet_state| {
+        // Message stays pending, as a rejected message is never consumed
+        if rejected {
+            let result = CallResult::Failure(CallFailure::Panic {
+                panic_data: vec![Felt252::from_bytes_be_slice(REJECTED_L1_MESSAGE.as_bytes())],
+            });
+            return Ok::<_, Infallible>((result, ()));
+        }
+
+        let result = call_l1_handler_with_context(
+            syscall_handler,
+            cheatnet_state,
+            contract_address,
+            function_selector,
+            &build_l1_handler_calldata(from_address, payload),
+            nonce,
+            block_context,
+        );
+        Ok((result, ()))
+    })
+    .map_or_else(|never| match never {}, |(result, ())| result)
+}
+
+/// Calls the L1 handler with the nonce and block info exposed to it for this call only
+fn call_l1_handler_with_context(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+    calldata: &[Felt252],
+    nonce: Option<Felt252>,
+    block_context: L1HandlerBlockContext,
+) -> CallResult {
+    // The nonce and block info are exposed to the handler for this call only,
+    // previous cheats are restored afterwards
+    let previous_nonce = nonce.map(|nonce| {
+        let tx_info = &mut cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .tx_info;
 
-    call_l1_handler(
+        std::mem::replace(
+            &mut tx_info.nonce,
+            CheatStatus::Cheated(nonce, CheatSpan::Indefinite),
+        )
+    });
+    let previous_block_number = block_context.block_number.map(|block_number| {
+        let block_info = &mut cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .block_info;
+
+        std::mem::replace(
+            &mut block_info.block_number,
+            CheatStatus::Cheated(block_number, CheatSpan::Indefinite),
+        )
+    });
+    let previous_block_timestamp = block_context.block_timestamp.map(|block_timestamp| {
+        let block_info = &mut cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .block_info;
+
+        std::mem::replace(
+            &mut block_info.block_timestamp,
+            CheatStatus::Cheated(block_timestamp, CheatSpan::Indefinite),
+        )
+    });
+
+    let result = call_l1_handler(
         syscall_handler,
         cheatnet_state,
         &contract_address,
         function_selector,
-        calldata.as_slice(),
+        calldata,
+    );
+
+    if let Some(previous_nonce) = previous_nonce {
+        cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .tx_info
+            .nonce = previous_nonce;
+    }
+    if let Some(previous_block_number) = previous_block_number {
+        cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .block_info
+            .block_number = previous_block_number;
+    }
+    if let Some(previous_block_timestamp) = previous_block_timestamp {
+        cheatnet_state
+            .get_cheated_execution_info_for_contract(contract_address)
+            .block_info
+            .block_timestamp = previous_block_timestamp;
+    }
+
+    result
+}
+
+/// Same as `l1_handler_execute`, but takes the message sender as an Ethereum address,
+/// so it can not be passed as an incorrectly padded felt
+pub fn l1_handler_execute_from_eth_address(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+    from_address: EthAddress,
+    payload: &[Felt252],
+    nonce: Option<Felt252>,
+) -> CallResult {
+    l1_handler_execute(
+        syscall_handler,
+        cheatnet_state,
+        contract_address,
+        function_selector,
+        from_address.into_(),
+        payload,
+        nonce,
+        L1HandlerBlockContext::default(),
+        false,
     )
 }
+
+/// Same as `l1_handler_execute`, but also returns storage slots changed by the handler,
+/// including the ones of contracts it called, sorted by contract address and storage key
+pub fn l1_handler_execute_tracking_storage(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+    from_address: Felt252,
+    payload: &[Felt252],
+) -> Result<(CallResult, Vec<StorageWrite>), StateError> {
+    let message = PendingL1Message {
+        from_address,
+        to_address: contract_address,
+        selector: function_selector,
+        payload: payload.to_vec(),
+    };
+
+    execute_l1_message(cheatnet_state, &message, true, |cheatnet_state| {
+        let (result, storage_writes) = call_l1_handler_tracking_storage(
+            syscall_handler,
+            cheatnet_state,
+            &contract_address,
+            function_selector,
+            &build_l1_handler_calldata(from_address, payload),
+        )?;
+        let storage_writes = storage_writes
+            .into_iter()
+            .map(
+                |(contract_address, key, old_value, new_value)| StorageWrite {
+                    contract_address,
+                    key: *key.0.key(),
+                    old_value,
+                    new_value,
+                },
+            )
+            .collect();
+
+        Ok((result, storage_writes))
+    })
+}
+
+/// Restores values the storage slots had before `storage_writes` were made.
+/// Each changed slot is listed once with its value from before the L1 handler call,
+/// other changes made by the handler, e.g. deployed contracts, are kept
+pub fn rollback_storage_writes(
+    state: &mut dyn State,
+    storage_writes: &[StorageWrite],
+) -> anyhow::Result<()> {
+    for storage_write in storage_writes {
+        store(
+            state,
+            storage_write.contract_address,
+            storage_write.key,
+            storage_write.old_value,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Executes messages one after another, so each message observes state changes made by the
+/// previous ones. When `stop_on_failure` is set, messages after the first failing one are not executed.
+pub fn l1_handler_execute_batch(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+    messages: &[L1Message],
+    stop_on_failure: bool,
+) -> Vec<CallResult> {
+    let mut results = Vec::with_capacity(messages.len());
+
+    for message in messages {
+        let result = l1_handler_execute(
+            syscall_handler,
+            cheatnet_state,
+            contract_address,
+            function_selector,
+            message.from_address,
+            &message.payload,
+            message.nonce,
+            L1HandlerBlockContext::default(),
+            false,
+        );
+        let failed = matches!(result, CallResult::Failure(_));
+
+        results.push(result);
+
+        if failed && stop_on_failure {
+            break;
+        }
+    }
+
+    results
+}
+
+/// Executes the handler once per payload. Each payload is handled on top of the state from before
+/// this call, as changes made to the contracts state by the handler, together with events and
+/// messages to L1 it emitted, are discarded after every run.
+/// For the same reason, the messages have to be pending, but are not consumed.
+pub fn l1_handler_execute_isolated(
+    syscall_handler: &mut SyscallHintProcessor,
+    cheatnet_state: &mut CheatnetState,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+    from_address: Felt252,
+    payloads: &[Vec<Felt252>],
+) -> Vec<CallResult> {
+    payloads
+        .iter()
+        .map(|payload| {
+            let message = PendingL1Message {
+                from_address,
+                to_address: contract_address,
+                selector: function_selector,
+                payload: payload.clone(),
+            };
+
+            execute_l1_message(cheatnet_state, &message, false, |cheatnet_state| {
+                let result = call_l1_handler_isolated(
+                    syscall_handler,
+                    cheatnet_state,
+                    &contract_address,
+                    function_selector,
+                    &build_l1_handler_calldata(from_address, payload),
+                );
+                Ok::<_, Infallible>((result, ()))
+            })
+            .map_or_else(|never| match never {}, |(result, ())| result)
+        })
+        .collect()
+}
+
+/// Selectors of the `#[l1_handler]` functions of the contract deployed at `contract_address`
+pub fn l1_handler_selectors<S: StateReader + ?Sized>(
+    state: &S,
+    contract_address: ContractAddress,
+) -> anyhow::Result<Vec<EntryPointSelector>> {
+    let class_hash = state.get_class_hash_at(contract_address)?;
+    if class_hash == ClassHash::default() {
+        bail!(
+            "No contract is deployed at address = {}",
+            contract_address.into_hex_string()
+        );
+    }
+
+    let selectors = match state.get_compiled_contract_class(class_hash)? {
+        ContractClass::V0(class) => class
+            .entry_points_by_type
+            .get(&EntryPointType::L1Handler)
+            .into_iter()
+            .flatten()
+            .map(|entry_point| entry_point.selector)
+            .collect(),
+        ContractClass::V1(class) => class
+            .entry_points_by_type
+            .get(&EntryPointType::L1Handler)
+            .into_iter()
+            .flatten()
+            .map(|entry_point| entry_point.selector)
+            .collect(),
+    };
+
+    Ok(selectors)
+}
+
+/// Checks that the contract deployed at `contract_address` has an `#[l1_handler]` function
+/// with `function_selector`, so a message can be executed by it
+pub fn validate_l1_handler<S: StateReader + ?Sized>(
+    state: &S,
+    contract_address: ContractAddress,
+    function_selector: EntryPointSelector,
+) -> anyhow::Result<()> {
+    if !l1_handler_selectors(state, contract_address)?.contains(&function_selector) {
+        bail!(
+            "Contract at address = {} has no L1 handler with selector = {}",
+            contract_address.into_hex_string(),
+            function_selector.0.into_hex_string()
+        );
+    }
+
+    Ok(())
+}
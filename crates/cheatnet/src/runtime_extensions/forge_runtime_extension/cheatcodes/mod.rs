@@ -11,12 +11,15 @@ pub mod cheat_sequencer_address;
 pub mod declare;
 pub mod deploy;
 pub mod get_class_hash;
+pub mod is_class_declared;
 pub mod l1_handler_execute;
 pub mod mock_call;
 pub mod precalculate_address;
+pub mod prefetch_fork_state;
 pub mod replace_bytecode;
 pub mod spy_events;
 pub mod spy_messages_to_l1;
+pub mod spy_side_effects;
 pub mod storage;
 
 /// A structure used for returning cheatcode errors in tests
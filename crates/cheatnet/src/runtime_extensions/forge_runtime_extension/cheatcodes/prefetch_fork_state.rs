@@ -0,0 +1,25 @@
+use crate::state::AttachedFork;
+use cairo_vm::Felt252;
+use starknet_api::core::ContractAddress;
+
+///
+/// # Arguments
+///
+/// * `fork`: Fork the test runs against, `None` when the test is not forked
+/// * `target`: The address of the contract whose state should be fetched
+/// * `storage_addresses`: Storage addresses of the values to be fetched
+///
+/// returns: Result<(), Error> - a result containing the error if any of the reads failed
+///
+/// Fetches class, nonce and given storage of the contract into the fork cache.
+/// Does nothing when the test is not forked
+pub fn prefetch_fork_state(
+    fork: Option<&AttachedFork>,
+    target: ContractAddress,
+    storage_addresses: &[Felt252],
+) -> Result<(), anyhow::Error> {
+    match fork {
+        Some(fork) => fork.state_reader.prefetch(target, storage_addresses),
+        None => Ok(()),
+    }
+}
@@ -1,12 +1,16 @@
 use crate::CheatnetState;
 use blockifier::execution::call_info::OrderedEvent;
 use cairo_vm::Felt252;
-use conversions::{serde::serialize::CairoSerialize, FromConv};
+use conversions::string::IntoHexStr;
+use conversions::{
+    serde::{deserialize::CairoDeserialize, serialize::CairoSerialize},
+    FromConv,
+};
 use starknet_api::core::ContractAddress;
 
 /// Represents an emitted event. It is used in the `CheatnetState` to keep track of events
 /// emitted in the `cheatnet::src::rpc::call_contract`
-#[derive(CairoSerialize, Debug, PartialEq, Clone)]
+#[derive(CairoSerialize, CairoDeserialize, Debug, PartialEq, Clone)]
 pub struct Event {
     pub from: ContractAddress,
     pub keys: Vec<Felt252>,
@@ -36,6 +40,23 @@ impl Event {
                 .collect(),
         }
     }
+
+    pub(crate) fn describe(&self) -> String {
+        let join = |felts: &[Felt252]| {
+            felts
+                .iter()
+                .map(|felt| felt.into_hex_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        format!(
+            "from = {}, keys = [{}], data = [{}]",
+            self.from.into_hex_string(),
+            join(&self.keys),
+            join(&self.data)
+        )
+    }
 }
 
 impl CheatnetState {
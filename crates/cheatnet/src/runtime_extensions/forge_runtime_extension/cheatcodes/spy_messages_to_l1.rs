@@ -1,10 +1,12 @@
 use crate::state::CheatnetState;
 use blockifier::execution::call_info::OrderedL2ToL1Message;
+use conversions::serde::deserialize::CairoDeserialize;
 use conversions::serde::serialize::CairoSerialize;
+use conversions::string::IntoHexStr;
 use starknet_api::core::{ContractAddress, EthAddress};
 use starknet_types_core::felt::Felt as Felt252;
 
-#[derive(CairoSerialize, Clone)]
+#[derive(CairoSerialize, CairoDeserialize, Clone, Debug, PartialEq)]
 pub struct MessageToL1 {
     from_address: ContractAddress,
     to_address: EthAddress,
@@ -30,6 +32,21 @@ impl MessageToL1 {
                 .collect(),
         }
     }
+
+    pub(crate) fn describe(&self) -> String {
+        let payload = self
+            .payload
+            .iter()
+            .map(|felt| felt.into_hex_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            "from = {}, to = {}, payload = [{payload}]",
+            self.from_address.into_hex_string(),
+            self.to_address.into_hex_string()
+        )
+    }
 }
 
 impl CheatnetState {
@@ -37,4 +54,39 @@ impl CheatnetState {
     pub fn get_messages_to_l1(&self, message_offset: usize) -> Vec<MessageToL1> {
         self.detected_messages_to_l1[message_offset..].to_vec()
     }
+
+    /// Compares messages sent since `message_offset` with the `expected` ones, regardless of order.
+    /// Returns a description of missing and unexpected messages, `None` if they match exactly.
+    #[must_use]
+    pub fn diff_messages_to_l1(
+        &self,
+        message_offset: usize,
+        expected: &[MessageToL1],
+    ) -> Option<String> {
+        let mut unexpected = self.get_messages_to_l1(message_offset);
+        let mut missing = vec![];
+
+        for message in expected {
+            match unexpected.iter().position(|sent| sent == message) {
+                Some(index) => {
+                    unexpected.remove(index);
+                }
+                None => missing.push(message),
+            }
+        }
+
+        if missing.is_empty() && unexpected.is_empty() {
+            return None;
+        }
+
+        let mut diff = String::from("Sent messages to L1 do not match the expected ones");
+        for message in missing {
+            diff.push_str(&format!("\n- missing: {}", message.describe()));
+        }
+        for message in &unexpected {
+            diff.push_str(&format!("\n+ unexpected: {}", message.describe()));
+        }
+
+        Some(diff)
+    }
 }
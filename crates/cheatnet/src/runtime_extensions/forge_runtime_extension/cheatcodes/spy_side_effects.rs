@@ -0,0 +1,85 @@
+use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_events::Event;
+use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_messages_to_l1::MessageToL1;
+use crate::state::CheatnetState;
+use conversions::serde::deserialize::CairoDeserialize;
+use conversions::serde::serialize::CairoSerialize;
+
+/// Event or message to L1, as seen by the test in the order of emission
+#[derive(CairoSerialize, CairoDeserialize, Clone, Debug, PartialEq)]
+pub enum SideEffect {
+    Event(Event),
+    MessageToL1(MessageToL1),
+}
+
+impl SideEffect {
+    fn describe(&self) -> String {
+        match self {
+            SideEffect::Event(event) => format!("event {}", event.describe()),
+            SideEffect::MessageToL1(message) => format!("message to L1 {}", message.describe()),
+        }
+    }
+}
+
+/// Position of an emitted side effect in `detected_events` or `detected_messages_to_l1`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SideEffectIndex {
+    Event(usize),
+    MessageToL1(usize),
+}
+
+impl CheatnetState {
+    /// Events and messages to L1 emitted since `side_effect_offset`, interleaved in emission order
+    #[must_use]
+    pub fn get_side_effects(&self, side_effect_offset: usize) -> Vec<SideEffect> {
+        self.detected_side_effects[side_effect_offset..]
+            .iter()
+            .map(|index| match *index {
+                SideEffectIndex::Event(index) => {
+                    SideEffect::Event(self.detected_events[index].clone())
+                }
+                SideEffectIndex::MessageToL1(index) => {
+                    SideEffect::MessageToL1(self.detected_messages_to_l1[index].clone())
+                }
+            })
+            .collect()
+    }
+
+    /// Compares side effects emitted since `side_effect_offset` with the `expected` sequence,
+    /// including their order. Returns a description of positions that differ, `None` if they match.
+    #[must_use]
+    pub fn diff_side_effects(
+        &self,
+        side_effect_offset: usize,
+        expected: &[SideEffect],
+    ) -> Option<String> {
+        let emitted = self.get_side_effects(side_effect_offset);
+
+        if emitted == expected {
+            return None;
+        }
+
+        let mut diff = String::from("Emitted side effects do not match the expected sequence");
+        for position in 0..emitted.len().max(expected.len()) {
+            let expected = expected.get(position);
+            let emitted = emitted.get(position);
+
+            if expected == emitted {
+                continue;
+            }
+            if let Some(expected) = expected {
+                diff.push_str(&format!(
+                    "\n- expected at {position}: {}",
+                    expected.describe()
+                ));
+            }
+            if let Some(emitted) = emitted {
+                diff.push_str(&format!(
+                    "\n+ emitted at {position}: {}",
+                    emitted.describe()
+                ));
+            }
+        }
+
+        Some(diff)
+    }
+}
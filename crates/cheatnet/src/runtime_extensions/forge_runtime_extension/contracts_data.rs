@@ -1,10 +1,11 @@
 use super::cheatcodes::declare::get_class_hash;
 use anyhow::Result;
 use bimap::BiMap;
+use cairo_vm::Felt252;
 use camino::Utf8PathBuf;
 use conversions::IntoConv;
-use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
-use scarb_api::StarknetContractArtifacts;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use scarb_api::{entry_point_selectors, StarknetContractArtifacts};
 use starknet::core::types::contract::{AbiEntry, SierraClass};
 use starknet::core::utils::get_selector_from_name;
 use starknet_api::core::{ClassHash, EntryPointSelector};
@@ -31,11 +32,9 @@ impl ContractsData {
     pub fn try_from(
         contracts: HashMap<ContractName, (StarknetContractArtifacts, Utf8PathBuf)>,
     ) -> Result<Self> {
-        let parsed_contracts: HashMap<ContractName, SierraClass> = contracts
+        let parsed_contracts: HashMap<ContractName, &SierraClass> = contracts
             .par_iter()
-            .map(|(name, (artifact, _))| {
-                Ok((name.clone(), serde_json::from_str(&artifact.sierra)?))
-            })
+            .map(|(name, (artifact, _))| Ok((name.clone(), artifact.parse_sierra()?)))
             .collect::<Result<_>>()?;
 
         let class_hashes: Vec<(ContractName, ClassHash)> = parsed_contracts
@@ -44,6 +43,15 @@ impl ContractsData {
             .collect::<Result<_>>()?;
         let class_hashes = BiMap::from_iter(class_hashes);
 
+        let selectors = contracts
+            .par_iter()
+            .map(|(_, (artifacts, _))| entry_point_selectors(artifacts))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .flatten()
+            .map(|(selector, entry_point)| (selector.into_(), entry_point.name))
+            .collect();
+
         let contracts = contracts
             .into_iter()
             .map(|(name, (artifacts, source_sierra_path))| {
@@ -59,12 +67,6 @@ impl ContractsData {
             })
             .collect();
 
-        let selectors = parsed_contracts
-            .into_par_iter()
-            .map(|(_, sierra_class)| build_name_selector_map(sierra_class.abi))
-            .flatten()
-            .collect();
-
         Ok(ContractsData {
             contracts,
             class_hashes,
@@ -105,42 +107,31 @@ impl ContractsData {
     ) -> Option<&FunctionName> {
         self.selectors.get(entry_point_selector)
     }
-}
 
-fn build_name_selector_map(abi: Vec<AbiEntry>) -> HashMap<EntryPointSelector, FunctionName> {
-    let mut selector_map = HashMap::new();
-    for abi_entry in abi {
-        match abi_entry {
-            AbiEntry::Interface(abi_interface) => {
-                for abi_entry in abi_interface.items {
-                    add_simple_abi_entry_to_mapping(abi_entry, &mut selector_map);
-                }
-            }
-            _ => add_simple_abi_entry_to_mapping(abi_entry, &mut selector_map),
-        };
+    /// Names and selectors of `#[l1_handler]` entry points of the contract
+    #[must_use]
+    pub fn get_l1_handlers(&self, contract_name: &str) -> Option<Vec<(FunctionName, Felt252)>> {
+        let sierra_class = self
+            .contracts
+            .get(contract_name)?
+            .artifacts
+            .parse_sierra()
+            .expect("Sierra is parsed when building contracts data");
+
+        Some(l1_handler_entry_points(&sierra_class.abi))
     }
-    selector_map
 }
 
-fn add_simple_abi_entry_to_mapping(
-    abi_entry: AbiEntry,
-    selector_map: &mut HashMap<EntryPointSelector, FunctionName>,
-) {
-    match abi_entry {
-        AbiEntry::Function(abi_function) | AbiEntry::L1Handler(abi_function) => {
-            selector_map.insert(
-                get_selector_from_name(&abi_function.name).unwrap().into_(),
-                abi_function.name,
-            );
-        }
-        AbiEntry::Constructor(abi_constructor) => {
-            selector_map.insert(
-                get_selector_from_name(&abi_constructor.name)
-                    .unwrap()
-                    .into_(),
-                abi_constructor.name,
-            );
-        }
-        _ => {}
-    }
+/// Names and selectors of `#[l1_handler]` entry points found in the `abi`
+#[must_use]
+pub fn l1_handler_entry_points(abi: &[AbiEntry]) -> Vec<(FunctionName, Felt252)> {
+    abi.iter()
+        .filter_map(|abi_entry| match abi_entry {
+            AbiEntry::L1Handler(abi_function) => Some((
+                abi_function.name.clone(),
+                get_selector_from_name(&abi_function.name).unwrap(),
+            )),
+            _ => None,
+        })
+        .collect()
 }
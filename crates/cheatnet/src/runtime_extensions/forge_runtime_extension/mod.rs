@@ -9,9 +9,15 @@ use crate::runtime_extensions::{
     common::{get_relocated_vm_trace, sum_syscall_counters},
     forge_runtime_extension::cheatcodes::{
         declare::declare,
-        deploy::{deploy, deploy_at},
+        deploy::{deploy, deploy_at, deploy_from_class_hash},
         get_class_hash::get_class_hash,
-        l1_handler_execute::l1_handler_execute,
+        is_class_declared::is_class_declared,
+        l1_handler_execute::{
+            l1_handler_execute, l1_handler_execute_batch, l1_handler_execute_isolated,
+            l1_handler_execute_tracking_storage, pending_l1_message_count, register_l1_message,
+            rollback_storage_writes, validate_l1_handler, PendingL1Message,
+        },
+        prefetch_fork_state::prefetch_fork_state,
         storage::{calculate_variable_address, load, store},
         CheatcodeError,
     },
@@ -40,6 +46,7 @@ use conversions::felt252::TryInferFormat;
 use conversions::serde::deserialize::BufferReader;
 use conversions::serde::serialize::CairoSerialize;
 use data_transformer::cairo_types::CairoU256;
+use runtime::starknet::context::build_transaction_context;
 use runtime::{
     CheatcodeHandlingResult, EnhancedHintError, ExtendedRuntime, ExtensionLogic,
     SyscallHandlingResult,
@@ -48,6 +55,7 @@ use starknet::core::types::Felt;
 use starknet::signers::SigningKey;
 use starknet_api::{core::ClassHash, deprecated_contract_class::EntryPointType::L1Handler};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 pub mod cheatcodes;
 pub mod contracts_data;
@@ -190,6 +198,21 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
                     contract_address,
                 ))
             }
+            "deploy_from_class_hash" => {
+                let class_hash = input_reader.read()?;
+                let calldata: Vec<_> = input_reader.read()?;
+                let cheatnet_runtime = &mut extended_runtime.extended_runtime;
+                let syscall_handler = &mut cheatnet_runtime.extended_runtime.hint_handler;
+
+                syscall_handler.increment_syscall_count_by(&DeprecatedSyscallSelector::Deploy, 1);
+
+                handle_declare_deploy_result(deploy_from_class_hash(
+                    syscall_handler,
+                    cheatnet_runtime.extension.cheatnet_state,
+                    &class_hash,
+                    &calldata,
+                ))
+            }
             "precalculate_address" => {
                 let class_hash = input_reader.read()?;
                 let calldata: Vec<_> = input_reader.read()?;
@@ -236,6 +259,9 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
                 let from_address = input_reader.read()?;
 
                 let payload: Vec<_> = input_reader.read()?;
+                let nonce = input_reader.read()?;
+                let block_context = input_reader.read()?;
+                let rejected = input_reader.read()?;
 
                 let cheatnet_runtime = &mut extended_runtime.extended_runtime;
 
@@ -247,6 +273,9 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
                     function_selector,
                     from_address,
                     &payload,
+                    nonce,
+                    block_context,
+                    rejected,
                 ) {
                     CallResult::Success { .. } => {
                         Ok(CheatcodeHandlingResult::from_serializable(0_u8))
@@ -259,6 +288,160 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
                     ),
                 }
             }
+            "l1_handler_register_message" => {
+                let to_address = input_reader.read()?;
+                let selector = input_reader.read()?;
+                let from_address = input_reader.read()?;
+                let payload = input_reader.read()?;
+
+                register_l1_message(
+                    extended_runtime.extended_runtime.extension.cheatnet_state,
+                    PendingL1Message {
+                        from_address,
+                        to_address,
+                        selector,
+                        payload,
+                    },
+                );
+
+                Ok(CheatcodeHandlingResult::from_serializable(()))
+            }
+            "l1_handler_pending_messages" => {
+                let to_address = input_reader.read()?;
+                let selector = input_reader.read()?;
+                let from_address = input_reader.read()?;
+                let payload = input_reader.read()?;
+
+                let count = pending_l1_message_count(
+                    extended_runtime.extended_runtime.extension.cheatnet_state,
+                    &PendingL1Message {
+                        from_address,
+                        to_address,
+                        selector,
+                        payload,
+                    },
+                );
+
+                Ok(CheatcodeHandlingResult::from_serializable(count))
+            }
+            "l1_handler_execute_batch" => {
+                let contract_address = input_reader.read()?;
+                let function_selector = input_reader.read()?;
+                let messages: Vec<_> = input_reader.read()?;
+                let stop_on_failure = input_reader.read()?;
+
+                let cheatnet_runtime = &mut extended_runtime.extended_runtime;
+
+                let syscall_handler = &mut cheatnet_runtime.extended_runtime.hint_handler;
+                let results = l1_handler_execute_batch(
+                    syscall_handler,
+                    cheatnet_runtime.extension.cheatnet_state,
+                    contract_address,
+                    function_selector,
+                    &messages,
+                    stop_on_failure,
+                );
+
+                let results = results
+                    .into_iter()
+                    .map(syscall_result_from_call_result)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(CheatcodeHandlingResult::from_serializable(results))
+            }
+            "l1_handler_execute_isolated" => {
+                let contract_address = input_reader.read()?;
+                let function_selector = input_reader.read()?;
+                let from_address = input_reader.read()?;
+                let payloads: Vec<Vec<_>> = input_reader.read()?;
+
+                let cheatnet_runtime = &mut extended_runtime.extended_runtime;
+
+                let syscall_handler = &mut cheatnet_runtime.extended_runtime.hint_handler;
+                let results = l1_handler_execute_isolated(
+                    syscall_handler,
+                    cheatnet_runtime.extension.cheatnet_state,
+                    contract_address,
+                    function_selector,
+                    from_address,
+                    &payloads,
+                );
+
+                let results = results
+                    .into_iter()
+                    .map(syscall_result_from_call_result)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok(CheatcodeHandlingResult::from_serializable(results))
+            }
+            "l1_handler_execute_tracking_storage" => {
+                let contract_address = input_reader.read()?;
+                let function_selector = input_reader.read()?;
+                let from_address = input_reader.read()?;
+                let payload: Vec<_> = input_reader.read()?;
+
+                let cheatnet_runtime = &mut extended_runtime.extended_runtime;
+
+                let syscall_handler = &mut cheatnet_runtime.extended_runtime.hint_handler;
+                let (result, storage_writes) = l1_handler_execute_tracking_storage(
+                    syscall_handler,
+                    cheatnet_runtime.extension.cheatnet_state,
+                    contract_address,
+                    function_selector,
+                    from_address,
+                    &payload,
+                )?;
+
+                // Storage writes are returned even if the handler failed, the result is shaped
+                // by the callers in snforge_std
+                let result = syscall_result_from_call_result(result)?;
+
+                Ok(CheatcodeHandlingResult::from_serializable((
+                    result,
+                    storage_writes,
+                )))
+            }
+            "rollback_storage_writes" => {
+                let state = &mut extended_runtime
+                    .extended_runtime
+                    .extended_runtime
+                    .hint_handler
+                    .state;
+                let storage_writes: Vec<_> = input_reader.read()?;
+
+                rollback_storage_writes(*state, &storage_writes)
+                    .context("Failed to roll back storage writes")?;
+
+                Ok(CheatcodeHandlingResult::from_serializable(()))
+            }
+            "validate_l1_handler" => {
+                let state = &mut extended_runtime
+                    .extended_runtime
+                    .extended_runtime
+                    .hint_handler
+                    .state;
+                let contract_address = input_reader.read()?;
+                let function_selector = input_reader.read()?;
+
+                let result = validate_l1_handler(*state, contract_address, function_selector)
+                    .map_err(|err| ByteArray::from(err.to_string().as_str()));
+
+                Ok(CheatcodeHandlingResult::from_serializable(result))
+            }
+            "fork_block_number" => {
+                // the block of the fork currently selected with `select_fork`
+                let fork_block_number = extended_runtime
+                    .extended_runtime
+                    .extension
+                    .cheatnet_state
+                    .active_fork
+                    .fork()
+                    .map(|fork| fork.state_reader.block_number().0);
+
+                Ok(CheatcodeHandlingResult::from_serializable(
+                    fork_block_number,
+                ))
+            }
             "read_txt" => {
                 let file_path: String = input_reader.read::<ByteArray>()?.into();
                 let parsed_content = file_operations::read_txt(file_path)?;
@@ -313,6 +496,63 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
 
                 Ok(CheatcodeHandlingResult::from_serializable(messages))
             }
+            "assert_messages_to_l1_sent_exactly" => {
+                let messages_offset = input_reader.read()?;
+                let expected_messages: Vec<_> = input_reader.read()?;
+
+                let diff = extended_runtime
+                    .extended_runtime
+                    .extension
+                    .cheatnet_state
+                    .diff_messages_to_l1(messages_offset, &expected_messages);
+
+                let result = match diff {
+                    Some(diff) => Err(ByteArray::from(diff.as_str())),
+                    None => Ok(()),
+                };
+
+                Ok(CheatcodeHandlingResult::from_serializable(result))
+            }
+            "spy_side_effects" => {
+                let side_effects_offset = extended_runtime
+                    .extended_runtime
+                    .extension
+                    .cheatnet_state
+                    .detected_side_effects
+                    .len();
+
+                Ok(CheatcodeHandlingResult::from_serializable(
+                    side_effects_offset,
+                ))
+            }
+            "get_side_effects" => {
+                let side_effects_offset = input_reader.read()?;
+
+                let side_effects = extended_runtime
+                    .extended_runtime
+                    .extension
+                    .cheatnet_state
+                    .get_side_effects(side_effects_offset);
+
+                Ok(CheatcodeHandlingResult::from_serializable(side_effects))
+            }
+            "assert_side_effects_in_order" => {
+                let side_effects_offset = input_reader.read()?;
+                let expected_side_effects: Vec<_> = input_reader.read()?;
+
+                let diff = extended_runtime
+                    .extended_runtime
+                    .extension
+                    .cheatnet_state
+                    .diff_side_effects(side_effects_offset, &expected_side_effects);
+
+                let result = match diff {
+                    Some(diff) => Err(ByteArray::from(diff.as_str())),
+                    None => Ok(()),
+                };
+
+                Ok(CheatcodeHandlingResult::from_serializable(result))
+            }
             "generate_stark_keys" => {
                 let key_pair = SigningKey::from_random();
 
@@ -464,6 +704,65 @@ impl<'a> ExtensionLogic for ForgeExtension<'a> {
 
                 Ok(CheatcodeHandlingResult::from_serializable(loaded))
             }
+            "select_fork" => {
+                let name: String = input_reader.read::<ByteArray>()?.into();
+
+                let cheatnet_state =
+                    &mut extended_runtime.extended_runtime.extension.cheatnet_state;
+                let selected = cheatnet_state
+                    .active_fork
+                    .select(&name)
+                    .map(|fork| (fork.block_info.clone(), fork.chain_id.clone()));
+
+                let result = match selected {
+                    Ok((block_info, chain_id)) => {
+                        // the test runs with block info and chain id of the selected fork
+                        extended_runtime
+                            .extended_runtime
+                            .extended_runtime
+                            .hint_handler
+                            .context
+                            .tx_context =
+                            Arc::new(build_transaction_context(&block_info, Some(chain_id)));
+                        cheatnet_state.block_info = block_info;
+                        Ok(())
+                    }
+                    Err(err) => Err(ByteArray::from(err.as_str())),
+                };
+
+                Ok(CheatcodeHandlingResult::from_serializable(result))
+            }
+            "prefetch_fork_state" => {
+                let target = input_reader.read()?;
+                let storage_addresses: Vec<_> = input_reader.read()?;
+
+                prefetch_fork_state(
+                    extended_runtime
+                        .extended_runtime
+                        .extension
+                        .cheatnet_state
+                        .active_fork
+                        .fork(),
+                    target,
+                    &storage_addresses,
+                )
+                .context("Failed to prefetch fork state")?;
+
+                Ok(CheatcodeHandlingResult::from_serializable(()))
+            }
+            "is_class_declared" => {
+                let cheatnet_runtime = &mut extended_runtime.extended_runtime;
+                let class_hash = input_reader.read()?;
+
+                let is_declared = is_class_declared(
+                    cheatnet_runtime.extended_runtime.hint_handler.state,
+                    cheatnet_runtime.extension.cheatnet_state.active_fork.fork(),
+                    class_hash,
+                )
+                .context("Failed to check if the class is declared")?;
+
+                Ok(CheatcodeHandlingResult::from_serializable(is_declared))
+            }
             "map_entry_address" => {
                 let map_selector = input_reader.read()?;
                 let keys: Vec<_> = input_reader.read()?;
@@ -510,6 +809,20 @@ fn handle_declare_deploy_result<T: CairoSerialize>(
     Ok(CheatcodeHandlingResult::from_serializable(result))
 }
 
+/// Maps the result of a call to the `SyscallResult<()>` returned to the test,
+/// errors other than panics fail the test
+fn syscall_result_from_call_result(
+    result: CallResult,
+) -> Result<Result<(), Vec<Felt252>>, EnhancedHintError> {
+    match result {
+        CallResult::Success { .. } => Ok(Ok(())),
+        CallResult::Failure(CallFailure::Panic { panic_data }) => Ok(Err(panic_data)),
+        CallResult::Failure(CallFailure::Error { msg }) => Err(EnhancedHintError::from(
+            HintError::CustomHint(Box::from(msg)),
+        )),
+    }
+}
+
 pub fn update_top_call_execution_resources(runtime: &mut ForgeRuntime) {
     let all_execution_resources = runtime
         .extended_runtime
@@ -1,19 +1,22 @@
-use crate::constants::{build_test_entry_point, TEST_CONTRACT_CLASS_HASH};
+use crate::constants::{build_test_entry_point, build_testing_state, TEST_CONTRACT_CLASS_HASH};
 use crate::forking::state::ForkStateReader;
 use crate::runtime_extensions::call_to_blockifier_runtime_extension::rpc::CallResult;
 use crate::runtime_extensions::forge_runtime_extension::cheatcodes::cheat_execution_info::{
     ExecutionInfoMock, ResourceBounds,
 };
+use crate::runtime_extensions::forge_runtime_extension::cheatcodes::l1_handler_execute::PendingL1Message;
 use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_events::Event;
 use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_messages_to_l1::MessageToL1;
+use crate::runtime_extensions::forge_runtime_extension::cheatcodes::spy_side_effects::SideEffectIndex;
 use blockifier::blockifier::block::BlockInfo;
 use blockifier::execution::call_info::OrderedL2ToL1Message;
 use blockifier::execution::entry_point::CallEntryPoint;
 use blockifier::execution::syscalls::hint_processor::SyscallCounter;
+use blockifier::state::cached_state::CachedState;
 use blockifier::state::errors::StateError::UndeclaredClassHash;
 use blockifier::{
     execution::contract_class::ContractClass,
-    state::state_api::{StateReader, StateResult},
+    state::state_api::{State, StateReader, StateResult},
 };
 use cairo_annotations::trace_data::L1Resources;
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
@@ -24,6 +27,7 @@ use conversions::serde::serialize::{BufferWriter, CairoSerialize};
 use conversions::string::TryFromHexStr;
 use runtime::starknet::context::SerializableBlockInfo;
 use runtime::starknet::state::DictStateReader;
+use scarb_api::StarknetContractArtifacts;
 use starknet_api::core::{ChainId, EntryPointSelector};
 use starknet_api::transaction::ContractAddressSalt;
 use starknet_api::{
@@ -31,7 +35,7 @@ use starknet_api::{
     state::StorageKey,
 };
 use std::cell::{Ref, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 // Specifies the duration of the cheat
@@ -44,16 +48,243 @@ pub enum CheatSpan {
 #[derive(Debug)]
 pub struct ExtendedStateReader {
     pub dict_state_reader: DictStateReader,
-    pub fork_state_reader: Option<ForkStateReader>,
+    pub fork_state_reader: Option<Rc<ForkStateReader>>,
+}
+
+/// Fork attached to the test, with block info and chain id the test runs with while it is selected
+#[derive(Debug, Clone)]
+pub struct AttachedFork {
+    pub state_reader: Rc<ForkStateReader>,
+    pub block_info: BlockInfo,
+    pub chain_id: ChainId,
+}
+
+impl AttachedFork {
+    pub fn new(state_reader: ForkStateReader) -> anyhow::Result<Self> {
+        let block_info = state_reader.get_block_info()?;
+        let chain_id = state_reader.chain_id()?;
+
+        Ok(Self {
+            state_reader: Rc::new(state_reader),
+            block_info,
+            chain_id,
+        })
+    }
+}
+
+/// Forks attached to the test and the one selected with the `select_fork` cheatcode,
+/// shared by `ForkedState` and `CheatnetState`.
+/// When no other fork is selected, the test runs on the fork it has started with
+#[derive(Debug, Clone, Default)]
+pub struct ActiveFork {
+    selected: Rc<RefCell<Option<String>>>,
+    primary: Option<AttachedFork>,
+    primary_name: Option<String>,
+    additional: Rc<Vec<(String, AttachedFork)>>,
+}
+
+impl ActiveFork {
+    /// Forks of a test forked from `primary` only
+    #[must_use]
+    pub fn new(primary: AttachedFork) -> Self {
+        Self {
+            primary: Some(primary),
+            ..Default::default()
+        }
+    }
+
+    /// Forks of a test that starts on the `primary` named fork and can select any of `additional`
+    #[must_use]
+    pub fn with_selectable_forks(
+        (primary_name, primary): (String, AttachedFork),
+        additional: Vec<(String, AttachedFork)>,
+    ) -> Self {
+        Self {
+            selected: Rc::default(),
+            primary: Some(primary),
+            primary_name: Some(primary_name),
+            additional: Rc::new(additional),
+        }
+    }
+
+    /// Name of the selected fork, `None` if it is the one the test has started with
+    #[must_use]
+    pub fn selected(&self) -> Option<String> {
+        self.selected.borrow().clone()
+    }
+
+    /// Fork the test runs on, `None` if the test is not forked
+    #[must_use]
+    pub fn fork(&self) -> Option<&AttachedFork> {
+        match self.selected() {
+            Some(name) => self
+                .additional
+                .iter()
+                .find_map(|(fork_name, fork)| (*fork_name == name).then_some(fork)),
+            None => self.primary.as_ref(),
+        }
+    }
+
+    pub fn select(&self, name: &str) -> Result<&AttachedFork, String> {
+        if self.primary_name.as_deref() == Some(name) {
+            *self.selected.borrow_mut() = None;
+        } else if self
+            .additional
+            .iter()
+            .any(|(fork_name, _)| fork_name == name)
+        {
+            *self.selected.borrow_mut() = Some(name.to_string());
+        } else {
+            let attached: Vec<_> = self
+                .primary_name
+                .iter()
+                .chain(self.additional.iter().map(|(fork_name, _)| fork_name))
+                .map(String::as_str)
+                .collect();
+
+            return Err(format!(
+                "Fork = {name} is not attached to the test, attached forks = [{}]",
+                attached.join(", ")
+            ));
+        }
+
+        Ok(self.fork().expect("Selected fork is attached to the test"))
+    }
+}
+
+/// State of the test kept separately for every fork attached to it, so contracts deployed and
+/// values written or read on one fork are not visible on the others.
+/// Reads and writes go to the state of the fork selected with the `select_fork` cheatcode
+pub struct ForkedState {
+    primary: CachedState<ExtendedStateReader>,
+    additional: HashMap<String, CachedState<ExtendedStateReader>>,
+    active_fork: ActiveFork,
+}
+
+impl ForkedState {
+    #[must_use]
+    pub fn new(active_fork: ActiveFork) -> Self {
+        let state_of = |fork: Option<&AttachedFork>| {
+            CachedState::new(ExtendedStateReader {
+                dict_state_reader: build_testing_state(),
+                fork_state_reader: fork.map(|fork| fork.state_reader.clone()),
+            })
+        };
+
+        Self {
+            primary: state_of(active_fork.primary.as_ref()),
+            additional: active_fork
+                .additional
+                .iter()
+                .map(|(name, fork)| (name.clone(), state_of(Some(fork))))
+                .collect(),
+            active_fork,
+        }
+    }
+
+    /// States of all forks attached to the test
+    pub fn states_mut(&mut self) -> impl Iterator<Item = &mut CachedState<ExtendedStateReader>> {
+        std::iter::once(&mut self.primary).chain(self.additional.values_mut())
+    }
+
+    fn active_state(&self) -> &CachedState<ExtendedStateReader> {
+        match self.active_fork.selected() {
+            Some(name) => &self.additional[&name],
+            None => &self.primary,
+        }
+    }
+
+    fn active_state_mut(&mut self) -> &mut CachedState<ExtendedStateReader> {
+        match self.active_fork.selected() {
+            Some(name) => self
+                .additional
+                .get_mut(&name)
+                .expect("Selected fork is attached to the test"),
+            None => &mut self.primary,
+        }
+    }
+}
+
+impl StateReader for ForkedState {
+    fn get_storage_at(
+        &self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+    ) -> StateResult<Felt252> {
+        self.active_state().get_storage_at(contract_address, key)
+    }
+
+    fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        self.active_state().get_nonce_at(contract_address)
+    }
+
+    fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        self.active_state().get_class_hash_at(contract_address)
+    }
+
+    fn get_compiled_contract_class(&self, class_hash: ClassHash) -> StateResult<ContractClass> {
+        self.active_state().get_compiled_contract_class(class_hash)
+    }
+
+    fn get_compiled_class_hash(&self, class_hash: ClassHash) -> StateResult<CompiledClassHash> {
+        self.active_state().get_compiled_class_hash(class_hash)
+    }
+}
+
+impl State for ForkedState {
+    fn set_storage_at(
+        &mut self,
+        contract_address: ContractAddress,
+        key: StorageKey,
+        value: Felt252,
+    ) -> StateResult<()> {
+        self.active_state_mut()
+            .set_storage_at(contract_address, key, value)
+    }
+
+    fn increment_nonce(&mut self, contract_address: ContractAddress) -> StateResult<()> {
+        self.active_state_mut().increment_nonce(contract_address)
+    }
+
+    fn set_class_hash_at(
+        &mut self,
+        contract_address: ContractAddress,
+        class_hash: ClassHash,
+    ) -> StateResult<()> {
+        self.active_state_mut()
+            .set_class_hash_at(contract_address, class_hash)
+    }
+
+    fn set_contract_class(
+        &mut self,
+        class_hash: ClassHash,
+        contract_class: ContractClass,
+    ) -> StateResult<()> {
+        self.active_state_mut()
+            .set_contract_class(class_hash, contract_class)
+    }
+
+    fn set_compiled_class_hash(
+        &mut self,
+        class_hash: ClassHash,
+        compiled_class_hash: CompiledClassHash,
+    ) -> StateResult<()> {
+        self.active_state_mut()
+            .set_compiled_class_hash(class_hash, compiled_class_hash)
+    }
+
+    fn add_visited_pcs(&mut self, class_hash: ClassHash, pcs: &HashSet<usize>) {
+        self.active_state_mut().add_visited_pcs(class_hash, pcs);
+    }
 }
 
 pub trait BlockInfoReader {
-    fn get_block_info(&mut self) -> StateResult<BlockInfo>;
+    fn get_block_info(&self) -> StateResult<BlockInfo>;
 }
 
 impl BlockInfoReader for ExtendedStateReader {
-    fn get_block_info(&mut self) -> StateResult<BlockInfo> {
-        if let Some(ref mut fork_state_reader) = self.fork_state_reader {
+    fn get_block_info(&self) -> StateResult<BlockInfo> {
+        if let Some(fork_state_reader) = &self.fork_state_reader {
             return fork_state_reader.get_block_info();
         }
 
@@ -129,6 +360,30 @@ impl ExtendedStateReader {
             .map(ForkStateReader::chain_id)
             .transpose()
     }
+
+    /// Artifacts of the sierra class declared under `class_hash` on the fork the test reads state from
+    pub fn get_fork_contract_artifacts(
+        &self,
+        class_hash: ClassHash,
+    ) -> anyhow::Result<StarknetContractArtifacts> {
+        self.fork_state_reader
+            .as_ref()
+            .ok_or_else(|| {
+                anyhow::anyhow!("Contract artifacts can only be fetched in a forked test")
+            })?
+            .get_contract_artifacts(class_hash)
+    }
+
+    /// Whether a class with `class_hash` is declared on the fork the test reads state from.
+    /// Classes declared in the test itself are not taken into account.
+    pub fn is_class_declared_on_fork(&self, class_hash: ClassHash) -> anyhow::Result<bool> {
+        self.fork_state_reader
+            .as_ref()
+            .ok_or_else(|| {
+                anyhow::anyhow!("Declared classes can only be checked in a forked test")
+            })?
+            .is_class_declared(class_hash)
+    }
 }
 
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
@@ -329,9 +584,16 @@ pub struct CheatnetState {
     pub replaced_bytecode_contracts: HashMap<ContractAddress, ClassHash>,
     pub detected_events: Vec<Event>,
     pub detected_messages_to_l1: Vec<MessageToL1>,
+    /// Events and messages to L1 in the order they were emitted, shared by both so their
+    /// relative order is preserved
+    pub detected_side_effects: Vec<SideEffectIndex>,
+    /// Registered L1 -> L2 messages not consumed yet with their counts,
+    /// `None` until the first message is registered
+    pub pending_l1_messages: Option<HashMap<PendingL1Message, usize>>,
     pub deploy_salt_base: u32,
     pub block_info: BlockInfo,
     pub trace_data: TraceData,
+    pub active_fork: ActiveFork,
 }
 
 impl Default for CheatnetState {
@@ -351,12 +613,15 @@ impl Default for CheatnetState {
             replaced_bytecode_contracts: Default::default(),
             detected_events: vec![],
             detected_messages_to_l1: vec![],
+            detected_side_effects: vec![],
+            pending_l1_messages: None,
             deploy_salt_base: 0,
             block_info: SerializableBlockInfo::default().into(),
             trace_data: TraceData {
                 current_call_stack: NotEmptyCallStack::from(test_call),
                 is_vm_trace_needed: false,
             },
+            active_fork: ActiveFork::default(),
         }
     }
 }
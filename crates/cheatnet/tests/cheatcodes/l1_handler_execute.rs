@@ -0,0 +1,60 @@
+use cairo_vm::Felt252;
+use cheatnet::runtime_extensions::forge_runtime_extension::cheatcodes::l1_handler_execute::build_l1_handler_calldata;
+use cheatnet::runtime_extensions::forge_runtime_extension::contracts_data::l1_handler_entry_points;
+use starknet::core::types::contract::AbiEntry;
+use starknet::core::utils::get_selector_from_name;
+
+#[test]
+fn l1_handler_calldata_starts_with_sender() {
+    let payload = [Felt252::from(1), Felt252::from(2)];
+
+    let calldata = build_l1_handler_calldata(Felt252::from(123), &payload);
+
+    assert_eq!(
+        calldata,
+        vec![Felt252::from(123), Felt252::from(1), Felt252::from(2)]
+    );
+}
+
+#[test]
+fn l1_handler_calldata_with_empty_payload() {
+    let calldata = build_l1_handler_calldata(Felt252::from(123), &[]);
+
+    assert_eq!(calldata, vec![Felt252::from(123)]);
+}
+
+#[test]
+fn l1_handler_entry_points_from_abi() {
+    let abi: Vec<AbiEntry> = serde_json::from_str(
+        r#"[
+            {
+                "type": "function",
+                "name": "get_balance",
+                "inputs": [],
+                "outputs": [{ "type": "core::felt252" }],
+                "state_mutability": "view"
+            },
+            {
+                "type": "l1_handler",
+                "name": "process_l1_message",
+                "inputs": [
+                    { "name": "from_address", "type": "core::felt252" },
+                    { "name": "data", "type": "core::array::Span::<core::felt252>" }
+                ],
+                "outputs": [],
+                "state_mutability": "external"
+            }
+        ]"#,
+    )
+    .unwrap();
+
+    let l1_handlers = l1_handler_entry_points(&abi);
+
+    assert_eq!(
+        l1_handlers,
+        vec![(
+            "process_l1_message".to_string(),
+            get_selector_from_name("process_l1_message").unwrap()
+        )]
+    );
+}
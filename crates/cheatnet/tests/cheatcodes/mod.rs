@@ -13,6 +13,7 @@ mod cheat_sequencer_address;
 mod declare;
 mod deploy;
 mod get_class_hash;
+mod l1_handler_execute;
 mod load;
 mod mock_call;
 mod precalculate_address;
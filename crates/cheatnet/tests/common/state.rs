@@ -1,9 +1,10 @@
 use blockifier::state::cached_state::CachedState;
 use cheatnet::constants::build_testing_state;
-use cheatnet::forking::state::ForkStateReader;
+use cheatnet::forking::state::{ForkStateReader, DEFAULT_MAX_CONCURRENT_REQUESTS};
 use cheatnet::state::ExtendedStateReader;
 use shared::test_utils::node_url::node_rpc_url;
 use starknet_api::block::BlockNumber;
+use std::rc::Rc;
 
 pub fn create_cached_state() -> CachedState<ExtendedStateReader> {
     CachedState::new(ExtendedStateReader {
@@ -23,8 +24,14 @@ pub fn create_fork_cached_state_at(
     let node_url = node_rpc_url();
     CachedState::new(ExtendedStateReader {
         dict_state_reader: build_testing_state(),
-        fork_state_reader: Some(
-            ForkStateReader::new(node_url, BlockNumber(block_number), cache_dir.into()).unwrap(),
-        ),
+        fork_state_reader: Some(Rc::new(
+            ForkStateReader::new(
+                node_url,
+                BlockNumber(block_number),
+                cache_dir.into(),
+                DEFAULT_MAX_CONCURRENT_REQUESTS,
+            )
+            .unwrap(),
+        )),
     })
 }
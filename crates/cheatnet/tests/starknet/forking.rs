@@ -7,7 +7,10 @@ use cairo_vm::vm::errors::hint_errors::HintError;
 use cairo_vm::Felt252;
 use camino::Utf8Path;
 use cheatnet::constants::build_testing_state;
-use cheatnet::forking::{cache::CACHE_VERSION, state::ForkStateReader};
+use cheatnet::forking::{
+    cache::CACHE_VERSION,
+    state::{ForkStateReader, DEFAULT_MAX_CONCURRENT_REQUESTS},
+};
 use cheatnet::runtime_extensions::forge_runtime_extension::cheatcodes::CheatcodeError;
 use cheatnet::state::{BlockInfoReader, CheatnetState, ExtendedStateReader};
 use conversions::byte_array::ByteArray;
@@ -17,7 +20,8 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use runtime::EnhancedHintError;
 use serde_json::Value;
 use starknet_api::block::BlockNumber;
-use starknet_api::core::ContractAddress;
+use starknet_api::core::{ClassHash, ContractAddress};
+use std::rc::Rc;
 use tempfile::TempDir;
 
 #[test]
@@ -177,6 +181,66 @@ fn call_forked_contract_from_other_contract() {
     assert_success(output, &[Felt252::from(0)]);
 }
 
+#[test]
+fn fetch_contract_artifacts_of_forked_class_hash() {
+    let cache_dir = TempDir::new().unwrap();
+    let cached_fork_state = create_fork_cached_state(cache_dir.path().to_str().unwrap());
+
+    let forked_class_hash = ClassHash::try_from_hex_str(
+        "0x06a7eb29ee38b0a0b198e39ed6ad458d2e460264b463351a0acfc05822d61550",
+    )
+    .unwrap();
+
+    let artifacts = cached_fork_state
+        .state
+        .get_fork_contract_artifacts(forked_class_hash)
+        .unwrap();
+
+    assert!(!artifacts.casm.is_empty());
+    assert_ne!(artifacts.abi().unwrap(), "[]");
+
+    let cached_artifacts = cached_fork_state
+        .state
+        .get_fork_contract_artifacts(forked_class_hash)
+        .unwrap();
+    assert_eq!(artifacts, cached_artifacts);
+}
+
+#[test]
+fn check_if_class_is_declared_on_fork() {
+    let cache_dir = TempDir::new().unwrap();
+    let cached_fork_state = create_fork_cached_state(cache_dir.path().to_str().unwrap());
+
+    let forked_class_hash = ClassHash::try_from_hex_str(
+        "0x06a7eb29ee38b0a0b198e39ed6ad458d2e460264b463351a0acfc05822d61550",
+    )
+    .unwrap();
+    let undeclared_class_hash = ClassHash::try_from_hex_str("0x1234").unwrap();
+
+    assert!(cached_fork_state
+        .state
+        .is_class_declared_on_fork(forked_class_hash)
+        .unwrap());
+    assert!(!cached_fork_state
+        .state
+        .is_class_declared_on_fork(undeclared_class_hash)
+        .unwrap());
+
+    drop(cached_fork_state);
+
+    let cache = read_cache(
+        cache_dir
+            .path()
+            .join(format!("*v{CACHE_VERSION}.json"))
+            .to_str()
+            .unwrap(),
+    );
+    assert_eq!(
+        cache["undeclared_class_hashes"],
+        serde_json::json!([undeclared_class_hash])
+    );
+}
+
 #[test]
 fn library_call_on_forked_class_hash() {
     let cache_dir = TempDir::new().unwrap();
@@ -645,14 +709,15 @@ fn test_calling_nonexistent_url() {
     let nonexistent_url = "http://nonexistent-node-address.com".parse().unwrap();
     let mut cached_fork_state = CachedState::new(ExtendedStateReader {
         dict_state_reader: build_testing_state(),
-        fork_state_reader: Some(
+        fork_state_reader: Some(Rc::new(
             ForkStateReader::new(
                 nonexistent_url,
                 BlockNumber(1),
                 Utf8Path::from_path(temp_dir.path()).unwrap(),
+                DEFAULT_MAX_CONCURRENT_REQUESTS,
             )
             .unwrap(),
-        ),
+        )),
     });
 
     let mut cheatnet_state = CheatnetState::default();
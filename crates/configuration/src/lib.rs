@@ -3,7 +3,7 @@ use scarb_metadata::{Metadata, PackageId};
 use serde_json::Number;
 use std::{env, fs};
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use tempfile::{tempdir, TempDir};
 use toml::Value;
 pub const CONFIG_FILENAME: &str = "snfoundry.toml";
@@ -28,6 +28,13 @@ pub trait PackageConfig {
     fn from_raw(config: &serde_json::Value) -> Result<Self>
     where
         Self: Sized;
+
+    /// Whether `${VAR}` references in the string under `path` of the tool config are left as is,
+    /// because the tool interpolates them itself. `path` lists object keys and array indices
+    #[must_use]
+    fn interpolates_env_variables(_path: &[String]) -> bool {
+        false
+    }
 }
 
 fn get_with_ownership(config: serde_json::Value, key: &str) -> Option<serde_json::Value> {
@@ -73,7 +80,7 @@ pub fn load_global_config<T: GlobalConfig + Default>(
                 .context("Conversion from TOML value to JSON value should not fail.")?;
 
             let profile = get_profile(raw_config_json, T::tool_name(), profile)?;
-            T::from_raw(resolve_env_variables(profile)?)
+            T::from_raw(resolve_env_variables(profile, &mut vec![], &|_| false)?)
         }
         None => Ok(T::default()),
     }
@@ -92,18 +99,57 @@ pub fn load_package_config<T: PackageConfig + Default>(
         .tool_metadata(T::tool_name())
         .cloned();
     match maybe_raw_metadata {
-        Some(raw_metadata) => T::from_raw(&resolve_env_variables(raw_metadata)?),
+        Some(raw_metadata) => T::from_raw(&resolve_env_variables(
+            raw_metadata,
+            &mut vec![],
+            &T::interpolates_env_variables,
+        )?),
         None => Ok(T::default()),
     }
 }
 
-fn resolve_env_variables(config: serde_json::Value) -> Result<serde_json::Value> {
+/// Loads config from the `[tool]` section of the `Scarb.toml` file at `manifest_path`.
+/// Unlike [`load_package_config`], it does not need package metadata, so it also reads the
+/// config of a virtual workspace root, which is not a package
+pub fn load_manifest_tool_config<T: PackageConfig + Default>(
+    manifest_path: &Utf8Path,
+) -> Result<T> {
+    let raw_manifest_toml = fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read {manifest_path}"))?
+        .parse::<Value>()
+        .with_context(|| format!("Failed to parse {manifest_path}"))?;
+
+    let raw_manifest_json = serde_json::to_value(raw_manifest_toml)
+        .context("Conversion from TOML value to JSON value should not fail.")?;
+
+    let maybe_raw_config = get_with_ownership(raw_manifest_json, "tool")
+        .and_then(|tool| get_with_ownership(tool, T::tool_name()));
+    match maybe_raw_config {
+        Some(raw_config) => T::from_raw(&resolve_env_variables(
+            raw_config,
+            &mut vec![],
+            &T::interpolates_env_variables,
+        )?),
+        None => Ok(T::default()),
+    }
+}
+
+/// Resolves `$VAR` strings of `config` to values of the environment variables,
+/// except for `${VAR}` references in strings for which `is_interpolated_by_tool` returns `true`
+fn resolve_env_variables(
+    config: serde_json::Value,
+    path: &mut Vec<String>,
+    is_interpolated_by_tool: &impl Fn(&[String]) -> bool,
+) -> Result<serde_json::Value> {
     match config {
         serde_json::Value::Object(map) => {
             let val = map
                 .into_iter()
                 .map(|(k, v)| -> Result<(String, serde_json::Value)> {
-                    Ok((k, resolve_env_variables(v)?))
+                    path.push(k.clone());
+                    let v = resolve_env_variables(v, path, is_interpolated_by_tool);
+                    path.pop();
+                    Ok((k, v?))
                 })
                 .collect::<Result<serde_json::Map<String, serde_json::Value>>>()?;
             Ok(serde_json::Value::Object(val))
@@ -111,10 +157,21 @@ fn resolve_env_variables(config: serde_json::Value) -> Result<serde_json::Value>
         serde_json::Value::Array(val) => {
             let val = val
                 .into_iter()
-                .map(resolve_env_variables)
+                .enumerate()
+                .map(|(index, v)| {
+                    path.push(index.to_string());
+                    let v = resolve_env_variables(v, path, is_interpolated_by_tool);
+                    path.pop();
+                    v
+                })
                 .collect::<Result<Vec<serde_json::Value>>>()?;
             Ok(serde_json::Value::Array(val))
         }
+        serde_json::Value::String(val)
+            if val.starts_with("${") && is_interpolated_by_tool(path) =>
+        {
+            Ok(serde_json::Value::String(val))
+        }
         serde_json::Value::String(val) if val.starts_with('$') => resolve_env_variable(&val),
         val => Ok(val),
     }
@@ -369,4 +426,65 @@ mod tests {
         assert_eq!(config.nested.list_example, vec![true, false]);
         assert_eq!(config.nested.url_nested, 321.312);
     }
+
+    impl PackageConfig for StubConfig {
+        fn tool_name() -> &'static str {
+            "stubtool"
+        }
+
+        fn from_raw(config: &serde_json::Value) -> Result<Self> {
+            Ok(serde_json::from_value::<StubConfig>(config.clone())?)
+        }
+    }
+
+    #[test]
+    fn load_tool_config_of_virtual_workspace_manifest() {
+        let temp_dir = tempdir().expect("Failed to create a temporary directory");
+        let manifest_path = Utf8PathBuf::try_from(temp_dir.path().join("Scarb.toml")).unwrap();
+        fs::write(
+            &manifest_path,
+            r#"
+            [workspace]
+            members = ["crates/*"]
+
+            [tool.stubtool]
+            url = "http://127.0.0.1:5050/rpc"
+            "#,
+        )
+        .unwrap();
+
+        let config = load_manifest_tool_config::<StubConfig>(&manifest_path).unwrap();
+        assert_eq!(config.url, String::from("http://127.0.0.1:5050/rpc"));
+        assert_eq!(config.account, String::new());
+
+        fs::write(
+            &manifest_path,
+            "[workspace]
+members = []
+",
+        )
+        .unwrap();
+        let config = load_manifest_tool_config::<StubConfig>(&manifest_path).unwrap();
+        assert_eq!(config.url, String::new());
+    }
+
+    #[test]
+    fn env_references_are_kept_only_where_tool_interpolates_them() {
+        let config = serde_json::json!({
+            "fork": [{ "url": "${SNFOUNDRY_TEST_UNSET_URL}" }],
+            "url": "${SNFOUNDRY_TEST_UNSET_URL}",
+        });
+        let is_fork_url = |path: &[String]| path.first().is_some_and(|key| key == "fork");
+
+        let err = resolve_env_variables(config.clone(), &mut vec![], &|_| false).unwrap_err();
+        assert!(err.to_string().contains("environment variable not found"));
+
+        let err = resolve_env_variables(config.clone(), &mut vec![], &is_fork_url).unwrap_err();
+        assert!(err.to_string().contains("environment variable not found"));
+
+        let mut config = config;
+        config.as_object_mut().unwrap().remove("url");
+        let resolved = resolve_env_variables(config.clone(), &mut vec![], &is_fork_url).unwrap();
+        assert_eq!(resolved, config);
+    }
 }
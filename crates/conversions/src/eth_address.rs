@@ -13,3 +13,9 @@ impl FromConv<EthAddress> for Felt252 {
         value.into()
     }
 }
+
+/// Converts a 20-byte Ethereum address to a felt, left-padding it with zeros
+#[must_use]
+pub fn eth_address_to_felt(address: [u8; 20]) -> Felt252 {
+    Felt252::from_bytes_be_slice(&address)
+}
@@ -2,7 +2,7 @@ use super::{BufferReadError, BufferReadResult, BufferReader, CairoDeserialize};
 use crate::{byte_array::ByteArray, IntoConv};
 use num_traits::cast::ToPrimitive;
 use starknet::providers::Url;
-use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, EthAddress, Nonce};
 use starknet_types_core::felt::Felt as Felt252;
 use std::num::NonZeroU32;
 
@@ -60,6 +60,12 @@ where
     }
 }
 
+impl CairoDeserialize for EthAddress {
+    fn deserialize(reader: &mut BufferReader<'_>) -> BufferReadResult<Self> {
+        EthAddress::try_from(reader.read_felt()?).map_err(|_| BufferReadError::ParseFailed)
+    }
+}
+
 impl CairoDeserialize for bool {
     fn deserialize(reader: &mut BufferReader<'_>) -> BufferReadResult<Self> {
         let num: usize = reader.read()?;
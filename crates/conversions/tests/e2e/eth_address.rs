@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod tests_eth_address {
+    use conversions::eth_address::eth_address_to_felt;
+    use conversions::{FromConv, IntoConv};
+    use starknet_api::core::EthAddress;
+    use starknet_types_core::felt::Felt as Felt252;
+
+    #[test]
+    fn test_eth_address_to_felt() {
+        let mut address = [0u8; 20];
+        address[19] = 0x12;
+        address[0] = 0xab;
+
+        let felt = eth_address_to_felt(address);
+
+        let mut expected = [0u8; 32];
+        expected[12..].copy_from_slice(&address);
+        assert_eq!(felt, Felt252::from_bytes_be(&expected));
+    }
+
+    #[test]
+    fn test_eth_address_conversions_max_address() {
+        let felt = eth_address_to_felt([0xff; 20]);
+        let eth_address = EthAddress::from_(felt);
+
+        assert_eq!(felt, eth_address.into_());
+    }
+}
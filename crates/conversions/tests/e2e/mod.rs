@@ -1,6 +1,7 @@
 mod class_hash;
 mod contract_address;
 mod entrypoint_selector;
+mod eth_address;
 mod felt252;
 mod field_elements;
 mod nonce;
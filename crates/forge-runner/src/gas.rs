@@ -14,14 +14,14 @@ use blockifier::transaction::objects::{GasVector, HasRelatedFeeType};
 use blockifier::utils::u128_from_usize;
 use cairo_vm::Felt252;
 use cheatnet::runtime_extensions::call_to_blockifier_runtime_extension::rpc::UsedResources;
-use cheatnet::state::ExtendedStateReader;
+use cheatnet::state::{ExtendedStateReader, ForkedState};
 use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
 use starknet_api::transaction::EventContent;
 use std::collections::HashMap;
 
 pub fn calculate_used_gas(
     transaction_context: &TransactionContext,
-    state: &mut CachedState<ExtendedStateReader>,
+    state: &mut ForkedState,
     resources: UsedResources,
 ) -> Result<u128, StateError> {
     let versioned_constants = transaction_context.block_context.versioned_constants();
@@ -31,7 +31,11 @@ pub fn calculate_used_gas(
         &resources.l1_handler_payload_lengths,
     );
 
-    let l1_data_cost = get_l1_data_cost(transaction_context, state)?;
+    // state changes made on every fork attached to the test are charged
+    let mut l1_data_cost = GasVector::default();
+    for fork_state in state.states_mut() {
+        l1_data_cost = l1_data_cost + get_l1_data_cost(transaction_context, fork_state)?;
+    }
 
     let l1_gas_by_vm_costs =
         calculate_l1_gas_by_vm_usage(versioned_constants, &resources.execution_resources, 0)
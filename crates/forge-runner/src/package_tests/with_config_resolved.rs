@@ -2,6 +2,7 @@ use super::{TestCase, TestTarget};
 use crate::expected_result::ExpectedTestResult;
 use cheatnet::runtime_extensions::forge_config_extension::config::RawFuzzerConfig;
 use starknet_api::block::BlockNumber;
+use std::collections::HashMap;
 use url::Url;
 
 pub type TestTargetWithResolvedConfig = TestTarget<TestCaseResolvedConfig>;
@@ -12,6 +13,14 @@ pub type TestCaseWithResolvedConfig = TestCase<TestCaseResolvedConfig>;
 pub struct ResolvedForkConfig {
     pub url: Url,
     pub block_number: BlockNumber,
+    pub max_concurrent_requests: usize,
+    /// Endpoints used when `url` is unreachable
+    pub fallback_urls: Vec<Url>,
+    /// HTTP headers sent with every RPC request to `url` and `fallback_urls`
+    pub headers: HashMap<String, String>,
+    /// Forks attached with `#[fork("A", "B")]` by name, the first one is the fork the test starts with.
+    /// Empty when only one fork is attached
+    pub selectable_forks: Vec<(String, ResolvedForkConfig)>,
 }
 
 /// Test case with config that has been resolved, that is
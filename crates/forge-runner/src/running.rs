@@ -5,13 +5,11 @@ use crate::package_tests::with_config_resolved::{ResolvedForkConfig, TestCaseWit
 use crate::test_case_summary::{Single, TestCaseSummary};
 use anyhow::{bail, ensure, Result};
 use blockifier::execution::entry_point::EntryPointExecutionContext;
-use blockifier::state::cached_state::CachedState;
 use cairo_lang_runner::{RunResult, RunnerError, SierraCasmRunner};
 use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use cairo_vm::Felt252;
 use camino::Utf8Path;
 use casm::{get_assembled_program, run_assembled_program};
-use cheatnet::constants as cheatnet_constants;
 use cheatnet::forking::state::ForkStateReader;
 use cheatnet::runtime_extensions::call_to_blockifier_runtime_extension::rpc::UsedResources;
 use cheatnet::runtime_extensions::call_to_blockifier_runtime_extension::CallToBlockifierExtension;
@@ -21,10 +19,10 @@ use cheatnet::runtime_extensions::forge_runtime_extension::{
     get_all_used_resources, update_top_call_execution_resources, update_top_call_l1_resources,
     update_top_call_vm_trace, ForgeExtension, ForgeRuntime,
 };
-use cheatnet::state::{BlockInfoReader, CallTrace, CheatnetState, ExtendedStateReader};
+use cheatnet::state::{ActiveFork, AttachedFork, CallTrace, CheatnetState, ForkedState};
 use entry_code::create_entry_code;
 use hints::{hints_by_representation, hints_to_params};
-use runtime::starknet::context::{build_context, set_max_steps};
+use runtime::starknet::context::{build_context, set_max_steps, SerializableBlockInfo};
 use runtime::{ExtendedRuntime, StarknetRuntime};
 use std::cell::RefCell;
 use std::default::Default;
@@ -149,25 +147,22 @@ pub fn run_test_case(
     let string_to_hint = hints_by_representation(&assembled_program);
     let hints_dict = hints_to_params(&assembled_program);
 
-    let mut state_reader = ExtendedStateReader {
-        dict_state_reader: cheatnet_constants::build_testing_state(),
-        fork_state_reader: get_fork_state_reader(
-            runtime_config.cache_dir,
-            &case.config.fork_config,
-        )?,
-    };
-    let block_info = state_reader.get_block_info()?;
-    let chain_id = state_reader.get_chain_id()?;
+    let active_fork = get_active_fork(runtime_config.cache_dir, &case.config.fork_config)?;
+    let block_info = active_fork.fork().map_or_else(
+        || SerializableBlockInfo::default().into(),
+        |fork| fork.block_info.clone(),
+    );
+    let chain_id = active_fork.fork().map(|fork| fork.chain_id.clone());
 
     let mut context = build_context(&block_info, chain_id);
 
     if let Some(max_n_steps) = runtime_config.max_n_steps {
         set_max_steps(&mut context, max_n_steps);
     }
-    let mut cached_state = CachedState::new(state_reader);
+    let mut forked_state = ForkedState::new(active_fork.clone());
     let mut execution_resources = ExecutionResources::default();
     let syscall_handler = build_syscall_handler(
-        &mut cached_state,
+        &mut forked_state,
         &string_to_hint,
         &mut execution_resources,
         &mut context,
@@ -176,6 +171,7 @@ pub fn run_test_case(
 
     let mut cheatnet_state = CheatnetState {
         block_info,
+        active_fork,
         ..Default::default()
     };
     cheatnet_state.trace_data.is_vm_trace_needed = runtime_config.is_vm_trace_needed;
@@ -253,7 +249,7 @@ pub fn run_test_case(
     let used_resources = get_all_used_resources(forge_runtime, &transaction_context);
     let gas = calculate_used_gas(
         &transaction_context,
-        &mut cached_state,
+        &mut forked_state,
         used_resources.clone(),
     )?;
 
@@ -304,7 +300,7 @@ fn extract_test_case_summary(
                 Err(err) => bail!(err),
             }
         }
-        // `ForkStateReader.get_block_info`, `get_fork_state_reader, `calculate_used_gas` may return an error
+        // `ForkStateReader.get_block_info`, `get_active_fork`, `calculate_used_gas` may return an error
         // `available_gas` may be specified with Scarb ~2.4
         Err(error) => Ok(TestCaseSummary::Failed {
             name: case.name.clone(),
@@ -315,16 +311,56 @@ fn extract_test_case_summary(
     }
 }
 
-fn get_fork_state_reader(
+fn new_fork_state_reader(
+    cache_dir: &Utf8Path,
+    ResolvedForkConfig {
+        url,
+        block_number,
+        max_concurrent_requests,
+        fallback_urls,
+        headers,
+        selectable_forks: _,
+    }: &ResolvedForkConfig,
+) -> Result<ForkStateReader> {
+    ForkStateReader::new(
+        url.clone(),
+        *block_number,
+        cache_dir,
+        *max_concurrent_requests,
+    )
+    .map(|reader| {
+        reader
+            .with_headers(headers.clone())
+            .with_fallback_urls(fallback_urls.clone())
+    })
+}
+
+/// Forks attached to the test, together with the handle used to switch between them
+fn get_active_fork(
     cache_dir: &Utf8Path,
     fork_config: &Option<ResolvedForkConfig>,
-) -> Result<Option<ForkStateReader>> {
-    fork_config
-        .as_ref()
-        .map(|ResolvedForkConfig { url, block_number }| {
-            ForkStateReader::new(url.clone(), *block_number, cache_dir)
+) -> Result<ActiveFork> {
+    let Some(fork_config) = fork_config else {
+        return Ok(ActiveFork::default());
+    };
+    let primary = AttachedFork::new(new_fork_state_reader(cache_dir, fork_config)?)?;
+
+    let Some(((primary_name, _), additional)) = fork_config.selectable_forks.split_first() else {
+        return Ok(ActiveFork::new(primary));
+    };
+
+    let additional = additional
+        .iter()
+        .map(|(name, fork_config)| {
+            let fork = AttachedFork::new(new_fork_state_reader(cache_dir, fork_config)?)?;
+            Ok((name.clone(), fork))
         })
-        .transpose()
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(ActiveFork::with_selectable_forks(
+        (primary_name.clone(), primary),
+        additional,
+    ))
 }
 
 fn get_context<'a>(runtime: &'a ForgeRuntime) -> &'a EntryPointExecutionContext {
@@ -1,27 +1,138 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
 use cairo_vm::Felt252;
 use conversions::{string::IntoHexStr, IntoConv};
+use serde_json::{json, Value};
+use shared::rpc_transport::RpcTransport;
 use starknet::{
     core::types::{BlockId, MaybePendingBlockWithTxHashes},
-    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+    providers::{JsonRpcClient, Provider},
 };
 use starknet_api::block::BlockNumber;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
 use tokio::runtime::Handle;
 use url::Url;
 
+/// Source of block numbers used to resolve block ids of forks.
+/// `headers` are sent with every RPC request to `url`
+#[async_trait]
+pub trait BlockNumberProvider: Send + Sync {
+    async fn latest_block_number(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+    ) -> Result<BlockNumber>;
+
+    async fn block_number_for_hash(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        hash: Felt252,
+    ) -> Result<BlockNumber>;
+
+    /// Unix timestamp of the block with `block_number`
+    async fn block_timestamp(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        block_number: BlockNumber,
+    ) -> Result<u64>;
+
+    /// Unix timestamp of the Ethereum block with `l1_block_number`, fetched from the L1 node at `l1_url`
+    async fn l1_block_timestamp(&self, l1_url: &Url, l1_block_number: u64) -> Result<u64>;
+}
+
+type RpcClient = JsonRpcClient<RpcTransport>;
+
+/// Fetches block numbers from the RPC node.
+/// A single client is created for every url and headers, so e.g. the binary search
+/// in [`BlockNumberMap::get_block_number_for_l1_block`] reuses its connection
 #[derive(Default)]
+pub struct RpcBlockNumberProvider {
+    clients: Mutex<HashMap<(Url, BTreeMap<String, String>), Arc<RpcClient>>>,
+}
+
+impl RpcBlockNumberProvider {
+    fn client(&self, url: &Url, headers: &HashMap<String, String>) -> Arc<RpcClient> {
+        let key = (url.clone(), headers.clone().into_iter().collect());
+
+        self.clients
+            .lock()
+            .expect("RPC clients lock should not be poisoned")
+            .entry(key)
+            .or_insert_with(|| {
+                Arc::new(JsonRpcClient::new(RpcTransport::new(url.clone(), headers)))
+            })
+            .clone()
+    }
+}
+
+#[async_trait]
+impl BlockNumberProvider for RpcBlockNumberProvider {
+    async fn latest_block_number(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+    ) -> Result<BlockNumber> {
+        fetch_latest_block_number(self.client(url, headers)).await
+    }
+
+    async fn block_number_for_hash(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        hash: Felt252,
+    ) -> Result<BlockNumber> {
+        fetch_block_number_for_hash(self.client(url, headers), hash).await
+    }
+
+    async fn block_timestamp(
+        &self,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        block_number: BlockNumber,
+    ) -> Result<u64> {
+        fetch_block_timestamp(self.client(url, headers), block_number).await
+    }
+
+    async fn l1_block_timestamp(&self, l1_url: &Url, l1_block_number: u64) -> Result<u64> {
+        fetch_l1_block_timestamp(l1_url, l1_block_number).await
+    }
+}
+
 pub struct BlockNumberMap {
     url_to_latest_block_number: HashMap<Url, BlockNumber>,
     url_and_hash_to_block_number: HashMap<(Url, Felt252), BlockNumber>,
+    provider: Box<dyn BlockNumberProvider>,
+}
+
+impl Default for BlockNumberMap {
+    fn default() -> Self {
+        Self::with_provider(Box::<RpcBlockNumberProvider>::default())
+    }
 }
 
 impl BlockNumberMap {
-    pub async fn get_latest_block_number(&mut self, url: Url) -> Result<BlockNumber> {
+    /// Create a map resolving block numbers with the given provider instead of the RPC node
+    #[must_use]
+    pub fn with_provider(provider: Box<dyn BlockNumberProvider>) -> Self {
+        Self {
+            url_to_latest_block_number: HashMap::new(),
+            url_and_hash_to_block_number: HashMap::new(),
+            provider,
+        }
+    }
+
+    pub async fn get_latest_block_number(
+        &mut self,
+        url: Url,
+        headers: &HashMap<String, String>,
+    ) -> Result<BlockNumber> {
         let block_number = if let Some(block_number) = self.url_to_latest_block_number.get(&url) {
             *block_number
         } else {
-            let latest_block_number = fetch_latest_block_number(url.clone()).await?;
+            let latest_block_number = self.provider.latest_block_number(&url, headers).await?;
 
             self.url_to_latest_block_number
                 .insert(url, latest_block_number);
@@ -35,6 +146,7 @@ impl BlockNumberMap {
     pub async fn get_block_number_for_hash(
         &mut self,
         url: Url,
+        headers: &HashMap<String, String>,
         hash: Felt252,
     ) -> Result<BlockNumber> {
         let block_number = if let Some(block_number) =
@@ -42,7 +154,10 @@ impl BlockNumberMap {
         {
             *block_number
         } else {
-            let block_number = fetch_block_number_for_hash(url.clone(), hash).await?;
+            let block_number = self
+                .provider
+                .block_number_for_hash(&url, headers, hash)
+                .await?;
 
             self.url_and_hash_to_block_number
                 .insert((url, hash), block_number);
@@ -53,24 +168,69 @@ impl BlockNumberMap {
         Ok(block_number)
     }
 
+    /// Last block which is not newer than the Ethereum block with `l1_block_number`.
+    /// Blocks do not reference the L1 blocks, so they are matched by timestamps,
+    /// with a binary search over the blocks up to the latest one
+    pub async fn get_block_number_for_l1_block(
+        &mut self,
+        url: Url,
+        headers: &HashMap<String, String>,
+        l1_url: &Url,
+        l1_block_number: u64,
+    ) -> Result<BlockNumber> {
+        let l1_timestamp = self
+            .provider
+            .l1_block_timestamp(l1_url, l1_block_number)
+            .await?;
+        let latest_block_number = self.get_latest_block_number(url.clone(), headers).await?;
+
+        if self
+            .provider
+            .block_timestamp(&url, headers, BlockNumber(0))
+            .await?
+            > l1_timestamp
+        {
+            bail!(
+                "L1 block = {l1_block_number} is older than the first block served by url = {url}"
+            );
+        }
+
+        // Block `low` is never newer than the L1 block
+        let (mut low, mut high) = (0, latest_block_number.0);
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+            let timestamp = self
+                .provider
+                .block_timestamp(&url, headers, BlockNumber(middle))
+                .await?;
+
+            if timestamp <= l1_timestamp {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+
+        Ok(BlockNumber(low))
+    }
+
     #[must_use]
     pub fn get_url_to_latest_block_number(&self) -> &HashMap<Url, BlockNumber> {
         &self.url_to_latest_block_number
     }
 }
 
-async fn fetch_latest_block_number(url: Url) -> Result<BlockNumber> {
-    let client = JsonRpcClient::new(HttpTransport::new(url));
-
+async fn fetch_latest_block_number(client: Arc<RpcClient>) -> Result<BlockNumber> {
     Ok(Handle::current()
         .spawn(async move { client.block_number().await })
         .await?
         .map(BlockNumber)?)
 }
 
-async fn fetch_block_number_for_hash(url: Url, block_hash: Felt252) -> Result<BlockNumber> {
-    let client = JsonRpcClient::new(HttpTransport::new(url));
-
+async fn fetch_block_number_for_hash(
+    client: Arc<RpcClient>,
+    block_hash: Felt252,
+) -> Result<BlockNumber> {
     let hash = BlockId::Hash(block_hash.into_());
 
     match Handle::current()
@@ -84,3 +244,55 @@ async fn fetch_block_number_for_hash(url: Url, block_hash: Felt252) -> Result<Bl
         )),
     }
 }
+
+async fn fetch_block_timestamp(client: Arc<RpcClient>, block_number: BlockNumber) -> Result<u64> {
+    let block_id = BlockId::Number(block_number.0);
+
+    match Handle::current()
+        .spawn(async move { client.get_block_with_tx_hashes(block_id).await })
+        .await?
+    {
+        Ok(MaybePendingBlockWithTxHashes::Block(block)) => Ok(block.timestamp),
+        _ => Err(anyhow!(
+            "Could not get the timestamp of block with number {block_number}"
+        )),
+    }
+}
+
+async fn fetch_l1_block_timestamp(l1_url: &Url, l1_block_number: u64) -> Result<u64> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_getBlockByNumber",
+        "params": [format!("{l1_block_number:#x}"), false],
+    });
+
+    let response = reqwest::Client::new()
+        .post(l1_url.clone())
+        .header("Content-Type", "application/json")
+        .body(request.to_string())
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .with_context(|| {
+            format!("Failed to fetch L1 block = {l1_block_number} from url = {l1_url}")
+        })?
+        .text()
+        .await?;
+    let response: Value = serde_json::from_str(&response)
+        .with_context(|| format!("L1 url = {l1_url} returned invalid JSON-RPC response"))?;
+
+    if let Some(error) = response.get("error") {
+        bail!("L1 url = {l1_url} failed to return block = {l1_block_number}: {error}");
+    }
+
+    let timestamp = response
+        .get("result")
+        .and_then(|block| block.get("timestamp"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("L1 block = {l1_block_number} not found at url = {l1_url}"))?;
+
+    u64::from_str_radix(timestamp.trim_start_matches("0x"), 16).with_context(|| {
+        format!("Failed to parse timestamp = {timestamp} of L1 block = {l1_block_number}")
+    })
+}
@@ -147,6 +147,9 @@ mod tests {
             build_profile: true,
             coverage: true,
             max_n_steps: Some(1_000_000),
+            max_casm_size: None,
+            fork_lockfile: None,
+            refresh_fork_lockfile: false,
         };
 
         let config = combine_configs(
@@ -201,6 +204,9 @@ mod tests {
             build_profile: false,
             coverage: false,
             max_n_steps: Some(1234),
+            max_casm_size: None,
+            fork_lockfile: None,
+            refresh_fork_lockfile: false,
         };
         let config = combine_configs(
             true,
@@ -0,0 +1,161 @@
+use crate::scarb::config::ForkTarget;
+use anyhow::{Context, Result};
+use camino::Utf8Path;
+use cheatnet::runtime_extensions::forge_config_extension::config::BlockId;
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+
+/// Block numbers forks with `block_id.tag` or `block_id.offset` resolved to, stored so the next
+/// runs fork from the same blocks instead of resolving them again
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ForkLockfile {
+    #[serde(default)]
+    forks: BTreeMap<String, LockedFork>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct LockedFork {
+    url: String,
+    /// Block id the block number was resolved from, `None` in lockfiles written before it was stored
+    #[serde(default)]
+    block_id: Option<LockedBlockId>,
+    block_number: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LockedBlockId {
+    Tag,
+    Offset(u64),
+}
+
+impl LockedBlockId {
+    /// Block id of the fork as written in `Scarb.toml`, `None` if the fork is not locked.
+    /// Forks with `block_id.l1_number` are not locked, they always resolve to the same block
+    fn of(fork_target: &ForkTarget) -> Option<Self> {
+        if fork_target.block_id != BlockId::BlockTag || fork_target.l1_block_number.is_some() {
+            return None;
+        }
+
+        Some(match fork_target.latest_offset {
+            Some(offset) => Self::Offset(offset),
+            None => Self::Tag,
+        })
+    }
+}
+
+impl ForkLockfile {
+    /// Lockfile stored at `path`, empty if the file does not exist yet
+    pub fn load(path: &Utf8Path) -> Result<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("Failed to read fork lockfile {path}"))?,
+        };
+
+        serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse fork lockfile {path}"))
+    }
+
+    pub fn save(&self, path: &Utf8Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+
+        fs::write(path, contents + "\n")
+            .with_context(|| format!("Failed to write fork lockfile {path}"))
+    }
+
+    /// Block number locked for the fork, `None` if the fork is not locked
+    /// or its url or block id changed since it was locked.
+    /// `fork_target` has to be the one from `Scarb.toml`, before its block id is resolved
+    #[must_use]
+    pub fn block_number(&self, fork_target: &ForkTarget) -> Option<BlockNumber> {
+        let block_id = LockedBlockId::of(fork_target)?;
+
+        self.forks
+            .get(&fork_target.name)
+            .filter(|locked_fork| {
+                locked_fork.url == fork_target.url.as_str()
+                    && locked_fork.block_id == Some(block_id)
+            })
+            .map(|locked_fork| BlockNumber(locked_fork.block_number))
+    }
+
+    /// Store the block number the fork resolved to, forks with `block_id.tag`
+    /// or `block_id.offset` only. `fork_target` has to be the one from `Scarb.toml`,
+    /// before its block id is resolved
+    pub fn lock(&mut self, fork_target: &ForkTarget, block_number: BlockNumber) {
+        let Some(block_id) = LockedBlockId::of(fork_target) else {
+            return;
+        };
+
+        self.forks.insert(
+            fork_target.name.clone(),
+            LockedFork {
+                url: fork_target.url.to_string(),
+                block_id: Some(block_id),
+                block_number: block_number.0,
+            },
+        );
+    }
+
+    /// Remove the fork from the lockfile, so its block number is resolved again
+    pub fn unlock(&mut self, fork_name: &str) {
+        self.forks.remove(fork_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::fixture::PathChild;
+    use assert_fs::TempDir;
+    use camino::Utf8PathBuf;
+
+    #[test]
+    fn lockfile_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = Utf8PathBuf::from_path_buf(temp.child("forks.lock").to_path_buf()).unwrap();
+        let fork_target = ForkTarget::new("FORK", "http://example.com", "tag", "latest").unwrap();
+
+        let mut lockfile = ForkLockfile::load(&path).unwrap();
+        assert_eq!(lockfile, ForkLockfile::default());
+
+        lockfile.lock(&fork_target, BlockNumber(123));
+        lockfile.save(&path).unwrap();
+
+        let lockfile = ForkLockfile::load(&path).unwrap();
+        assert_eq!(lockfile.block_number(&fork_target), Some(BlockNumber(123)));
+
+        let moved_fork_target =
+            ForkTarget::new("FORK", "http://other.com", "tag", "latest").unwrap();
+        assert_eq!(lockfile.block_number(&moved_fork_target), None);
+    }
+
+    #[test]
+    fn lockfile_misses_when_block_id_changed() {
+        let mut lockfile = ForkLockfile::default();
+        let offset_10 = ForkTarget::new("FORK", "http://example.com", "offset", "-10").unwrap();
+        let offset_20 = ForkTarget::new("FORK", "http://example.com", "offset", "-20").unwrap();
+        let tag = ForkTarget::new("FORK", "http://example.com", "tag", "latest").unwrap();
+
+        lockfile.lock(&offset_10, BlockNumber(123));
+
+        assert_eq!(lockfile.block_number(&offset_10), Some(BlockNumber(123)));
+        assert_eq!(lockfile.block_number(&offset_20), None);
+        assert_eq!(lockfile.block_number(&tag), None);
+    }
+
+    #[test]
+    fn l1_number_forks_are_not_locked() {
+        let mut lockfile = ForkLockfile::default();
+        let l1_number = ForkTarget::new("FORK", "http://example.com", "l1_number", "5005").unwrap();
+
+        lockfile.lock(&l1_number, BlockNumber(500));
+
+        assert_eq!(lockfile, ForkLockfile::default());
+        assert_eq!(lockfile.block_number(&l1_number), None);
+    }
+}
@@ -6,10 +6,12 @@ use scarb_api::{metadata::MetadataCommandExt, ScarbCommand};
 use scarb_ui::args::{FeaturesSpec, PackagesFilter};
 use std::{fs, num::NonZeroU32, thread::available_parallelism};
 use tokio::runtime::Builder;
+use tracing_subscriber::EnvFilter;
 use universal_sierra_compiler_api::UniversalSierraCompilerCommand;
 
 pub mod block_number_map;
 mod combine_configs;
+mod fork_lockfile;
 mod init;
 pub mod pretty_printing;
 pub mod run_tests;
@@ -20,6 +22,10 @@ mod warn;
 
 pub const CAIRO_EDITION: &str = "2023_11";
 
+/// Filter directives of the logs printed to stderr, e.g. `SNFORGE_LOG=cheatnet::forking=debug`
+/// logs every request sent to the fork RPC. Logging is disabled if not set.
+pub const LOG_ENV_VAR: &str = "SNFORGE_LOG";
+
 #[derive(Parser, Debug)]
 #[command(
     version,
@@ -145,6 +151,18 @@ pub struct TestArgs {
     /// Build contracts separately in the scarb starknet contract target
     #[arg(long)]
     no_optimization: bool,
+
+    /// Compile contracts sierra to casm without using the cache stored in the target directory
+    #[arg(long)]
+    no_casm_cache: bool,
+
+    /// Remove casm cached in the target directory before compiling contracts
+    #[arg(long)]
+    clear_casm_cache: bool,
+
+    /// Resolve block numbers of forks again, replacing the ones stored in the fork lockfile
+    #[arg(long)]
+    refresh_fork_lockfile: bool,
 }
 
 pub enum ExitStatus {
@@ -152,9 +170,20 @@ pub enum ExitStatus {
     Failure,
 }
 
+fn init_logging() {
+    if let Ok(directives) = std::env::var(LOG_ENV_VAR) {
+        tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::new(directives))
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
+
 pub fn main_execution() -> Result<ExitStatus> {
     let cli = Cli::parse();
 
+    init_logging();
+
     ScarbCommand::new().ensure_available()?;
     UniversalSierraCompilerCommand::ensure_available()?;
 
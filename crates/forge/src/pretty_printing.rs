@@ -73,6 +73,18 @@ pub fn print_failures(all_failed_tests: &[AnyTestCaseSummary]) {
     }
 }
 
+pub(crate) fn print_pinned_fork_block_number(fork_name: &str, block_number: BlockNumber) {
+    println!("Fork {fork_name} pinned to block number = {block_number}");
+}
+
+pub(crate) fn print_fork_block_number_for_l1_block(
+    fork_name: &str,
+    l1_block_number: u64,
+    block_number: BlockNumber,
+) {
+    println!("Fork {fork_name} pinned to block number = {block_number} for L1 block number = {l1_block_number}");
+}
+
 #[allow(clippy::implicit_hasher)]
 pub fn print_latest_blocks_numbers(url_to_latest_block_number_map: &HashMap<Url, BlockNumber>) {
     if !url_to_latest_block_number_map.is_empty() {
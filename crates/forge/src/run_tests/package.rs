@@ -1,20 +1,25 @@
 use super::{
-    resolve_config::resolve_config,
+    resolve_config::{
+        pin_fork_targets_tags, resolve_config, used_fork_names, verify_fork_targets_chain_ids,
+        verify_fork_targets_rpc_versions,
+    },
     test_target::{run_for_test_target, TestTargetRunResult},
 };
 use crate::{
     block_number_map::BlockNumberMap,
     combine_configs::combine_configs,
+    fork_lockfile::ForkLockfile,
     pretty_printing,
     scarb::{
         config::{ForgeConfigFromScarb, ForkTarget},
-        load_test_artifacts, should_compile_starknet_contract_target,
+        effective_forks_for_package, load_test_artifacts, should_compile_starknet_contract_target,
+        verify_casm_sizes,
     },
     shared_cache::FailedTestsCache,
     test_filter::{NameFilter, TestsFilter},
     warn::{
-        warn_if_available_gas_used_with_incompatible_scarb_version,
-        warn_if_incompatible_rpc_version,
+        warn_if_available_gas_used_with_incompatible_scarb_version, warn_if_forks_unused,
+        warn_if_incompatible_rpc_version, warn_if_pinned_fork_is_stale,
     },
     TestArgs,
 };
@@ -24,13 +29,17 @@ use cheatnet::runtime_extensions::forge_runtime_extension::contracts_data::Contr
 use configuration::load_package_config;
 use forge_runner::{
     forge_config::ForgeConfig,
-    package_tests::{raw::TestTargetRaw, with_config_resolved::TestTargetWithResolvedConfig},
+    package_tests::{
+        raw::TestTargetRaw, with_config::TestTargetWithConfig,
+        with_config_resolved::TestTargetWithResolvedConfig,
+    },
     running::with_config::test_target_with_config,
     test_case_summary::AnyTestCaseSummary,
     test_target_summary::TestTargetSummary,
 };
-use scarb_api::get_contracts_artifacts_and_source_sierra_paths;
+use scarb_api::{load_artifacts, target_dir_for_workspace, ArtifactLoadOptions, CasmCache};
 use scarb_metadata::{Metadata, PackageMetadata};
+use std::collections::HashSet;
 use std::sync::Arc;
 
 pub struct RunForPackageArgs {
@@ -38,6 +47,8 @@ pub struct RunForPackageArgs {
     pub tests_filter: TestsFilter,
     pub forge_config: Arc<ForgeConfig>,
     pub fork_targets: Vec<ForkTarget>,
+    pub fork_lockfile: Option<Utf8PathBuf>,
+    pub refresh_fork_lockfile: bool,
     pub package_name: String,
 }
 
@@ -51,20 +62,25 @@ impl RunForPackageArgs {
         versioned_programs_dir: Utf8PathBuf,
     ) -> Result<RunForPackageArgs> {
         let raw_test_targets = load_test_artifacts(snforge_target_dir_path, &package)?;
+        let forge_config_from_scarb =
+            load_package_config::<ForgeConfigFromScarb>(scarb_metadata, &package.id)?;
 
-        let contracts = get_contracts_artifacts_and_source_sierra_paths(
-            scarb_metadata,
-            &package.id,
-            None,
-            !should_compile_starknet_contract_target(
-                &scarb_metadata.app_version_info.version,
-                args.no_optimization,
-            ),
-        )?;
+        let use_test_target_contracts = !should_compile_starknet_contract_target(
+            &scarb_metadata.app_version_info.version,
+            args.no_optimization,
+        );
+        let casm_cache = CasmCache::in_target_dir(&target_dir_for_workspace(scarb_metadata));
+        let mut load_options = ArtifactLoadOptions::new();
+        load_options.use_test_target_contracts(use_test_target_contracts);
+        if !args.no_casm_cache {
+            load_options.casm_cache(&casm_cache);
+        }
+        let contracts = load_artifacts(scarb_metadata, &package.id, &load_options)?;
+        if let Some(max_casm_size) = forge_config_from_scarb.max_casm_size {
+            verify_casm_sizes(&contracts, max_casm_size)?;
+        }
         let contracts_data = ContractsData::try_from(contracts)?;
 
-        let forge_config_from_scarb =
-            load_package_config::<ForgeConfigFromScarb>(scarb_metadata, &package.id)?;
         let forge_config = Arc::new(combine_configs(
             args.exit_first,
             args.fuzzer_runs,
@@ -93,22 +109,26 @@ impl RunForPackageArgs {
             test_targets: raw_test_targets,
             forge_config,
             tests_filter: test_filter,
-            fork_targets: forge_config_from_scarb.fork,
+            fork_targets: effective_forks_for_package(scarb_metadata, &package.id)?,
+            fork_lockfile: forge_config_from_scarb
+                .fork_lockfile
+                .as_ref()
+                .map(|fork_lockfile| package.root.join(fork_lockfile)),
+            refresh_fork_lockfile: args.refresh_fork_lockfile
+                || forge_config_from_scarb.refresh_fork_lockfile,
             package_name: package.name,
         })
     }
 }
 
 async fn test_package_with_config_resolved(
-    test_targets: Vec<TestTargetRaw>,
+    test_targets: Vec<TestTargetWithConfig>,
     fork_targets: &[ForkTarget],
     block_number_map: &mut BlockNumberMap,
 ) -> Result<Vec<TestTargetWithResolvedConfig>> {
     let mut test_targets_with_resolved_config = Vec::with_capacity(test_targets.len());
 
     for test_target in test_targets {
-        let test_target = test_target_with_config(test_target)?;
-
         let test_target = resolve_config(test_target, fork_targets, block_number_map).await?;
 
         test_targets_with_resolved_config.push(test_target);
@@ -127,10 +147,46 @@ pub async fn run_for_package(
         forge_config,
         tests_filter,
         fork_targets,
+        fork_lockfile: fork_lockfile_path,
+        refresh_fork_lockfile,
         package_name,
     }: RunForPackageArgs,
     block_number_map: &mut BlockNumberMap,
 ) -> Result<Vec<TestTargetSummary>> {
+    let test_targets = test_targets
+        .into_iter()
+        .map(test_target_with_config)
+        .collect::<Result<Vec<_>>>()?;
+    let used_forks: HashSet<String> = test_targets
+        .iter()
+        .flat_map(|test_target| used_fork_names(test_target, &fork_targets))
+        .collect();
+
+    // Only forks used by the tests are resolved, so unused ones don't send any RPC requests
+    let (used_fork_targets, unused_fork_targets): (Vec<_>, Vec<_>) = fork_targets
+        .into_iter()
+        .partition(|fork_target| used_forks.contains(&fork_target.name));
+
+    let mut fork_lockfile = fork_lockfile_path
+        .as_deref()
+        .map(ForkLockfile::load)
+        .transpose()?;
+    if let (Some(fork_lockfile), true) = (fork_lockfile.as_mut(), refresh_fork_lockfile) {
+        for fork_target in &used_fork_targets {
+            fork_lockfile.unlock(&fork_target.name);
+        }
+    }
+    let used_fork_targets =
+        pin_fork_targets_tags(used_fork_targets, block_number_map, fork_lockfile.as_mut()).await?;
+    if let (Some(fork_lockfile), Some(path)) = (&fork_lockfile, &fork_lockfile_path) {
+        fork_lockfile.save(path)?;
+    }
+    verify_fork_targets_chain_ids(&used_fork_targets).await?;
+    verify_fork_targets_rpc_versions(&used_fork_targets).await?;
+    let fork_targets: Vec<_> = used_fork_targets
+        .into_iter()
+        .chain(unused_fork_targets)
+        .collect();
     let mut test_targets =
         test_package_with_config_resolved(test_targets, &fork_targets, block_number_map).await?;
     let all_tests = sum_test_cases(&test_targets);
@@ -141,6 +197,7 @@ pub async fn run_for_package(
 
     warn_if_available_gas_used_with_incompatible_scarb_version(&test_targets)?;
     warn_if_incompatible_rpc_version(&test_targets).await?;
+    warn_if_pinned_fork_is_stale(&fork_targets, &used_forks, block_number_map).await;
 
     let not_filtered = sum_test_cases(&test_targets);
     pretty_printing::print_collected_tests_count(not_filtered, &package_name);
@@ -192,5 +249,7 @@ pub async fn run_for_package(
         pretty_printing::print_test_seed(forge_config.test_runner_config.fuzzer_seed);
     }
 
+    warn_if_forks_unused(&fork_targets, &used_forks);
+
     Ok(summaries)
 }
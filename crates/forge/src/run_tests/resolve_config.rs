@@ -1,5 +1,9 @@
-use crate::{block_number_map::BlockNumberMap, scarb::config::ForkTarget};
-use anyhow::{anyhow, Result};
+use crate::{
+    block_number_map::BlockNumberMap, fork_lockfile::ForkLockfile, pretty_printing,
+    scarb::config::ForkTarget,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use cheatnet::forking::state::DEFAULT_MAX_CONCURRENT_REQUESTS;
 use cheatnet::runtime_extensions::forge_config_extension::config::{
     BlockId, InlineForkConfig, OverriddenForkConfig, RawForkConfig,
 };
@@ -11,7 +15,14 @@ use forge_runner::package_tests::{
         TestTargetWithResolvedConfig,
     },
 };
+use semver::Version;
+use shared::rpc::{create_rpc_client_with_headers, get_rpc_version};
+use starknet::core::utils::parse_cairo_short_string;
+use starknet::providers::Provider;
 use starknet_api::block::BlockNumber;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use url::Url;
 
 pub async fn resolve_config(
     test_target: TestTargetWithConfig,
@@ -21,22 +32,25 @@ pub async fn resolve_config(
     let mut test_cases = Vec::with_capacity(test_target.test_cases.len());
 
     for case in test_target.test_cases {
-        test_cases.push(TestCaseWithResolvedConfig {
-            name: case.name,
-            test_details: case.test_details,
-            config: TestCaseResolvedConfig {
-                available_gas: case.config.available_gas,
-                ignored: case.config.ignored,
-                expected_result: case.config.expected_result,
-                fork_config: resolve_fork_config(
-                    case.config.fork_config,
-                    block_number_map,
-                    fork_targets,
-                )
-                .await?,
-                fuzzer_config: case.config.fuzzer_config,
-            },
-        });
+        for (fork_name, fork_config) in expand_fork_config(case.config.fork_config, fork_targets)? {
+            let name = match fork_name {
+                Some(fork_name) => format!("{}[{fork_name}]", case.name),
+                None => case.name.clone(),
+            };
+
+            test_cases.push(TestCaseWithResolvedConfig {
+                name,
+                test_details: case.test_details.clone(),
+                config: TestCaseResolvedConfig {
+                    available_gas: case.config.available_gas,
+                    ignored: case.config.ignored,
+                    expected_result: case.config.expected_result.clone(),
+                    fork_config: resolve_fork_config(fork_config, block_number_map, fork_targets)
+                        .await?,
+                    fuzzer_config: case.config.fuzzer_config.clone(),
+                },
+            });
+        }
     }
 
     Ok(TestTargetWithResolvedConfig {
@@ -56,25 +70,417 @@ async fn resolve_fork_config(
         return Ok(None);
     };
 
+    let RawForkConfig::Multiple(names) = fc else {
+        return resolve_single_fork_config(fc, block_number_map, fork_targets)
+            .await
+            .map(Some);
+    };
+
+    let mut selectable_forks: Vec<(String, ResolvedForkConfig)> = Vec::with_capacity(names.len());
+    for name in names {
+        let name = String::from(name);
+
+        if selectable_forks.iter().any(|(used, _)| *used == name) {
+            bail!("Fork = {name} is attached to the test more than once");
+        }
+
+        let fork_config = resolve_single_fork_config(
+            RawForkConfig::Named(ByteArray::from(name.as_str())),
+            block_number_map,
+            fork_targets,
+        )
+        .await?;
+
+        selectable_forks.push((name, fork_config));
+    }
+
+    let Some((_, primary)) = selectable_forks.first() else {
+        bail!("At least one fork has to be attached to the test");
+    };
+
+    Ok(Some(ResolvedForkConfig {
+        selectable_forks: selectable_forks.clone(),
+        ..primary.clone()
+    }))
+}
+
+async fn resolve_single_fork_config(
+    fc: RawForkConfig,
+    block_number_map: &mut BlockNumberMap,
+    fork_targets: &[ForkTarget],
+) -> Result<ResolvedForkConfig> {
+    let fork_target = fork_target_for_config(&fc, fork_targets);
+    let max_concurrent_requests = fork_target
+        .and_then(|fork_target| fork_target.max_concurrent_requests)
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+    let fallback_urls = fork_target
+        .map(|fork_target| fork_target.fallback_urls.clone())
+        .unwrap_or_default();
+    let headers = fork_target
+        .map(|fork_target| fork_target.headers.clone())
+        .unwrap_or_default();
+
     let raw_fork_params = replace_id_with_params(fc, fork_targets)?;
 
     let url = raw_fork_params.url;
 
-    let block_number = match raw_fork_params.block {
-        BlockId::BlockNumber(block_number) => BlockNumber(block_number),
-        BlockId::BlockHash(hash) => {
-            block_number_map
-                .get_block_number_for_hash(url.clone(), hash)
-                .await?
+    let urls: Vec<_> = std::iter::once(url.clone())
+        .chain(fallback_urls.iter().cloned())
+        .collect();
+    let block_number =
+        resolve_block_number(&raw_fork_params.block, &urls, &headers, block_number_map).await?;
+
+    Ok(ResolvedForkConfig {
+        url,
+        block_number,
+        max_concurrent_requests,
+        fallback_urls,
+        headers,
+        selectable_forks: vec![],
+    })
+}
+
+/// Resolve block id to a number using the first of `urls` that responds,
+/// so all endpoints of a fork are used with the same block number
+pub(crate) async fn resolve_block_number(
+    block: &BlockId,
+    urls: &[Url],
+    headers: &HashMap<String, String>,
+    block_number_map: &mut BlockNumberMap,
+) -> Result<BlockNumber> {
+    let mut first_error = None;
+
+    for url in urls {
+        let result = match block {
+            BlockId::BlockNumber(block_number) => return Ok(BlockNumber(*block_number)),
+            BlockId::BlockHash(hash) => {
+                block_number_map
+                    .get_block_number_for_hash(url.clone(), headers, *hash)
+                    .await
+            }
+            BlockId::BlockTag => {
+                block_number_map
+                    .get_latest_block_number(url.clone(), headers)
+                    .await
+            }
+        };
+
+        match result {
+            Ok(block_number) => return Ok(block_number),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    Err(first_error.expect("Fork should have at least one url"))
+}
+
+/// Fork target from `Scarb.toml` referenced by the config, `None` for inline configs
+fn fork_target_for_config<'a>(
+    raw_fork_config: &RawForkConfig,
+    fork_targets: &'a [ForkTarget],
+) -> Option<&'a ForkTarget> {
+    match raw_fork_config {
+        RawForkConfig::Inline(_) | RawForkConfig::Multiple(_) => None,
+        RawForkConfig::Named(name)
+        | RawForkConfig::Overridden(OverriddenForkConfig { name, .. }) => {
+            get_fork_target_from_runner_config(fork_targets, name).ok()
         }
-        BlockId::BlockTag => {
-            block_number_map
-                .get_latest_block_number(url.clone())
-                .await?
+    }
+}
+
+/// Replace `block_id.tag` of fork targets with `pin_tag` set and `block_id.offset` of fork targets
+/// by the block number they resolve to, so all tests using such fork run against the same block.
+/// Forks with `block_id.l1_number` are pinned to the last block not newer than the L1 block.
+/// With `fork_lockfile`, every fork with `block_id.tag` is pinned: block numbers of forks with
+/// `block_id.tag` or `block_id.offset` stored in the lockfile are reused, as long as their url
+/// and block id did not change, and the ones resolved in this run are stored in it.
+pub async fn pin_fork_targets_tags(
+    fork_targets: Vec<ForkTarget>,
+    block_number_map: &mut BlockNumberMap,
+    mut fork_lockfile: Option<&mut ForkLockfile>,
+) -> Result<Vec<ForkTarget>> {
+    let mut pinned_fork_targets = Vec::with_capacity(fork_targets.len());
+
+    for mut fork_target in fork_targets {
+        // Lockfile entries are keyed by the block id from `Scarb.toml`, before it is resolved
+        let unresolved_fork_target = fork_lockfile.is_some().then(|| fork_target.clone());
+        let locked_block_number = fork_lockfile
+            .as_deref()
+            .and_then(|fork_lockfile| fork_lockfile.block_number(&fork_target));
+
+        if let Some(block_number) = locked_block_number {
+            pretty_printing::print_pinned_fork_block_number(&fork_target.name, block_number);
+
+            fork_target.latest_offset = None;
+            fork_target.l1_block_number = None;
+            fork_target.block_id = BlockId::BlockNumber(block_number.0);
+        } else if let Some(l1_block_number) = fork_target.l1_block_number.take() {
+            let l1_url = fork_target.l1_url.clone().ok_or_else(|| {
+                anyhow!(
+                    "block_id.l1_number of fork = {} requires l1_url to be set",
+                    fork_target.name
+                )
+            })?;
+            let block_number = block_number_map
+                .get_block_number_for_l1_block(
+                    fork_target.url.clone(),
+                    &fork_target.headers,
+                    &l1_url,
+                    l1_block_number,
+                )
+                .await
+                .with_context(|| {
+                    format!(
+                        "Failed to resolve block_id.l1_number of fork = {}",
+                        fork_target.name
+                    )
+                })?;
+
+            pretty_printing::print_fork_block_number_for_l1_block(
+                &fork_target.name,
+                l1_block_number,
+                block_number,
+            );
+
+            fork_target.block_id = BlockId::BlockNumber(block_number.0);
+        } else if let Some(offset) = fork_target.latest_offset.take() {
+            let urls: Vec<_> = std::iter::once(fork_target.url.clone())
+                .chain(fork_target.fallback_urls.iter().cloned())
+                .collect();
+            let latest_block_number = resolve_block_number(
+                &BlockId::BlockTag,
+                &urls,
+                &fork_target.headers,
+                block_number_map,
+            )
+            .await?;
+            let block_number = latest_block_number.0.checked_sub(offset).ok_or_else(|| {
+                anyhow!(
+                    "block_id.offset = -{offset} of fork = {} points below the genesis block, latest block number = {latest_block_number}",
+                    fork_target.name
+                )
+            })?;
+
+            pretty_printing::print_pinned_fork_block_number(
+                &fork_target.name,
+                BlockNumber(block_number),
+            );
+
+            fork_target.block_id = BlockId::BlockNumber(block_number);
+        } else if (fork_target.pin_tag || fork_lockfile.is_some())
+            && fork_target.block_id == BlockId::BlockTag
+        {
+            let urls: Vec<_> = std::iter::once(fork_target.url.clone())
+                .chain(fork_target.fallback_urls.iter().cloned())
+                .collect();
+            let block_number = resolve_block_number(
+                &BlockId::BlockTag,
+                &urls,
+                &fork_target.headers,
+                block_number_map,
+            )
+            .await?;
+
+            pretty_printing::print_pinned_fork_block_number(&fork_target.name, block_number);
+
+            fork_target.block_id = BlockId::BlockNumber(block_number.0);
+        }
+
+        if let (
+            Some(fork_lockfile),
+            Some(unresolved_fork_target),
+            BlockId::BlockNumber(block_number),
+        ) = (
+            fork_lockfile.as_deref_mut(),
+            &unresolved_fork_target,
+            &fork_target.block_id,
+        ) {
+            fork_lockfile.lock(unresolved_fork_target, BlockNumber(*block_number));
+        }
+
+        pinned_fork_targets.push(fork_target);
+    }
+
+    Ok(pinned_fork_targets)
+}
+
+/// Verify that endpoints of fork targets with `chain_id` set serve the expected chain.
+/// Called with the forks used by the tests only, so unused ones are not queried
+pub async fn verify_fork_targets_chain_ids(fork_targets: &[ForkTarget]) -> Result<()> {
+    for fork_target in fork_targets {
+        if fork_target.chain_id.is_none() {
+            continue;
+        }
+
+        let client =
+            create_rpc_client_with_headers(fork_target.url.as_str(), &fork_target.headers)?;
+        let chain_id = client
+            .chain_id()
+            .await
+            .with_context(|| format!("Failed to fetch chain id of fork = {}", fork_target.name))?;
+
+        verify_chain_id(fork_target, &parse_cairo_short_string(&chain_id)?)?;
+    }
+
+    Ok(())
+}
+
+/// State of a fork endpoint reported by [`check_fork_health`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkHealth {
+    pub chain_id: String,
+    pub latest_block_number: BlockNumber,
+    /// Time the endpoint took to answer both queries
+    pub latency: Duration,
+}
+
+/// Query the endpoint of the fork target for its chain id and latest block number, without
+/// running any tests. Both requests are sent concurrently, so `latency` is a single round-trip
+pub async fn check_fork_health(fork_target: &ForkTarget) -> Result<ForkHealth> {
+    let client = create_rpc_client_with_headers(fork_target.url.as_str(), &fork_target.headers)?;
+
+    let start = Instant::now();
+    let (chain_id, latest_block_number) =
+        tokio::try_join!(client.chain_id(), client.block_number())
+            .with_context(|| format!("Fork = {} is not reachable", fork_target.name))?;
+    let latency = start.elapsed();
+
+    let chain_id = parse_cairo_short_string(&chain_id)?;
+    verify_chain_id(fork_target, &chain_id)?;
+
+    Ok(ForkHealth {
+        chain_id,
+        latest_block_number: BlockNumber(latest_block_number),
+        latency,
+    })
+}
+
+/// Verify that endpoints of fork targets with `rpc_version` set use the pinned JSON-RPC spec version.
+/// Called with the forks used by the tests only, so unused ones are not queried
+pub async fn verify_fork_targets_rpc_versions(fork_targets: &[ForkTarget]) -> Result<()> {
+    for fork_target in fork_targets {
+        if fork_target.rpc_version.is_none() {
+            continue;
+        }
+
+        let client =
+            create_rpc_client_with_headers(fork_target.url.as_str(), &fork_target.headers)?;
+        let rpc_version = get_rpc_version(&client).await.with_context(|| {
+            format!("Failed to fetch RPC version of fork = {}", fork_target.name)
+        })?;
+
+        verify_rpc_version(fork_target, &rpc_version)?;
+    }
+
+    Ok(())
+}
+
+fn verify_rpc_version(fork_target: &ForkTarget, actual_rpc_version: &Version) -> Result<()> {
+    match &fork_target.rpc_version {
+        Some(expected_rpc_version) if expected_rpc_version != actual_rpc_version => bail!(
+            "Fork = {} is pinned to RPC version = {expected_rpc_version}, but url = {} uses RPC version = {actual_rpc_version}",
+            fork_target.name,
+            fork_target.url
+        ),
+        _ => Ok(()),
+    }
+}
+
+fn verify_chain_id(fork_target: &ForkTarget, actual_chain_id: &str) -> Result<()> {
+    match &fork_target.chain_id {
+        Some(expected_chain_id) if expected_chain_id != actual_chain_id => bail!(
+            "Fork = {} is expected to be on chain with id = {expected_chain_id}, but url = {} points to chain with id = {actual_chain_id}",
+            fork_target.name,
+            fork_target.url
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Names of fork targets referenced by test cases of the given test target
+#[must_use]
+pub fn used_fork_names(
+    test_target: &TestTargetWithConfig,
+    fork_targets: &[ForkTarget],
+) -> HashSet<String> {
+    test_target
+        .test_cases
+        .iter()
+        .flat_map(|case| match &case.config.fork_config {
+            Some(RawForkConfig::Named(name))
+            | Some(RawForkConfig::Overridden(OverriddenForkConfig { name, .. })) => {
+                vec![String::from(name.clone())]
+            }
+            Some(RawForkConfig::Multiple(names)) => {
+                names.iter().cloned().map(String::from).collect()
+            }
+            Some(RawForkConfig::Inline(_)) | None => vec![],
+        })
+        .flat_map(|name| {
+            let pattern = glob::Pattern::new(&name)
+                .ok()
+                .filter(|_| is_glob_pattern(&name));
+
+            fork_targets
+                .iter()
+                .filter(move |fork| match &pattern {
+                    Some(pattern) => pattern.matches(&fork.name),
+                    None => fork.name == name,
+                })
+                .map(|fork| fork.name.clone())
+        })
+        .collect()
+}
+
+fn is_glob_pattern(name: &str) -> bool {
+    name.contains(['*', '?', '['])
+}
+
+/// Expand fork config whose name is a glob pattern (e.g. `MAINNET_*`) into configs of all
+/// matching fork targets, each paired with the name of the fork target.
+/// Other configs are returned unchanged, without a fork target name.
+fn expand_fork_config(
+    fork_config: Option<RawForkConfig>,
+    fork_targets: &[ForkTarget],
+) -> Result<Vec<(Option<String>, Option<RawForkConfig>)>> {
+    let (pattern, block) = match &fork_config {
+        Some(RawForkConfig::Named(name)) => (String::from(name.clone()), None),
+        Some(RawForkConfig::Overridden(OverriddenForkConfig { name, block })) => {
+            (String::from(name.clone()), Some(block.clone()))
+        }
+        Some(RawForkConfig::Inline(_) | RawForkConfig::Multiple(_)) | None => {
+            return Ok(vec![(None, fork_config)]);
         }
     };
 
-    Ok(Some(ResolvedForkConfig { url, block_number }))
+    if !is_glob_pattern(&pattern) {
+        return Ok(vec![(None, fork_config)]);
+    }
+
+    let glob = glob::Pattern::new(&pattern)
+        .map_err(|err| anyhow!("Invalid fork name pattern = {pattern}: {err}"))?;
+
+    let expanded: Vec<_> = fork_targets
+        .iter()
+        .filter(|fork| glob.matches(&fork.name))
+        .map(|fork| {
+            let name = ByteArray::from(fork.name.as_str());
+            let fork_config = match block.clone() {
+                Some(block) => RawForkConfig::Overridden(OverriddenForkConfig { name, block }),
+                None => RawForkConfig::Named(name),
+            };
+
+            (Some(fork.name.clone()), Some(fork_config))
+        })
+        .collect();
+
+    if expanded.is_empty() {
+        bail!("No fork configuration in the Scarb.toml matches pattern = {pattern}");
+    }
+
+    Ok(expanded)
 }
 
 fn get_fork_target_from_runner_config<'a>(
@@ -115,12 +521,16 @@ fn replace_id_with_params(
 
             Ok(InlineForkConfig { url, block })
         }
+        RawForkConfig::Multiple(_) => {
+            bail!("Each of multiple forks attached to the test has to be resolved separately")
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::block_number_map::BlockNumberProvider;
     use cairo_lang_sierra::program::ProgramArtifact;
     use cairo_lang_sierra::{ids::GenericTypeId, program::Program};
     use forge_runner::package_tests::with_config::{TestCaseConfig, TestCaseWithConfig};
@@ -185,4 +595,360 @@ mod tests {
         .await
         .is_err());
     }
+
+    fn fork_targets_for_testing() -> Vec<ForkTarget> {
+        ["MAINNET_A", "MAINNET_B", "SEPOLIA"]
+            .into_iter()
+            .map(|name| ForkTarget::new(name, "http://example.com", "number", "1").unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn expand_fork_config_with_glob() {
+        let expanded = expand_fork_config(
+            Some(RawForkConfig::Named("MAINNET_*".into())),
+            &fork_targets_for_testing(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            expanded,
+            vec![
+                (
+                    Some("MAINNET_A".to_string()),
+                    Some(RawForkConfig::Named("MAINNET_A".into()))
+                ),
+                (
+                    Some("MAINNET_B".to_string()),
+                    Some(RawForkConfig::Named("MAINNET_B".into()))
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_fork_config_with_exact_name() {
+        let fork_config = Some(RawForkConfig::Named("SEPOLIA".into()));
+
+        let expanded =
+            expand_fork_config(fork_config.clone(), &fork_targets_for_testing()).unwrap();
+
+        assert_eq!(expanded, vec![(None, fork_config)]);
+    }
+
+    #[test]
+    fn expand_fork_config_with_glob_matching_nothing() {
+        let err = expand_fork_config(
+            Some(RawForkConfig::Named("GOERLI_*".into())),
+            &fork_targets_for_testing(),
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "No fork configuration in the Scarb.toml matches pattern = GOERLI_*"
+        );
+    }
+
+    #[test]
+    fn used_fork_names_include_glob_matches() {
+        let test_case = |name: &str, fork_config: Option<RawForkConfig>| TestCaseWithConfig {
+            name: name.to_string(),
+            config: TestCaseConfig {
+                available_gas: None,
+                ignored: false,
+                expected_result: ExpectedTestResult::Success,
+                fork_config,
+                fuzzer_config: None,
+            },
+            test_details: TestDetails::default(),
+        };
+
+        let test_target = TestTargetWithConfig {
+            sierra_program: program_for_testing(),
+            casm_program: Arc::new(compile_sierra_to_casm(&program_for_testing().program).unwrap()),
+            test_cases: vec![
+                test_case(
+                    "crate1::with_glob",
+                    Some(RawForkConfig::Named("MAINNET_*".into())),
+                ),
+                test_case("crate1::without_fork", None),
+            ],
+            tests_location: TestTargetLocation::Lib,
+        };
+
+        let used = used_fork_names(&test_target, &fork_targets_for_testing());
+
+        assert_eq!(
+            used,
+            HashSet::from(["MAINNET_A".to_string(), "MAINNET_B".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_multiple_fork_config() {
+        let resolved = resolve_fork_config(
+            Some(RawForkConfig::Multiple(vec![
+                "SEPOLIA".into(),
+                "MAINNET_A".into(),
+            ])),
+            &mut BlockNumberMap::default(),
+            &fork_targets_for_testing(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let names: Vec<_> = resolved
+            .selectable_forks
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        assert_eq!(names, ["SEPOLIA", "MAINNET_A"]);
+        assert_eq!(resolved.block_number, BlockNumber(1));
+        assert!(resolved
+            .selectable_forks
+            .iter()
+            .all(|(_, fork_config)| fork_config.selectable_forks.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn resolve_multiple_fork_config_with_duplicated_name() {
+        let err = resolve_fork_config(
+            Some(RawForkConfig::Multiple(vec![
+                "SEPOLIA".into(),
+                "SEPOLIA".into(),
+            ])),
+            &mut BlockNumberMap::default(),
+            &fork_targets_for_testing(),
+        )
+        .await
+        .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Fork = SEPOLIA is attached to the test more than once"
+        );
+    }
+
+    struct FixedBlockNumberProvider(u64);
+
+    #[async_trait::async_trait]
+    impl BlockNumberProvider for FixedBlockNumberProvider {
+        async fn latest_block_number(
+            &self,
+            _url: &Url,
+            _headers: &HashMap<String, String>,
+        ) -> Result<BlockNumber> {
+            Ok(BlockNumber(self.0))
+        }
+
+        async fn block_number_for_hash(
+            &self,
+            _url: &Url,
+            _headers: &HashMap<String, String>,
+            _hash: cairo_vm::Felt252,
+        ) -> Result<BlockNumber> {
+            Ok(BlockNumber(self.0 - 1))
+        }
+
+        async fn block_timestamp(
+            &self,
+            _url: &Url,
+            _headers: &HashMap<String, String>,
+            block_number: BlockNumber,
+        ) -> Result<u64> {
+            Ok(block_number.0 * 10)
+        }
+
+        async fn l1_block_timestamp(&self, _l1_url: &Url, l1_block_number: u64) -> Result<u64> {
+            Ok(l1_block_number)
+        }
+    }
+
+    #[tokio::test]
+    async fn pin_fork_targets_tags_with_mocked_latest_block() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(1234)));
+
+        let mut pinned = ForkTarget::new("PINNED", "http://example.com", "tag", "latest").unwrap();
+        pinned.pin_tag = true;
+        let not_pinned =
+            ForkTarget::new("NOT_PINNED", "http://example.com", "tag", "latest").unwrap();
+
+        let fork_targets =
+            pin_fork_targets_tags(vec![pinned, not_pinned], &mut block_number_map, None)
+                .await
+                .unwrap();
+
+        assert_eq!(fork_targets[0].block_id, BlockId::BlockNumber(1234));
+        assert_eq!(fork_targets[1].block_id, BlockId::BlockTag);
+    }
+
+    #[tokio::test]
+    async fn pin_fork_targets_offsets_with_mocked_latest_block() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(1234)));
+
+        let with_offset =
+            ForkTarget::new("WITH_OFFSET", "http://example.com", "offset", "-10").unwrap();
+
+        let fork_targets = pin_fork_targets_tags(vec![with_offset], &mut block_number_map, None)
+            .await
+            .unwrap();
+
+        assert_eq!(fork_targets[0].block_id, BlockId::BlockNumber(1224));
+        assert_eq!(fork_targets[0].latest_offset, None);
+    }
+
+    #[tokio::test]
+    async fn pin_fork_targets_tags_with_lockfile() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(1234)));
+
+        let locked = ForkTarget::new("LOCKED", "http://example.com", "tag", "latest").unwrap();
+        let not_locked =
+            ForkTarget::new("NOT_LOCKED", "http://example.com", "tag", "latest").unwrap();
+        let with_number = ForkTarget::new("NUMBER", "http://example.com", "number", "7").unwrap();
+        let mut fork_lockfile = ForkLockfile::default();
+        fork_lockfile.lock(&locked, BlockNumber(1000));
+
+        let fork_targets = pin_fork_targets_tags(
+            vec![locked.clone(), not_locked.clone(), with_number.clone()],
+            &mut block_number_map,
+            Some(&mut fork_lockfile),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fork_targets[0].block_id, BlockId::BlockNumber(1000));
+        assert_eq!(fork_targets[1].block_id, BlockId::BlockNumber(1234));
+        assert_eq!(fork_targets[2].block_id, BlockId::BlockNumber(7));
+        assert_eq!(fork_lockfile.block_number(&locked), Some(BlockNumber(1000)));
+        assert_eq!(
+            fork_lockfile.block_number(&not_locked),
+            Some(BlockNumber(1234))
+        );
+        assert_eq!(fork_lockfile.block_number(&with_number), None);
+    }
+
+    #[tokio::test]
+    async fn pin_fork_targets_l1_block_numbers_with_mocked_timestamps() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(1234)));
+
+        let mut with_l1_number =
+            ForkTarget::new("WITH_L1_NUMBER", "http://example.com", "l1_number", "5005").unwrap();
+        with_l1_number.l1_url = Some(Url::parse("http://ethereum.example.com").unwrap());
+        let without_l1_url =
+            ForkTarget::new("WITHOUT_L1_URL", "http://example.com", "l1_number", "5005").unwrap();
+
+        let fork_targets = pin_fork_targets_tags(vec![with_l1_number], &mut block_number_map, None)
+            .await
+            .unwrap();
+
+        // Block 500 has timestamp 5000, the last one not newer than 5005
+        assert_eq!(fork_targets[0].block_id, BlockId::BlockNumber(500));
+        assert_eq!(fork_targets[0].l1_block_number, None);
+
+        let err = pin_fork_targets_tags(vec![without_l1_url], &mut block_number_map, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "block_id.l1_number of fork = WITHOUT_L1_URL requires l1_url to be set"
+        );
+    }
+
+    #[tokio::test]
+    async fn pin_fork_targets_offset_below_genesis() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(5)));
+
+        let with_offset =
+            ForkTarget::new("WITH_OFFSET", "http://example.com", "offset", "-10").unwrap();
+
+        let err = pin_fork_targets_tags(vec![with_offset], &mut block_number_map, None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "block_id.offset = -10 of fork = WITH_OFFSET points below the genesis block, latest block number = 5"
+        );
+    }
+
+    #[test]
+    fn verify_chain_id_mismatch() {
+        let mut fork_target =
+            ForkTarget::new("MAINNET", "http://example.com", "tag", "latest").unwrap();
+
+        assert!(verify_chain_id(&fork_target, "SN_SEPOLIA").is_ok());
+
+        fork_target.chain_id = Some("SN_MAIN".to_string());
+
+        assert!(verify_chain_id(&fork_target, "SN_MAIN").is_ok());
+        assert_eq!(
+            verify_chain_id(&fork_target, "SN_SEPOLIA")
+                .unwrap_err()
+                .to_string(),
+            "Fork = MAINNET is expected to be on chain with id = SN_MAIN, but url = http://example.com/ points to chain with id = SN_SEPOLIA"
+        );
+    }
+
+    #[test]
+    fn verify_rpc_version_mismatch() {
+        let mut fork_target =
+            ForkTarget::new("MAINNET", "http://example.com", "tag", "latest").unwrap();
+
+        assert!(verify_rpc_version(&fork_target, &Version::new(0, 6, 0)).is_ok());
+
+        fork_target.rpc_version = Some(Version::new(0, 7, 1));
+
+        assert!(verify_rpc_version(&fork_target, &Version::new(0, 7, 1)).is_ok());
+        assert_eq!(
+            verify_rpc_version(&fork_target, &Version::new(0, 7, 0))
+                .unwrap_err()
+                .to_string(),
+            "Fork = MAINNET is pinned to RPC version = 0.7.1, but url = http://example.com/ uses RPC version = 0.7.0"
+        );
+    }
+
+    #[tokio::test]
+    async fn check_fork_health_unreachable_endpoint() {
+        let fork_target =
+            ForkTarget::new("UNREACHABLE", "http://127.0.0.1:1", "tag", "latest").unwrap();
+
+        let err = check_fork_health(&fork_target).await.unwrap_err();
+
+        assert_eq!(err.to_string(), "Fork = UNREACHABLE is not reachable");
+    }
+
+    #[tokio::test]
+    async fn resolve_block_number_with_mocked_provider() {
+        let mut block_number_map =
+            BlockNumberMap::with_provider(Box::new(FixedBlockNumberProvider(1234)));
+        let urls = [Url::parse("http://example.com").unwrap()];
+
+        let latest = resolve_block_number(
+            &BlockId::BlockTag,
+            &urls,
+            &HashMap::new(),
+            &mut block_number_map,
+        )
+        .await
+        .unwrap();
+        let for_hash = resolve_block_number(
+            &BlockId::BlockHash(cairo_vm::Felt252::from(1)),
+            &urls,
+            &HashMap::new(),
+            &mut block_number_map,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(latest, BlockNumber(1234));
+        assert_eq!(for_hash, BlockNumber(1233));
+    }
 }
@@ -67,7 +67,7 @@ pub async fn run_for_test_target(
         let function = sierra_program
             .funcs
             .iter()
-            .find(|f| f.id.debug_name.as_ref().unwrap().ends_with(&case_name))
+            .find(|f| f.entry_point.0 == case.test_details.sierra_entry_point_statement_idx)
             .ok_or(RunnerError::MissingFunction { suffix: case_name })?;
 
         let args = function_args(function, &type_declarations);
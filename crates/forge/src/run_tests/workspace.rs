@@ -13,7 +13,7 @@ use forge_runner::{
 use forge_runner::{test_target_summary::TestTargetSummary, CACHE_DIR};
 use scarb_api::{
     metadata::{Metadata, MetadataCommandExt, PackageMetadata},
-    target_dir_for_workspace, ScarbCommand,
+    profile_target_dir, target_dir_for_workspace, verify_sierra_enabled, CasmCache, ScarbCommand,
 };
 use scarb_ui::args::PackagesFilter;
 use shared::consts::SNFORGE_TEST_FILTER;
@@ -35,14 +35,17 @@ pub async fn run_for_workspace(args: TestArgs) -> Result<ExitStatus> {
 
     warn_if_snforge_std_not_compatible(&scarb_metadata)?;
 
-    let snforge_target_dir_path =
-        target_dir_for_workspace(&scarb_metadata).join(&scarb_metadata.current_profile);
+    let snforge_target_dir_path = profile_target_dir(&scarb_metadata);
 
     let packages: Vec<PackageMetadata> = args
         .packages_filter
         .match_many(&scarb_metadata)
         .context("Failed to find any packages matching the specified filter")?;
 
+    for package in &packages {
+        verify_sierra_enabled(&scarb_metadata, &package.id)?;
+    }
+
     let filter = PackagesFilter::generate_for::<Metadata>(packages.iter());
 
     if args.exact {
@@ -61,6 +64,10 @@ pub async fn run_for_workspace(args: TestArgs) -> Result<ExitStatus> {
         args.no_optimization,
     )?;
 
+    if args.clear_casm_cache {
+        CasmCache::in_target_dir(&target_dir_for_workspace(&scarb_metadata)).clear()?;
+    }
+
     let mut block_number_map = BlockNumberMap::default();
     let mut all_failed_tests = vec![];
 
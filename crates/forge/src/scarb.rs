@@ -1,14 +1,16 @@
-use crate::scarb::config::{ForgeConfigFromScarb, RawForgeConfig};
-use anyhow::{Context, Result};
+use crate::scarb::config::{ForgeConfigFromScarb, ForkTarget, RawForgeConfig};
+use anyhow::{anyhow, bail, Context, Result};
 use cairo_lang_sierra::program::VersionedProgram;
-use camino::Utf8Path;
-use configuration::PackageConfig;
+use camino::{Utf8Path, Utf8PathBuf};
+use configuration::{load_manifest_tool_config, load_package_config, PackageConfig};
 use forge_runner::package_tests::raw::TestTargetRaw;
 use forge_runner::package_tests::TestTargetLocation;
-use scarb_api::ScarbCommand;
-use scarb_metadata::{PackageMetadata, TargetMetadata};
+use itertools::Itertools;
+use scarb_api::{ScarbCommand, StarknetContractArtifacts, TestType};
+use scarb_metadata::{Metadata, PackageId, PackageMetadata, TargetMetadata};
 use scarb_ui::args::{FeaturesSpec, PackagesFilter};
 use semver::Version;
+use shared::print::print_as_warning;
 use std::collections::HashMap;
 use std::fs::read_to_string;
 use std::io::ErrorKind;
@@ -32,6 +34,15 @@ impl PackageConfig for ForgeConfigFromScarb {
             .try_into()
             .context("Invalid config in Scarb.toml: ")
     }
+
+    fn interpolates_env_variables(path: &[String]) -> bool {
+        // `${VAR}` references in fork urls and headers are interpolated when parsing the fork
+        matches!(
+            path,
+            [fork, _, field, ..]
+                if fork == "fork" && ["url", "urls", "headers", "l1_url"].contains(&field.as_str())
+        )
+    }
 }
 
 #[must_use]
@@ -106,12 +117,12 @@ pub fn load_test_artifacts(
     let dedup_targets = test_targets_by_name(package);
 
     for (target_name, target) in dedup_targets {
-        let tests_location =
-            if target.params.get("test-type").and_then(|v| v.as_str()) == Some("unit") {
-                TestTargetLocation::Lib
-            } else {
-                TestTargetLocation::Tests
-            };
+        let test_type = TestType::from_target(target);
+        let tests_location = if test_type == Some(TestType::Unit) {
+            TestTargetLocation::Lib
+        } else {
+            TestTargetLocation::Tests
+        };
 
         let target_file = format!("{target_name}.test.sierra.json");
 
@@ -138,15 +149,113 @@ pub fn load_test_artifacts(
     Ok(targets)
 }
 
+/// Fails if the casm of any of the `contracts` is larger than `max_casm_size` bytes
+pub fn verify_casm_sizes(
+    contracts: &HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    max_casm_size: usize,
+) -> Result<()> {
+    let oversized = contracts
+        .iter()
+        .filter(|(_, (artifacts, _))| artifacts.casm.len() > max_casm_size)
+        .sorted_by_key(|(name, _)| *name)
+        .map(|(name, (artifacts, _))| format!("{name} ({} bytes)", artifacts.casm.len()))
+        .collect_vec();
+
+    if !oversized.is_empty() {
+        bail!(
+            "Casm of contracts = [{}] exceeds max_casm_size = {max_casm_size} bytes",
+            oversized.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Forks available to tests of `package`: forks defined in `Scarb.toml` of the workspace root
+/// merged with forks defined by the package itself.
+/// The workspace root may be a package itself or a virtual workspace, in both cases its forks
+/// are read from the `[tool.snforge]` section of its `Scarb.toml`.
+/// A package fork overrides the workspace fork with the same name.
+pub fn effective_forks_for_package(
+    metadata: &Metadata,
+    package: &PackageId,
+) -> Result<Vec<ForkTarget>> {
+    let package_forks = load_package_config::<ForgeConfigFromScarb>(metadata, package)?.fork;
+
+    let workspace_root_package = metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path == metadata.workspace.manifest_path);
+
+    let workspace_forks = match workspace_root_package {
+        Some(root_package) if root_package.id == *package => return Ok(package_forks),
+        Some(root_package) => {
+            load_package_config::<ForgeConfigFromScarb>(metadata, &root_package.id)?.fork
+        }
+        // Virtual workspace, its root is not a package, so it is missing from the metadata
+        None => {
+            load_manifest_tool_config::<ForgeConfigFromScarb>(&metadata.workspace.manifest_path)?
+                .fork
+        }
+    };
+    let package_name = &metadata
+        .get_package(package)
+        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))?
+        .name;
+
+    Ok(merge_fork_targets(
+        workspace_forks,
+        package_forks,
+        package_name,
+    ))
+}
+
+/// Workspace forks come first, with the ones overridden by package forks replaced in place,
+/// followed by the remaining package forks
+fn merge_fork_targets(
+    workspace_forks: Vec<ForkTarget>,
+    package_forks: Vec<ForkTarget>,
+    package_name: &str,
+) -> Vec<ForkTarget> {
+    let mut package_forks: Vec<Option<ForkTarget>> = package_forks.into_iter().map(Some).collect();
+
+    let mut forks = workspace_forks
+        .into_iter()
+        .map(|workspace_fork| {
+            let package_fork = package_forks
+                .iter_mut()
+                .find(|fork| {
+                    fork.as_ref()
+                        .is_some_and(|fork| fork.name == workspace_fork.name)
+                })
+                .and_then(Option::take);
+
+            match package_fork {
+                Some(package_fork) => {
+                    if package_fork != workspace_fork {
+                        print_as_warning(&anyhow!(
+                            "Fork = {} defined in the workspace root is overridden by package = {package_name}",
+                            workspace_fork.name
+                        ));
+                    }
+                    package_fork
+                }
+                None => workspace_fork,
+            }
+        })
+        .collect_vec();
+    forks.extend(package_forks.into_iter().flatten());
+
+    forks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::scarb::config::ForkTarget;
     use assert_fs::fixture::{FileWriteStr, PathChild, PathCopy};
     use assert_fs::TempDir;
-    use camino::Utf8PathBuf;
     use cheatnet::runtime_extensions::forge_config_extension::config::BlockId;
-    use configuration::load_package_config;
     use indoc::{formatdoc, indoc};
     use scarb_api::metadata::MetadataCommandExt;
     use scarb_metadata::PackageId;
@@ -241,6 +350,9 @@ mod tests {
                 fuzzer_runs: None,
                 fuzzer_seed: None,
                 max_n_steps: None,
+                max_casm_size: None,
+                fork_lockfile: None,
+                refresh_fork_lockfile: false,
                 detailed_resources: false,
                 save_trace_data: false,
                 build_profile: false,
@@ -392,7 +504,7 @@ mod tests {
         )
         .unwrap_err();
         assert!(
-            format!("{err:?}").contains("block_id = wrong_variant is not valid. Possible values are = \"number\", \"hash\" and \"tag\"")
+            format!("{err:?}").contains("block_id = wrong_variant is not valid. Possible values are = \"number\", \"hash\", \"tag\", \"offset\" and \"l1_number\"")
         );
     }
 
@@ -502,6 +614,9 @@ mod tests {
                 fuzzer_runs: None,
                 fuzzer_seed: None,
                 max_n_steps: None,
+                max_casm_size: None,
+                fork_lockfile: None,
+                refresh_fork_lockfile: false,
                 detailed_resources: false,
                 save_trace_data: false,
                 build_profile: false,
@@ -509,4 +624,140 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn get_forge_config_with_max_casm_size() {
+        let temp = setup_package("simple_package");
+        let content = indoc!(
+            r#"
+            [package]
+            name = "simple_package"
+            version = "0.1.0"
+
+            [tool.snforge]
+            max_casm_size = 100000
+            "#
+        );
+        temp.child("Scarb.toml").write_str(content).unwrap();
+
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
+
+        let config = load_package_config::<ForgeConfigFromScarb>(
+            &scarb_metadata,
+            &scarb_metadata.workspace.members[0],
+        )
+        .unwrap();
+
+        assert_eq!(config.max_casm_size, Some(100_000));
+    }
+
+    #[test]
+    fn verify_casm_sizes_names_oversized_contracts() {
+        let contracts = HashMap::from([
+            (
+                "Small".to_string(),
+                (
+                    StarknetContractArtifacts::new("sierra", "casm"),
+                    Utf8PathBuf::from("small.contract_class.json"),
+                ),
+            ),
+            (
+                "Big".to_string(),
+                (
+                    StarknetContractArtifacts::new("sierra", "a".repeat(11)),
+                    Utf8PathBuf::from("big.contract_class.json"),
+                ),
+            ),
+        ]);
+
+        verify_casm_sizes(&contracts, 11).unwrap();
+
+        let err = verify_casm_sizes(&contracts, 10).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Casm of contracts = [Big (11 bytes)] exceeds max_casm_size = 10 bytes"
+        );
+    }
+
+    #[test]
+    fn virtual_workspace_forks_are_merged_with_package_forks() {
+        let temp = tempdir_with_tool_versions().unwrap();
+        temp.child("simple_package")
+            .copy_from("tests/data/simple_package", &["**/*.cairo"])
+            .unwrap();
+        temp.child("simple_package/Scarb.toml")
+            .write_str(indoc!(
+                r#"
+                [package]
+                name = "simple_package"
+                version = "0.1.0"
+
+                [[tool.snforge.fork]]
+                name = "PACKAGE_FORK"
+                url = "http://package.rpc.url"
+                block_id.number = "2"
+                "#
+            ))
+            .unwrap();
+        temp.child("Scarb.toml")
+            .write_str(indoc!(
+                r#"
+                [workspace]
+                members = ["simple_package"]
+
+                [[tool.snforge.fork]]
+                name = "WORKSPACE_FORK"
+                url = "http://workspace.rpc.url"
+                block_id.number = "1"
+                "#
+            ))
+            .unwrap();
+
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
+
+        let forks =
+            effective_forks_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0])
+                .unwrap();
+
+        assert_eq!(
+            forks,
+            vec![
+                ForkTarget::new("WORKSPACE_FORK", "http://workspace.rpc.url", "number", "1")
+                    .unwrap(),
+                ForkTarget::new("PACKAGE_FORK", "http://package.rpc.url", "number", "2").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn package_forks_override_workspace_forks_by_name() {
+        let workspace_forks = vec![
+            ForkTarget::new("SHARED", "http://workspace.rpc.url", "tag", "latest").unwrap(),
+            ForkTarget::new("WORKSPACE_ONLY", "http://workspace.rpc.url", "number", "1").unwrap(),
+        ];
+        let package_forks = vec![
+            ForkTarget::new("PACKAGE_ONLY", "http://package.rpc.url", "number", "2").unwrap(),
+            ForkTarget::new("SHARED", "http://package.rpc.url", "number", "3").unwrap(),
+        ];
+
+        let forks = merge_fork_targets(workspace_forks, package_forks, "package");
+
+        assert_eq!(
+            forks,
+            vec![
+                ForkTarget::new("SHARED", "http://package.rpc.url", "number", "3").unwrap(),
+                ForkTarget::new("WORKSPACE_ONLY", "http://workspace.rpc.url", "number", "1")
+                    .unwrap(),
+                ForkTarget::new("PACKAGE_ONLY", "http://package.rpc.url", "number", "2").unwrap(),
+            ]
+        );
+    }
 }
@@ -1,9 +1,14 @@
 use anyhow::{anyhow, bail, Result};
+use camino::Utf8PathBuf;
 use cheatnet::runtime_extensions::forge_config_extension::config::BlockId;
 use itertools::Itertools;
+use semver::Version;
 use serde::Deserialize;
+use shared::consts::EXPECTED_RPC_VERSION;
+use shared::rpc::is_expected_version;
 use std::{
     collections::{HashMap, HashSet},
+    env,
     num::NonZeroU32,
 };
 use url::Url;
@@ -62,6 +67,13 @@ pub struct ForgeConfigFromScarb {
     pub fork: Vec<ForkTarget>,
     /// Limit of steps
     pub max_n_steps: Option<u32>,
+    /// Maximum size (in bytes) of the casm of a single contract, exceeding it fails the run
+    pub max_casm_size: Option<usize>,
+    /// Path (relative to the package root) of the file storing block numbers forks with
+    /// `block_id.tag` or `block_id.offset` resolved to
+    pub fork_lockfile: Option<Utf8PathBuf>,
+    /// Resolve block numbers of forks again, replacing the ones stored in `fork_lockfile`
+    pub refresh_fork_lockfile: bool,
 }
 
 #[non_exhaustive]
@@ -70,11 +82,101 @@ pub struct ForkTarget {
     pub name: String,
     pub url: Url,
     pub block_id: BlockId,
+    /// Maximum number of blocks a pinned `block_id.number` may lag behind the latest block
+    /// before a warning is emitted
+    pub max_staleness: Option<u64>,
+    /// Resolve `block_id.tag` to a block number once, before running the tests
+    pub pin_tag: bool,
+    /// Maximum number of RPC requests sent to the fork endpoint at the same time
+    pub max_concurrent_requests: Option<usize>,
+    /// Endpoints tried in order when `url` (or the previous fallback) is unreachable
+    pub fallback_urls: Vec<Url>,
+    /// Number of blocks before the latest one to fork from, set by `block_id.offset`.
+    /// Resolved to a block number once, before running the tests
+    pub latest_offset: Option<u64>,
+    /// Chain id (e.g. `SN_MAIN`) the fork endpoint is expected to serve, verified before running the tests
+    pub chain_id: Option<String>,
+    /// JSON-RPC spec version the fork endpoint is expected to use, verified before running the tests.
+    /// It is only an assertion: the fork client always uses [`EXPECTED_RPC_VERSION`],
+    /// so the pinned version has to match it and does not change the requests sent to the endpoint
+    pub rpc_version: Option<Version>,
+    /// HTTP headers sent with every RPC request to the fork endpoints, e.g. `Authorization`
+    pub headers: HashMap<String, String>,
+    /// Ethereum block number set by `block_id.l1_number`. Resolved once, before running the tests,
+    /// to the last block which is not newer than it
+    pub l1_block_number: Option<u64>,
+    /// Url of the Ethereum RPC provider used to resolve `block_id.l1_number`
+    pub l1_url: Option<Url>,
+}
+
+fn parse_fork_url(url: &str) -> Result<Url> {
+    let parsed_url = Url::parse(url).map_err(|_| anyhow!("Failed to parse fork url"))?;
+    match parsed_url.scheme() {
+        "http" | "https" | "ws" | "wss" => Ok(parsed_url),
+        scheme => bail!("Fork url scheme = {scheme} is not supported. Possible values are = \"http\", \"https\", \"ws\" and \"wss\""),
+    }
+}
+
+fn parse_l1_url(url: &str) -> Result<Url> {
+    let parsed_url = Url::parse(url).map_err(|_| anyhow!("Failed to parse l1_url"))?;
+    match parsed_url.scheme() {
+        "http" | "https" => Ok(parsed_url),
+        scheme => bail!("l1_url scheme = {scheme} is not supported. Possible values are = \"http\" and \"https\""),
+    }
+}
+
+fn parse_rpc_version(fork_name: &str, rpc_version: &str) -> Result<Version> {
+    let version = Version::parse(rpc_version).map_err(|_| {
+        anyhow!("rpc_version = {rpc_version} of fork = {fork_name} is not a valid version")
+    })?;
+
+    if !is_expected_version(&version) {
+        bail!(
+            "rpc_version = {rpc_version} of fork = {fork_name} is not supported. Supported RPC version = {EXPECTED_RPC_VERSION}"
+        );
+    }
+
+    Ok(version)
+}
+
+/// Replaces every `${VAR}` reference in the `field` value of the fork (e.g. its url) with
+/// the value of the `VAR` variable returned by `env_var`
+fn interpolate_env_variables(
+    fork_name: &str,
+    field: &str,
+    value: &str,
+    env_var: &impl Fn(&str) -> Option<String>,
+) -> Result<String> {
+    let mut interpolated = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            bail!(
+                "The {field} of fork = {fork_name} has an unclosed environment variable reference"
+            );
+        };
+        let variable = &rest[start + 2..start + length];
+        let value = env_var(variable).ok_or_else(|| {
+            anyhow!(
+                "Environment variable = {variable} used in {field} of fork = {fork_name} is not set"
+            )
+        })?;
+
+        interpolated.push_str(&rest[..start]);
+        interpolated.push_str(&value);
+        rest = &rest[start + length + 1..];
+    }
+    interpolated.push_str(rest);
+
+    Ok(interpolated)
 }
 
 impl ForkTarget {
     pub fn new(name: &str, url: &str, block_id_type: &str, block_id_value: &str) -> Result<Self> {
-        let parsed_url = Url::parse(url).map_err(|_| anyhow!("Failed to parse fork url"))?;
+        let parsed_url = parse_fork_url(url)?;
+        let mut latest_offset = None;
+        let mut l1_block_number = None;
         let block_id = match block_id_type {
             "number" => BlockId::BlockNumber(
                 block_id_value
@@ -90,13 +192,41 @@ impl ForkTarget {
                 "latest" => BlockId::BlockTag,
                 _ => bail!("block_id.tag can only be equal to latest"),
             },
-            block_id_key => bail!("block_id = {block_id_key} is not valid. Possible values are = \"number\", \"hash\" and \"tag\""),
+            "offset" => {
+                let offset: i64 = block_id_value
+                    .parse()
+                    .map_err(|_| anyhow!("Failed to parse block offset"))?;
+                if offset > 0 {
+                    bail!("block_id.offset = {offset} is not valid. It is counted back from the latest block, so it cannot be positive");
+                }
+                latest_offset = Some(offset.unsigned_abs());
+                BlockId::BlockTag
+            }
+            "l1_number" => {
+                l1_block_number = Some(
+                    block_id_value
+                        .parse()
+                        .map_err(|_| anyhow!("Failed to parse L1 block number"))?,
+                );
+                BlockId::BlockTag
+            }
+            block_id_key => bail!("block_id = {block_id_key} is not valid. Possible values are = \"number\", \"hash\", \"tag\", \"offset\" and \"l1_number\""),
         };
 
         Ok(Self {
             name: name.to_string(),
             url: parsed_url,
             block_id,
+            max_staleness: None,
+            pin_tag: false,
+            max_concurrent_requests: None,
+            fallback_urls: vec![],
+            latest_offset,
+            chain_id: None,
+            rpc_version: None,
+            headers: HashMap::new(),
+            l1_block_number,
+            l1_url: None,
         })
     }
 }
@@ -129,13 +259,31 @@ pub(crate) struct RawForgeConfig {
     pub fork: Vec<RawForkTarget>,
     /// Limit of steps
     pub max_n_steps: Option<u32>,
+    /// Maximum size (in bytes) of the casm of a single contract
+    pub max_casm_size: Option<usize>,
+    /// Path of the file storing block numbers resolved for forks
+    pub fork_lockfile: Option<Utf8PathBuf>,
+    #[serde(default)]
+    /// Resolve block numbers of forks again, replacing the ones stored in `fork_lockfile`
+    pub refresh_fork_lockfile: bool,
 }
 
 #[derive(Deserialize, Debug, PartialEq, Default, Clone)]
 pub(crate) struct RawForkTarget {
     pub name: String,
-    pub url: String,
+    pub url: Option<String>,
+    #[serde(default)]
+    pub urls: Vec<String>,
     pub block_id: HashMap<String, String>,
+    pub max_staleness: Option<u64>,
+    #[serde(default)]
+    pub pin_tag: bool,
+    pub max_concurrent_requests: Option<usize>,
+    pub chain_id: Option<String>,
+    pub rpc_version: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub l1_url: Option<String>,
 }
 
 fn validate_raw_fork_config(raw_config: RawForgeConfig) -> Result<RawForgeConfig> {
@@ -148,6 +296,13 @@ fn validate_raw_fork_config(raw_config: RawForgeConfig) -> Result<RawForgeConfig
         bail!("Some fork names are duplicated");
     }
 
+    forks
+        .iter()
+        .try_for_each(|fork| match (&fork.url, fork.urls.is_empty()) {
+            (Some(_), true) | (None, false) => Ok(()),
+            _ => bail!("Exactly one of url and urls should be set per fork"),
+        })?;
+
     forks
         .iter()
         .try_for_each(|fork| match fork.block_id.len() {
@@ -155,27 +310,117 @@ fn validate_raw_fork_config(raw_config: RawForgeConfig) -> Result<RawForgeConfig
             _ => bail!("block_id should be set once per fork"),
         })?;
 
+    forks
+        .iter()
+        .filter(|fork| fork.max_staleness.is_some())
+        .try_for_each(|fork| {
+            if fork.block_id.contains_key("number") {
+                Ok(())
+            } else {
+                bail!("max_staleness can only be used with block_id.number")
+            }
+        })?;
+
+    forks
+        .iter()
+        .filter(|fork| fork.pin_tag)
+        .try_for_each(|fork| {
+            if fork.block_id.contains_key("tag") {
+                Ok(())
+            } else {
+                bail!("pin_tag can only be used with block_id.tag")
+            }
+        })?;
+
+    forks.iter().try_for_each(|fork| {
+        match (
+            fork.block_id.contains_key("l1_number"),
+            fork.l1_url.is_some(),
+        ) {
+            (true, false) => bail!("block_id.l1_number requires l1_url to be set"),
+            (false, true) => bail!("l1_url can only be used with block_id.l1_number"),
+            _ => Ok(()),
+        }
+    })?;
+
+    if forks
+        .iter()
+        .any(|fork| fork.max_concurrent_requests == Some(0))
+    {
+        bail!("max_concurrent_requests must be greater than 0");
+    }
+
     Ok(raw_config)
 }
 
+/// Fork target defined by `raw_fork_target`, with `${VAR}` references in its urls and headers
+/// replaced with the values returned by `env_var`
+fn fork_target_from_raw(
+    raw_fork_target: &RawForkTarget,
+    env_var: &impl Fn(&str) -> Option<String>,
+) -> Result<ForkTarget> {
+    let (block_id_type, block_id_value) = raw_fork_target.block_id.iter().exactly_one().unwrap();
+
+    let urls = raw_fork_target
+        .url
+        .iter()
+        .chain(&raw_fork_target.urls)
+        .map(|url| interpolate_env_variables(&raw_fork_target.name, "url", url, env_var))
+        .collect::<Result<Vec<_>>>()?;
+    let mut urls = urls.iter();
+    let url = urls.next().expect("url presence is validated");
+
+    let mut fork_target = ForkTarget::new(
+        raw_fork_target.name.as_str(),
+        url.as_str(),
+        block_id_type,
+        block_id_value,
+    )?;
+    fork_target.fallback_urls = urls.map(|url| parse_fork_url(url)).collect::<Result<_>>()?;
+    fork_target.max_staleness = raw_fork_target.max_staleness;
+    fork_target.pin_tag = raw_fork_target.pin_tag;
+    fork_target.max_concurrent_requests = raw_fork_target.max_concurrent_requests;
+    fork_target.chain_id = raw_fork_target.chain_id.clone();
+    fork_target.rpc_version = raw_fork_target
+        .rpc_version
+        .as_deref()
+        .map(|rpc_version| parse_rpc_version(&raw_fork_target.name, rpc_version))
+        .transpose()?;
+    fork_target.headers = raw_fork_target
+        .headers
+        .iter()
+        .map(|(name, value)| {
+            let field = format!("header = {name}");
+            let value = interpolate_env_variables(&raw_fork_target.name, &field, value, env_var)?;
+
+            Ok((name.clone(), value))
+        })
+        .collect::<Result<_>>()?;
+    fork_target.l1_url = raw_fork_target
+        .l1_url
+        .as_deref()
+        .map(|l1_url| {
+            let l1_url =
+                interpolate_env_variables(&raw_fork_target.name, "l1_url", l1_url, env_var)?;
+            parse_l1_url(&l1_url)
+        })
+        .transpose()?;
+
+    Ok(fork_target)
+}
+
 impl TryFrom<RawForgeConfig> for ForgeConfigFromScarb {
     type Error = anyhow::Error;
 
     fn try_from(value: RawForgeConfig) -> Result<Self, Self::Error> {
         let value = validate_raw_fork_config(value)?;
-        let mut fork_targets = vec![];
-
-        for raw_fork_target in value.fork {
-            let (block_id_type, block_id_value) =
-                raw_fork_target.block_id.iter().exactly_one().unwrap();
-
-            fork_targets.push(ForkTarget::new(
-                raw_fork_target.name.as_str(),
-                raw_fork_target.url.as_str(),
-                block_id_type,
-                block_id_value,
-            )?);
-        }
+        let fork_targets = value
+            .fork
+            .iter()
+            .map(|raw_fork_target| {
+                fork_target_from_raw(raw_fork_target, &|name: &str| env::var(name).ok())
+            })
+            .collect::<Result<Vec<_>>>()?;
 
         Ok(ForgeConfigFromScarb {
             exit_first: value.exit_first,
@@ -187,6 +432,9 @@ impl TryFrom<RawForgeConfig> for ForgeConfigFromScarb {
             coverage: value.coverage,
             fork: fork_targets,
             max_n_steps: value.max_n_steps,
+            max_casm_size: value.max_casm_size,
+            fork_lockfile: value.fork_lockfile,
+            refresh_fork_lockfile: value.refresh_fork_lockfile,
         })
     }
 }
@@ -263,6 +511,13 @@ mod tests {
         assert_eq!(result.unwrap_err().to_string(), "Failed to parse fork url");
     }
 
+    #[test]
+    fn test_fork_target_new_websocket_url() {
+        let fork_target =
+            ForkTarget::new("TestFork", "wss://example.com", "number", "123").unwrap();
+        assert_eq!(fork_target.url.scheme(), "wss");
+    }
+
     #[test]
     fn test_fork_target_new_invalid_block_id_value_number() {
         let name = "TestFork";
@@ -292,4 +547,355 @@ mod tests {
             "Failed to parse block hash"
         );
     }
+
+    #[test]
+    fn test_fork_target_new_valid_offset() {
+        let fork_target =
+            ForkTarget::new("TestFork", "http://example.com", "offset", "-10").unwrap();
+
+        assert_eq!(fork_target.block_id, BlockId::BlockTag);
+        assert_eq!(fork_target.latest_offset, Some(10));
+    }
+
+    #[test]
+    fn test_fork_target_new_positive_offset() {
+        let result = ForkTarget::new("TestFork", "http://example.com", "offset", "10");
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "block_id.offset = 10 is not valid. It is counted back from the latest block, so it cannot be positive"
+        );
+    }
+
+    #[test]
+    fn test_max_staleness_requires_block_number() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                urls: vec![],
+                block_id: HashMap::from([("tag".to_string(), "latest".to_string())]),
+                max_staleness: Some(10),
+                pin_tag: false,
+                max_concurrent_requests: None,
+                chain_id: None,
+                rpc_version: None,
+                headers: HashMap::new(),
+                l1_url: None,
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "max_staleness can only be used with block_id.number"
+        );
+    }
+
+    #[test]
+    fn test_max_staleness_is_passed_to_fork_target() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                urls: vec![],
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                max_staleness: Some(10),
+                pin_tag: false,
+                max_concurrent_requests: None,
+                chain_id: None,
+                rpc_version: None,
+                headers: HashMap::new(),
+                l1_url: None,
+            }],
+            ..Default::default()
+        };
+
+        let config = ForgeConfigFromScarb::try_from(raw_config).unwrap();
+        assert_eq!(config.fork[0].max_staleness, Some(10));
+    }
+
+    #[test]
+    fn test_chain_id_is_passed_to_fork_target() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                block_id: HashMap::from([("tag".to_string(), "latest".to_string())]),
+                chain_id: Some("SN_MAIN".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let config = ForgeConfigFromScarb::try_from(raw_config).unwrap();
+        assert_eq!(config.fork[0].chain_id, Some("SN_MAIN".to_string()));
+    }
+
+    #[test]
+    fn test_rpc_version_is_passed_to_fork_target() {
+        let raw_fork_target = |rpc_version: &str| RawForkTarget {
+            name: "TestFork".to_string(),
+            url: Some("http://example.com".to_string()),
+            block_id: HashMap::from([("tag".to_string(), "latest".to_string())]),
+            rpc_version: Some(rpc_version.to_string()),
+            ..Default::default()
+        };
+
+        let config = ForgeConfigFromScarb::try_from(RawForgeConfig {
+            fork: vec![raw_fork_target(EXPECTED_RPC_VERSION)],
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(
+            config.fork[0].rpc_version,
+            Some(Version::parse(EXPECTED_RPC_VERSION).unwrap())
+        );
+
+        let err = ForgeConfigFromScarb::try_from(RawForgeConfig {
+            fork: vec![raw_fork_target("latest")],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "rpc_version = latest of fork = TestFork is not a valid version"
+        );
+
+        let err = ForgeConfigFromScarb::try_from(RawForgeConfig {
+            fork: vec![raw_fork_target("0.1.0")],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("rpc_version = 0.1.0 of fork = TestFork is not supported. Supported RPC version = {EXPECTED_RPC_VERSION}")
+        );
+    }
+
+    #[test]
+    fn test_pin_tag_requires_block_tag() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                urls: vec![],
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                max_staleness: None,
+                pin_tag: true,
+                max_concurrent_requests: None,
+                chain_id: None,
+                rpc_version: None,
+                headers: HashMap::new(),
+                l1_url: None,
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "pin_tag can only be used with block_id.tag"
+        );
+    }
+
+    #[test]
+    fn test_max_concurrent_requests_must_be_positive() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                urls: vec![],
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                max_staleness: None,
+                pin_tag: false,
+                max_concurrent_requests: Some(0),
+                chain_id: None,
+                rpc_version: None,
+                headers: HashMap::new(),
+                l1_url: None,
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "max_concurrent_requests must be greater than 0"
+        );
+    }
+
+    #[test]
+    fn test_l1_number_is_passed_to_fork_target() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                block_id: HashMap::from([("l1_number".to_string(), "19000000".to_string())]),
+                l1_url: Some("http://ethereum.example.com".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let config = ForgeConfigFromScarb::try_from(raw_config).unwrap();
+        assert_eq!(config.fork[0].block_id, BlockId::BlockTag);
+        assert_eq!(config.fork[0].l1_block_number, Some(19_000_000));
+        assert_eq!(
+            config.fork[0].l1_url,
+            Some(Url::parse("http://ethereum.example.com").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_l1_number_requires_l1_url() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://example.com".to_string()),
+                block_id: HashMap::from([("l1_number".to_string(), "19000000".to_string())]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "block_id.l1_number requires l1_url to be set"
+        );
+    }
+
+    #[test]
+    fn test_urls_are_split_into_url_and_fallbacks() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: None,
+                urls: vec![
+                    "http://first.com".to_string(),
+                    "http://second.com".to_string(),
+                ],
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let config = ForgeConfigFromScarb::try_from(raw_config).unwrap();
+        assert_eq!(config.fork[0].url, Url::parse("http://first.com").unwrap());
+        assert_eq!(
+            config.fork[0].fallback_urls,
+            vec![Url::parse("http://second.com").unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_url_and_urls_are_exclusive() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("http://first.com".to_string()),
+                urls: vec!["http://second.com".to_string()],
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Exactly one of url and urls should be set per fork"
+        );
+    }
+
+    #[test]
+    fn test_env_variables_are_interpolated_in_urls() {
+        let env_var = |name: &str| match name {
+            "FORK_RPC_HOST" => Some("rpc.example.com".to_string()),
+            "FORK_API_KEY" => Some("secret".to_string()),
+            _ => None,
+        };
+
+        let raw_fork_target = RawForkTarget {
+            name: "TestFork".to_string(),
+            url: None,
+            urls: vec![
+                "https://${FORK_RPC_HOST}/v1/${FORK_API_KEY}".to_string(),
+                "http://fallback.com".to_string(),
+            ],
+            block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+            ..Default::default()
+        };
+
+        let fork_target = fork_target_from_raw(&raw_fork_target, &env_var).unwrap();
+        assert_eq!(
+            fork_target.url,
+            Url::parse("https://rpc.example.com/v1/secret").unwrap()
+        );
+        assert_eq!(
+            fork_target.fallback_urls,
+            vec![Url::parse("http://fallback.com").unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_unset_env_variable_in_url() {
+        let raw_config = RawForgeConfig {
+            fork: vec![RawForkTarget {
+                name: "TestFork".to_string(),
+                url: Some("${SNFORGE_TEST_UNSET_FORK_URL}".to_string()),
+                block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let result = ForgeConfigFromScarb::try_from(raw_config);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Environment variable = SNFORGE_TEST_UNSET_FORK_URL used in url of fork = TestFork is not set"
+        );
+    }
+
+    #[test]
+    fn test_env_variables_interpolated_in_headers() {
+        let env_var = |name: &str| (name == "FORK_HEADER_API_KEY").then(|| "secret".to_string());
+
+        let raw_fork_target = RawForkTarget {
+            name: "TestFork".to_string(),
+            url: Some("http://example.com".to_string()),
+            block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+            headers: HashMap::from([(
+                "x-api-key".to_string(),
+                "${FORK_HEADER_API_KEY}".to_string(),
+            )]),
+            ..Default::default()
+        };
+
+        let fork_target = fork_target_from_raw(&raw_fork_target, &env_var).unwrap();
+        assert_eq!(
+            fork_target.headers,
+            HashMap::from([("x-api-key".to_string(), "secret".to_string())])
+        );
+
+        let raw_fork_target = RawForkTarget {
+            name: "TestFork".to_string(),
+            url: Some("http://example.com".to_string()),
+            block_id: HashMap::from([("number".to_string(), "123".to_string())]),
+            headers: HashMap::from([(
+                "Authorization".to_string(),
+                "Bearer ${FORK_TOKEN}".to_string(),
+            )]),
+            ..Default::default()
+        };
+
+        let result = fork_target_from_raw(&raw_fork_target, &env_var);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Environment variable = FORK_TOKEN used in header = Authorization of fork = TestFork is not set"
+        );
+    }
 }
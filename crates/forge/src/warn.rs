@@ -1,12 +1,16 @@
-use anyhow::{anyhow, Result};
+use crate::block_number_map::BlockNumberMap;
+use crate::run_tests::resolve_config::resolve_block_number;
+use crate::scarb::config::ForkTarget;
+use anyhow::{anyhow, bail, Result};
+use cheatnet::runtime_extensions::forge_config_extension::config::BlockId;
 use forge_runner::package_tests::with_config_resolved::TestTargetWithResolvedConfig;
-use scarb_api::{package_matches_version_requirement, ScarbCommand};
+use scarb_api::{package_version, ScarbCommand};
 use scarb_metadata::Metadata;
 use semver::{Comparator, Op, Version, VersionReq};
 use shared::print::print_as_warning;
-use shared::rpc::create_rpc_client;
+use shared::rpc::create_rpc_client_with_headers;
 use shared::verify_and_warn_if_incompatible_rpc_version;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use url::Url;
 
 pub(crate) fn warn_if_available_gas_used_with_incompatible_scarb_version(
@@ -31,7 +35,7 @@ pub(crate) fn warn_if_available_gas_used_with_incompatible_scarb_version(
 pub(crate) async fn warn_if_incompatible_rpc_version(
     test_targets: &[TestTargetWithResolvedConfig],
 ) -> Result<()> {
-    let mut urls = HashSet::<Url>::new();
+    let mut urls = HashMap::<Url, HashMap<String, String>>::new();
 
     // collect urls
     for test_target in test_targets {
@@ -40,15 +44,15 @@ pub(crate) async fn warn_if_incompatible_rpc_version(
             .iter()
             .filter_map(|tc| tc.config.fork_config.as_ref())
         {
-            urls.insert(fork_config.url.clone());
+            urls.insert(fork_config.url.clone(), fork_config.headers.clone());
         }
     }
 
     let mut handles = Vec::with_capacity(urls.len());
 
-    for url in urls {
+    for (url, headers) in urls {
         handles.push(tokio::spawn(async move {
-            let client = create_rpc_client(url.as_ref())?;
+            let client = create_rpc_client_with_headers(url.as_ref(), &headers)?;
 
             verify_and_warn_if_incompatible_rpc_version(&client, &url).await
         }));
@@ -61,28 +65,198 @@ pub(crate) async fn warn_if_incompatible_rpc_version(
     Ok(())
 }
 
-fn snforge_std_version_requirement() -> VersionReq {
-    let version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+/// Warns about forks used by the tests whose pinned block lags behind the latest block by more
+/// than `max_staleness`. Failing to fetch the latest block is a warning too, it never fails the run
+pub(crate) async fn warn_if_pinned_fork_is_stale(
+    fork_targets: &[ForkTarget],
+    used_fork_names: &HashSet<String>,
+    block_number_map: &mut BlockNumberMap,
+) {
+    for fork_target in fork_targets
+        .iter()
+        .filter(|fork_target| used_fork_names.contains(&fork_target.name))
+    {
+        let (BlockId::BlockNumber(pinned_block_number), Some(max_staleness)) =
+            (&fork_target.block_id, fork_target.max_staleness)
+        else {
+            continue;
+        };
+
+        let urls: Vec<_> = std::iter::once(fork_target.url.clone())
+            .chain(fork_target.fallback_urls.iter().cloned())
+            .collect();
+        let latest_block_number = match resolve_block_number(
+            &BlockId::BlockTag,
+            &urls,
+            &fork_target.headers,
+            block_number_map,
+        )
+        .await
+        {
+            Ok(latest_block_number) => latest_block_number.0,
+            Err(err) => {
+                print_as_warning(&anyhow!(
+                    "Could not check if fork {} is stale, failed to fetch the latest block: {err}",
+                    fork_target.name
+                ));
+                continue;
+            }
+        };
+        let staleness = latest_block_number.saturating_sub(*pinned_block_number);
+
+        if staleness > max_staleness {
+            print_as_warning(&anyhow!(
+                "Fork {} is pinned to block {pinned_block_number} which is {staleness} blocks behind the latest block {latest_block_number} (max_staleness = {max_staleness})",
+                fork_target.name
+            ));
+        }
+    }
+}
+
+fn snforge_std_version_requirement(snforge_version: &Version) -> VersionReq {
     let comparator = Comparator {
         op: Op::Exact,
-        major: version.major,
-        minor: Some(version.minor),
-        patch: Some(version.patch),
-        pre: version.pre,
+        major: snforge_version.major,
+        minor: Some(snforge_version.minor),
+        patch: Some(snforge_version.patch),
+        pre: snforge_version.pre.clone(),
     };
     VersionReq {
         comparators: vec![comparator],
     }
 }
 
+/// Minimal Scarb versions `snforge_std` can be compiled with, each paired with the first
+/// `snforge_std` version requiring it, from the newest one
+static MINIMAL_SCARB_VERSIONS_FOR_SNFORGE_STD: [(Version, Version); 2] = [
+    (Version::new(0, 27, 0), Version::new(2, 7, 0)),
+    (Version::new(0, 13, 0), Version::new(2, 4, 0)),
+];
+
+fn minimal_scarb_version_for_snforge_std(
+    snforge_std_version: &Version,
+) -> Option<&'static Version> {
+    MINIMAL_SCARB_VERSIONS_FOR_SNFORGE_STD
+        .iter()
+        .find(|(first_snforge_std_version, _)| snforge_std_version >= first_snforge_std_version)
+        .map(|(_, minimal_scarb_version)| minimal_scarb_version)
+}
+
 pub fn warn_if_snforge_std_not_compatible(scarb_metadata: &Metadata) -> Result<()> {
-    let snforge_std_version_requirement = snforge_std_version_requirement();
-    if !package_matches_version_requirement(
-        scarb_metadata,
-        "snforge_std",
-        &snforge_std_version_requirement,
-    )? {
-        print_as_warning(&anyhow!("Package snforge_std version does not meet the recommended version requirement {snforge_std_version_requirement}, it might result in unexpected behaviour"));
+    let snforge_version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
+    let snforge_std_version = package_version(scarb_metadata, "snforge_std")?;
+
+    if let Err(err) = verify_snforge_std_compatibility(
+        &snforge_version,
+        snforge_std_version,
+        &scarb_metadata.app_version_info.version,
+    ) {
+        print_as_warning(&err);
+    }
+    Ok(())
+}
+
+fn verify_snforge_std_compatibility(
+    snforge_version: &Version,
+    snforge_std_version: &Version,
+    scarb_version: &Version,
+) -> Result<()> {
+    let snforge_std_version_requirement = snforge_std_version_requirement(snforge_version);
+    if !snforge_std_version_requirement.matches(snforge_std_version) {
+        bail!("Package snforge_std version = {snforge_std_version} does not meet the version requirement = {snforge_std_version_requirement} of snforge = {snforge_version}, it might result in unexpected behaviour");
     }
+    if let Some(minimal_scarb_version) = minimal_scarb_version_for_snforge_std(snforge_std_version)
+    {
+        if scarb_version < minimal_scarb_version {
+            bail!("Scarb version = {scarb_version} is lower than the minimal version = {minimal_scarb_version} required by snforge_std = {snforge_std_version}, it might result in unexpected behaviour");
+        }
+    }
+
     Ok(())
 }
+
+pub(crate) fn warn_if_forks_unused(fork_targets: &[ForkTarget], used_fork_names: &HashSet<String>) {
+    let unused: Vec<_> = fork_targets
+        .iter()
+        .map(|fork_target| fork_target.name.as_str())
+        .filter(|name| !used_fork_names.contains(*name))
+        .collect();
+
+    if !unused.is_empty() {
+        print_as_warning(&anyhow!(
+            "Following forks are defined in Scarb.toml but not used by any test: {}",
+            unused.join(", ")
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snforge_std_compatibility() {
+        let snforge_version = Version::new(0, 32, 0);
+        let scarb_version = Version::new(2, 8, 0);
+
+        assert!(verify_snforge_std_compatibility(
+            &snforge_version,
+            &snforge_version,
+            &scarb_version
+        )
+        .is_ok());
+
+        let err = verify_snforge_std_compatibility(
+            &snforge_version,
+            &Version::new(0, 31, 0),
+            &scarb_version,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Package snforge_std version = 0.31.0 does not meet the version requirement = =0.32.0 of snforge = 0.32.0, it might result in unexpected behaviour"
+        );
+
+        let err = verify_snforge_std_compatibility(
+            &snforge_version,
+            &snforge_version,
+            &Version::new(2, 6, 4),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Scarb version = 2.6.4 is lower than the minimal version = 2.7.0 required by snforge_std = 0.32.0, it might result in unexpected behaviour"
+        );
+    }
+
+    #[test]
+    fn minimal_scarb_version_depends_on_snforge_std_version() {
+        let snforge_version = Version::new(0, 20, 0);
+
+        assert!(verify_snforge_std_compatibility(
+            &snforge_version,
+            &snforge_version,
+            &Version::new(2, 6, 4)
+        )
+        .is_ok());
+
+        let err = verify_snforge_std_compatibility(
+            &snforge_version,
+            &snforge_version,
+            &Version::new(2, 3, 1),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Scarb version = 2.3.1 is lower than the minimal version = 2.4.0 required by snforge_std = 0.20.0, it might result in unexpected behaviour"
+        );
+
+        let snforge_version = Version::new(0, 12, 0);
+        assert!(verify_snforge_std_compatibility(
+            &snforge_version,
+            &snforge_version,
+            &Version::new(2, 3, 1)
+        )
+        .is_ok());
+    }
+}
@@ -13,8 +13,8 @@ use forge_runner::{
 };
 use indoc::formatdoc;
 use scarb_api::{
-    get_contracts_artifacts_and_source_sierra_paths, metadata::MetadataCommandExt, ScarbCommand,
-    StarknetContractArtifacts,
+    abi_from_sierra, get_contracts_artifacts_and_source_sierra_paths, metadata::MetadataCommandExt,
+    ScarbCommand, StarknetContractArtifacts,
 };
 use shared::command::CommandExt;
 use std::{
@@ -108,7 +108,7 @@ impl Contract {
         .ok_or(anyhow!("there is no contract with name {}", self.name))?
         .0;
 
-        Ok((contract.sierra, contract.casm))
+        Ok((contract.sierra()?.to_string(), contract.casm.to_string()))
     }
 }
 
@@ -205,7 +205,7 @@ impl<'a> TestCase {
                 Ok((
                     name,
                     (
-                        StarknetContractArtifacts { sierra, casm },
+                        StarknetContractArtifacts::new(sierra, casm),
                         Default::default(),
                     ),
                 ))
@@ -46,6 +46,8 @@ pub fn run_test_case(test: &TestCase) -> Vec<TestTargetSummary> {
     rt.block_on(run_for_package(
         RunForPackageArgs {
             test_targets: raw_test_targets,
+            fork_lockfile: None,
+            refresh_fork_lockfile: false,
             package_name: "test_package".to_string(),
             tests_filter: TestsFilter::from_flags(
                 None,
@@ -47,6 +47,18 @@ mod l1_handler_executor {
         self.token_id.write(data.token_id);
     }
 
+    #[l1_handler]
+    fn store_tx_nonce(ref self: ContractState, from_address: felt252) {
+        self.balance.write(starknet::get_tx_info().unbox().nonce);
+    }
+
+    #[l1_handler]
+    fn store_block_info(ref self: ContractState, from_address: felt252) {
+        let block_info = starknet::get_block_info().unbox();
+        self.balance.write(block_info.block_timestamp.into());
+        self.token_id.write(block_info.block_number.into());
+    }
+
     #[l1_handler]
     fn panicking_l1_handler(ref self: ContractState, from_address: felt252) {
         panic(array!['custom', 'panic']);
@@ -0,0 +1,53 @@
+use starknet::EthAddress;
+
+#[starknet::interface]
+trait ISideEffectsChecker<TContractState> {
+    fn emit_around_message(
+        ref self: TContractState, some_data: felt252, payload: Array<felt252>, to_address: EthAddress
+    );
+}
+
+#[starknet::contract]
+mod SideEffectsChecker {
+    use starknet::{EthAddress, SyscallResultTrait, send_message_to_l1_syscall};
+
+    #[storage]
+    struct Storage {}
+
+    #[event]
+    #[derive(Drop, starknet::Event)]
+    enum Event {
+        BeforeMessage: BeforeMessage,
+        AfterMessage: AfterMessage,
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct BeforeMessage {
+        some_data: felt252
+    }
+
+    #[derive(Drop, starknet::Event)]
+    struct AfterMessage {
+        some_data: felt252
+    }
+
+    #[abi(embed_v0)]
+    impl ISideEffectsChecker of super::ISideEffectsChecker<ContractState> {
+        fn emit_around_message(
+            ref self: ContractState,
+            some_data: felt252,
+            payload: Array<felt252>,
+            to_address: EthAddress
+        ) {
+            self.emit(Event::BeforeMessage(BeforeMessage { some_data }));
+            send_message_to_l1_syscall(to_address.into(), payload.span()).unwrap_syscall();
+            self.emit(Event::AfterMessage(AfterMessage { some_data }));
+        }
+    }
+
+    #[l1_handler]
+    fn relay_to_l1(ref self: ContractState, from_address: felt252, some_data: felt252) {
+        send_message_to_l1_syscall(from_address, array![some_data].span()).unwrap_syscall();
+        self.emit(Event::AfterMessage(AfterMessage { some_data }));
+    }
+}
@@ -0,0 +1,71 @@
+use indoc::indoc;
+use std::path::Path;
+use test_utils::runner::{assert_case_output_contains, assert_failed, assert_passed, Contract};
+use test_utils::running_tests::run_test_case;
+use test_utils::test_case;
+
+#[test]
+fn deploy_multiple_instances_from_class_hash() {
+    let test = test_case!(
+        indoc!(
+            r#"
+        use snforge_std::{ declare, deploy_from_class_hash, DeclareResultTrait };
+        use starknet::ContractAddress;
+
+        #[starknet::interface]
+        trait IHelloStarknet<TContractState> {
+            fn increase_balance(ref self: TContractState, amount: felt252);
+            fn get_balance(self: @TContractState) -> felt252;
+        }
+
+        #[test]
+        fn deploy_multiple_instances_from_class_hash() {
+            let class_hash = *declare("HelloStarknet").unwrap().contract_class().class_hash;
+
+            let (first_address, _) = deploy_from_class_hash(class_hash, @array![]).unwrap();
+            let (second_address, _) = deploy_from_class_hash(class_hash, @array![]).unwrap();
+            assert(first_address != second_address, 'addresses should differ');
+
+            let first = IHelloStarknetDispatcher { contract_address: first_address };
+            let second = IHelloStarknetDispatcher { contract_address: second_address };
+            first.increase_balance(5);
+
+            assert(first.get_balance() == 5, 'first balance mismatch');
+            assert(second.get_balance() == 0, 'second balance mismatch');
+        }
+    "#
+        ),
+        Contract::from_code_path(
+            "HelloStarknet".to_string(),
+            Path::new("tests/data/contracts/hello_starknet.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn deploy_from_undeclared_class_hash() {
+    let test = test_case!(indoc!(
+        r#"
+        use snforge_std::deploy_from_class_hash;
+
+        #[test]
+        fn deploy_from_undeclared_class_hash() {
+            deploy_from_class_hash(0x123.try_into().unwrap(), @array![]).unwrap();
+        }
+    "#
+    ));
+
+    let result = run_test_case(&test);
+
+    assert_failed(&result);
+    assert_case_output_contains(
+        &result,
+        "deploy_from_undeclared_class_hash",
+        "Class with hash = 0x123 is not declared",
+    );
+}
@@ -1,6 +1,6 @@
 use indoc::indoc;
 use std::path::Path;
-use test_utils::runner::{assert_passed, Contract};
+use test_utils::runner::{assert_case_output_contains, assert_failed, assert_passed, Contract};
 use test_utils::running_tests::run_test_case;
 use test_utils::test_case;
 
@@ -24,7 +24,7 @@ fn l1_handler_execute() {
             use serde::Serde;
             use array::{ArrayTrait, SpanTrait};
             use core::result::ResultTrait;
-            use snforge_std::{declare, ContractClassTrait, DeclareResultTrait, L1Handler, L1HandlerTrait};
+            use snforge_std::{declare, ContractClassTrait, DeclareResultTrait, L1Handler, L1HandlerTrait, L1Message, L1HandlerBlockContext, StorageWrite, L1HandlerExecutionTrait, L1HandlerContractTrait, deploy_for_l1_handler, start_cheat_block_timestamp};
             use starknet::contract_address_const;
 
             #[test]
@@ -54,6 +54,310 @@ fn l1_handler_execute() {
                 assert(dispatcher.get_token_id() == 8888_u256, 'Invalid token id');
             }
 
+            #[test]
+            fn l1_handler_execute_on_contract_deployed_for_l1_handler() {
+                let contract = deploy_for_l1_handler("l1_handler_executor", @array![0x123]);
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 42, token_id: 8888_u256 }.serialize(ref payload);
+
+                contract
+                    .execute_l1_handler(selector!("process_l1_message"), 0x123, payload.span())
+                    .unwrap();
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address: contract.contract_address };
+                assert(dispatcher.get_balance() == 42, dispatcher.get_balance());
+                assert(dispatcher.get_token_id() == 8888_u256, 'Invalid token id');
+            }
+
+            #[test]
+            fn l1_handler_execute_with_nonce() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("store_tx_nonce")
+                );
+
+                l1_handler.execute_with_nonce(0x123, array![].span(), 77).unwrap();
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 77, dispatcher.get_balance());
+            }
+
+            #[test]
+            fn l1_handler_execute_with_block_context() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("store_block_info")
+                );
+                let block_context = L1HandlerBlockContext {
+                    block_number: Option::Some(123), block_timestamp: Option::Some(1000)
+                };
+
+                start_cheat_block_timestamp(contract_address, 500);
+                l1_handler.clone().execute_with_block_context(0x123, array![].span(), block_context).unwrap();
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 1000, dispatcher.get_balance());
+                assert(dispatcher.get_token_id() == 123_u256, 'Invalid block number');
+
+                l1_handler.execute(0x123, array![].span()).unwrap();
+                assert(dispatcher.get_balance() == 500, 'Cheat should be restored');
+            }
+
+            #[test]
+            fn l1_handler_for_deployed_contract() {
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let deployed = contract.deploy(@array![0x123]).unwrap();
+                let (contract_address, _) = deployed;
+
+                let l1_handler = L1HandlerTrait::for_deployed(deployed, selector!("store_tx_nonce"));
+                l1_handler.execute_with_nonce(0x123, array![].span(), 77).unwrap();
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 77, dispatcher.get_balance());
+            }
+
+            #[test]
+            #[should_panic(expected: "has no L1 handler with selector")]
+            fn l1_handler_for_deployed_contract_without_handler() {
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let deployed = contract.deploy(@array![0x123]).unwrap();
+
+                L1HandlerTrait::for_deployed(deployed, selector!("get_balance"));
+            }
+
+            #[test]
+            fn l1_handler_execute_from_eth_address() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let l1_data = L1Data {
+                    balance: 42,
+                    token_id: 8888_u256,
+                };
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                l1_data.serialize(ref payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                let from_address: starknet::EthAddress = 0x123_felt252.try_into().unwrap();
+                l1_handler.execute_from_eth_address(from_address, payload.span()).unwrap();
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 42, dispatcher.get_balance());
+            }
+
+            #[test]
+            fn l1_handler_execute_batch() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut first_payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 1, token_id: 1_u256 }.serialize(ref first_payload);
+                let mut second_payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 2, token_id: 2_u256 }.serialize(ref second_payload);
+
+                let messages = array![
+                    L1Message { from_address: 0x123, payload: first_payload.span(), nonce: Option::None },
+                    L1Message { from_address: 0x123, payload: second_payload.span(), nonce: Option::None },
+                ];
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                let results = l1_handler.execute_batch(messages.span(), true);
+                assert(results.len() == 2, 'Wrong results count');
+                assert(results.at(0).is_ok(), 'First message failed');
+                assert(results.at(1).is_ok(), 'Second message failed');
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 2, dispatcher.get_balance());
+                assert(dispatcher.get_token_id() == 2_u256, 'Invalid token id');
+            }
+
+            #[test]
+            fn l1_handler_execute_isolated() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut first_payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 1, token_id: 1_u256 }.serialize(ref first_payload);
+                let mut second_payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 2, token_id: 2_u256 }.serialize(ref second_payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                let results = l1_handler
+                    .execute_isolated(0x123, array![first_payload.span(), second_payload.span()].span());
+                assert(results.len() == 2, 'Wrong results count');
+                assert(results.at(0).is_ok(), 'First message failed');
+                assert(results.at(1).is_ok(), 'Second message failed');
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 0, 'State should be discarded');
+
+                let results = l1_handler.execute_isolated(0x456, array![first_payload.span()].span());
+                assert(results.at(0).is_err(), 'Unauthorized caller passed');
+            }
+
+            #[test]
+            fn l1_handler_execute_tracking_storage() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 42, token_id: 0_u256 }.serialize(ref payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                let storage_writes = l1_handler
+                    .execute_tracking_storage(0x123, payload.span())
+                    .unwrap();
+
+                assert(storage_writes.len() == 1, 'Wrong storage writes count');
+                assert(
+                    *storage_writes.at(0) == StorageWrite {
+                        contract_address,
+                        key: selector!("balance"),
+                        old_value: 0,
+                        new_value: 42,
+                    },
+                    'Wrong storage write'
+                );
+
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+                assert(dispatcher.get_balance() == 42, 'State should be kept');
+            }
+
+            #[test]
+            fn l1_handler_execute_consumes_registered_message() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 42, token_id: 7_u256 }.serialize(ref payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                l1_handler.clone().register_message(0x123, payload.span());
+                l1_handler.clone().register_message(0x123, payload.span());
+                assert(l1_handler.clone().pending_messages(0x123, payload.span()) == 2, 'Not registered');
+
+                l1_handler.clone().execute(0x123, payload.span()).unwrap();
+                assert(l1_handler.clone().pending_messages(0x123, payload.span()) == 1, 'Not consumed');
+
+                l1_handler.clone().execute(0x123, payload.span()).unwrap();
+                assert(l1_handler.pending_messages(0x123, payload.span()) == 0, 'Not consumed');
+            }
+
+            #[test]
+            fn l1_handler_execute_variants_use_registered_messages() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 42, token_id: 7_u256 }.serialize(ref payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+
+                l1_handler.clone().register_message(0x123, payload.span());
+
+                let results = l1_handler.clone().execute_isolated(0x123, array![payload.span()].span());
+                assert(results.at(0).is_ok(), 'Isolated message failed');
+                assert(l1_handler.clone().pending_messages(0x123, payload.span()) == 1, 'Consumed by isolated');
+
+                l1_handler.clone().execute_tracking_storage(0x123, payload.span()).unwrap();
+                assert(l1_handler.pending_messages(0x123, payload.span()) == 0, 'Not consumed');
+            }
+
+            #[test]
+            fn l1_handler_execute_with_storage_rollback() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let mut payload: Array<felt252> = ArrayTrait::new();
+                L1Data { balance: 42, token_id: 7_u256 }.serialize(ref payload);
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("process_l1_message")
+                );
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+
+                let execution = l1_handler.execute_with_storage_rollback(0x123, payload.span());
+                assert(execution.result.is_ok(), 'Message failed');
+                assert(dispatcher.get_balance() == 42, 'State should be kept');
+
+                execution.rollback_storage();
+                assert(dispatcher.get_balance() == 0, 'Balance not rolled back');
+                assert(dispatcher.get_token_id() == 0_u256, 'Token id not rolled back');
+            }
+
+            #[test]
+            fn l1_handler_execute_batch_stop_on_failure() {
+                let calldata = array![0x123];
+
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@calldata).unwrap();
+
+                let message = L1Message { from_address: 0x123, payload: array![].span(), nonce: Option::None };
+                let messages = array![message, message];
+
+                let l1_handler = L1HandlerTrait::new(
+                    contract_address,
+                    selector!("panicking_l1_handler")
+                );
+
+                let results = l1_handler.clone().execute_batch(messages.span(), true);
+                assert(results.len() == 1, 'Should stop on failure');
+
+                let results = l1_handler.execute_batch(messages.span(), false);
+                assert(results.len() == 2, 'Should continue on failure');
+                assert(results.at(1).is_err(), 'Second message should fail');
+            }
+
             #[test]
             fn l1_handler_execute_panicking() {
                 let calldata = array![0x123];
@@ -123,3 +427,84 @@ fn l1_handler_execute() {
 
     assert_passed(&result);
 }
+
+#[test]
+fn l1_handler_execute_unregistered_message() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use snforge_std::{declare, ContractClassTrait, DeclareResultTrait, L1HandlerTrait};
+
+            #[test]
+            fn l1_handler_execute_unregistered_message() {
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![0x123]).unwrap();
+
+                let l1_handler = L1HandlerTrait::new(contract_address, selector!("store_tx_nonce"));
+
+                l1_handler.clone().register_message(0x123, array![].span());
+                l1_handler.execute(0x456, array![].span()).unwrap();
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "l1_handler_executor".to_string(),
+            Path::new("tests/data/contracts/l1_handler_execute_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_failed(&result);
+    assert_case_output_contains(
+        &result,
+        "l1_handler_execute_unregistered_message",
+        "No pending L1 -> L2 message",
+    );
+}
+
+#[test]
+fn l1_handler_execute_rejected() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use snforge_std::{declare, ContractClassTrait, DeclareResultTrait, L1HandlerTrait};
+
+            #[starknet::interface]
+            trait IBalanceToken<TContractState> {
+                fn get_balance(self: @TContractState) -> felt252;
+            }
+
+            #[test]
+            fn l1_handler_execute_rejected() {
+                let contract = declare("l1_handler_executor").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![0x123]).unwrap();
+                let dispatcher = IBalanceTokenDispatcher { contract_address };
+
+                let l1_handler = L1HandlerTrait::new(contract_address, selector!("store_tx_nonce"));
+                l1_handler.clone().register_message(0x123, array![].span());
+
+                match l1_handler.clone().execute_rejected(0x123, array![].span()) {
+                    Result::Ok(_) => panic_with_felt252('should have failed'),
+                    Result::Err(panic_data) => {
+                        assert(*panic_data.at(0) == 'L1 message rejected', 'Wrong panic data');
+                    },
+                }
+
+                assert(dispatcher.get_balance() == 0, 'Handler was executed');
+                assert(l1_handler.pending_messages(0x123, array![].span()) == 1, 'Message consumed');
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "l1_handler_executor".to_string(),
+            Path::new("tests/data/contracts/l1_handler_execute_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
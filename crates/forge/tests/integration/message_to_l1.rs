@@ -531,3 +531,136 @@ fn test_filtering() {
 
     assert_passed(&result);
 }
+
+#[test]
+fn assert_sent_exactly_passes() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use starknet::{ContractAddress, EthAddress};
+            use snforge_std::{
+                ContractClassTrait, DeclareResultTrait, declare, spy_messages_to_l1,
+                MessageToL1, MessageToL1SpyAssertionsTrait
+            };
+
+            #[starknet::interface]
+            trait IMessageToL1Checker<TContractState> {
+                fn send_message(ref self: TContractState, some_data: Array<felt252>, to_address: EthAddress);
+            }
+
+            fn deploy_message_to_l1_checker()  -> IMessageToL1CheckerDispatcher {
+               let declared = declare("MessageToL1Checker").unwrap().contract_class();
+               let (contract_address, _) = declared.deploy(@array![]).unwrap();
+
+               IMessageToL1CheckerDispatcher { contract_address }
+            }
+
+            fn expected_message(
+                sent_by: ContractAddress, payload: Array<felt252>
+            ) -> (ContractAddress, MessageToL1) {
+                (sent_by, MessageToL1 { to_address: 0x123.try_into().unwrap(), payload })
+            }
+
+            #[test]
+            fn assert_sent_exactly_passes() {
+               let message_to_l1_checker = deploy_message_to_l1_checker();
+
+               let mut spy = spy_messages_to_l1();
+               message_to_l1_checker.send_message(array![1], 0x123.try_into().unwrap());
+               message_to_l1_checker.send_message(array![2], 0x123.try_into().unwrap());
+
+               spy.assert_sent_exactly(
+                    @array![
+                        expected_message(message_to_l1_checker.contract_address, array![2]),
+                        expected_message(message_to_l1_checker.contract_address, array![1]),
+                    ]
+               );
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "MessageToL1Checker".to_string(),
+            Path::new("tests/data/contracts/message_to_l1_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn assert_sent_exactly_fails() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use starknet::{ContractAddress, EthAddress};
+            use snforge_std::{
+                ContractClassTrait, DeclareResultTrait, declare, spy_messages_to_l1,
+                MessageToL1, MessageToL1SpyAssertionsTrait
+            };
+
+            #[starknet::interface]
+            trait IMessageToL1Checker<TContractState> {
+                fn send_message(ref self: TContractState, some_data: Array<felt252>, to_address: EthAddress);
+            }
+
+            fn deploy_message_to_l1_checker()  -> IMessageToL1CheckerDispatcher {
+               let declared = declare("MessageToL1Checker").unwrap().contract_class();
+               let (contract_address, _) = declared.deploy(@array![]).unwrap();
+
+               IMessageToL1CheckerDispatcher { contract_address }
+            }
+
+            fn expected_message(
+                sent_by: ContractAddress, payload: Array<felt252>
+            ) -> (ContractAddress, MessageToL1) {
+                (sent_by, MessageToL1 { to_address: 0x123.try_into().unwrap(), payload })
+            }
+
+            #[test]
+            fn assert_sent_exactly_fails() {
+               let message_to_l1_checker = deploy_message_to_l1_checker();
+
+               let mut spy = spy_messages_to_l1();
+               message_to_l1_checker.send_message(array![1], 0x123.try_into().unwrap());
+               message_to_l1_checker.send_message(array![2], 0x123.try_into().unwrap());
+
+               spy.assert_sent_exactly(
+                    @array![
+                        expected_message(message_to_l1_checker.contract_address, array![1]),
+                        expected_message(message_to_l1_checker.contract_address, array![3]),
+                    ]
+               );
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "MessageToL1Checker".to_string(),
+            Path::new("tests/data/contracts/message_to_l1_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_failed(&result);
+    assert_case_output_contains(
+        &result,
+        "assert_sent_exactly_fails",
+        "Sent messages to L1 do not match the expected ones",
+    );
+    assert_case_output_contains(
+        &result,
+        "assert_sent_exactly_fails",
+        "to = 0x123, payload = [0x3]",
+    );
+    assert_case_output_contains(
+        &result,
+        "assert_sent_exactly_fails",
+        "to = 0x123, payload = [0x2]",
+    );
+}
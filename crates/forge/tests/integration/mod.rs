@@ -8,6 +8,7 @@ mod cheat_sequencer_address;
 mod declare;
 mod deploy;
 mod deploy_at;
+mod deploy_from_class_hash;
 mod dict;
 mod dispatchers;
 mod env;
@@ -24,6 +25,7 @@ mod resources;
 mod runtime;
 mod setup_fork;
 mod should_panic;
+mod side_effects;
 mod signing;
 mod spy_events;
 mod store_load;
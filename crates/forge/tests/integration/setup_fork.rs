@@ -69,6 +69,59 @@ fn fork_simple_decorator() {
     assert_passed(&result);
 }
 
+#[test]
+fn fork_block_number_cheatcode() {
+    let test = test_case!(formatdoc!(
+        r#"
+            use snforge_std::fork_block_number;
+
+            #[test]
+            #[fork(url: "{}", block_number: 54060)]
+            fn with_fork() {{
+                assert(fork_block_number() == Option::Some(54060), 'Wrong fork block number');
+            }}
+
+            #[test]
+            fn without_fork() {{
+                assert(fork_block_number().is_none(), 'Should not be forked');
+            }}
+        "#,
+        node_rpc_url()
+    )
+    .as_str());
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn prefetch_fork_state_cheatcode() {
+    let test = test_case!(formatdoc!(
+        r#"
+            use starknet::contract_address_const;
+            use snforge_std::{{load, prefetch_fork_state}};
+
+            #[test]
+            #[fork(url: "{}", block_number: 54060)]
+            fn prefetch_fork_state_cheatcode() {{
+                let contract_address = contract_address_const::<0x202de98471a4fae6bcbabb96cab00437d381abc58b02509043778074d6781e9>();
+
+                prefetch_fork_state(contract_address, array![selector!("balance")].span());
+
+                let balance = load(contract_address, selector!("balance"), 1);
+                assert(balance == array![0], 'Balance should be 0');
+            }}
+        "#,
+        node_rpc_url()
+    )
+    .as_str());
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
 #[test]
 fn fork_aliased_decorator() {
     let test = test_case!(indoc!(
@@ -132,6 +185,8 @@ fn fork_aliased_decorator() {
         .block_on(run_for_package(
             RunForPackageArgs {
                 test_targets: raw_test_targets,
+                fork_lockfile: None,
+                refresh_fork_lockfile: false,
                 package_name: "test_package".to_string(),
                 tests_filter: TestsFilter::from_flags(
                     None,
@@ -224,6 +279,8 @@ fn fork_aliased_decorator_overrding() {
         .block_on(run_for_package(
             RunForPackageArgs {
                 test_targets: raw_test_targets,
+                fork_lockfile: None,
+                refresh_fork_lockfile: false,
                 package_name: "test_package".to_string(),
                 tests_filter: TestsFilter::from_flags(
                     None,
@@ -0,0 +1,216 @@
+use indoc::indoc;
+use std::path::Path;
+use test_utils::runner::{assert_case_output_contains, assert_failed, assert_passed, Contract};
+use test_utils::running_tests::run_test_case;
+use test_utils::test_case;
+
+#[test]
+fn side_effects_in_emission_order() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use starknet::{ContractAddress, EthAddress};
+            use snforge_std::{
+                declare, ContractClassTrait, DeclareResultTrait, Event, MessageToL1, SideEffect,
+                SideEffectSpyTrait, spy_side_effects
+            };
+
+            #[starknet::interface]
+            trait ISideEffectsChecker<TContractState> {
+                fn emit_around_message(
+                    ref self: TContractState, some_data: felt252, payload: Array<felt252>, to_address: EthAddress
+                );
+            }
+
+            #[test]
+            fn side_effects_in_emission_order() {
+                let contract = declare("SideEffectsChecker").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![]).unwrap();
+                let dispatcher = ISideEffectsCheckerDispatcher { contract_address };
+
+                let mut spy = spy_side_effects();
+                dispatcher.emit_around_message(42, array![1, 2], 0x123.try_into().unwrap());
+
+                spy.assert_emitted_in_order(
+                    @array![
+                        SideEffect::Event(
+                            (contract_address, Event { keys: array![selector!("BeforeMessage")], data: array![42] })
+                        ),
+                        SideEffect::MessageToL1(
+                            (contract_address, MessageToL1 { to_address: 0x123.try_into().unwrap(), payload: array![1, 2] })
+                        ),
+                        SideEffect::Event(
+                            (contract_address, Event { keys: array![selector!("AfterMessage")], data: array![42] })
+                        ),
+                    ]
+                );
+                assert(spy.get_side_effects().len() == 3, 'Wrong number of side effects');
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "SideEffectsChecker".to_string(),
+            Path::new("tests/data/contracts/side_effects_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn side_effects_of_l1_handler() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use snforge_std::{
+                declare, ContractClassTrait, DeclareResultTrait, Event, L1HandlerTrait, MessageToL1,
+                SideEffect, SideEffectSpyTrait, spy_side_effects
+            };
+
+            #[test]
+            fn side_effects_of_l1_handler() {
+                let contract = declare("SideEffectsChecker").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![]).unwrap();
+
+                let mut spy = spy_side_effects();
+                L1HandlerTrait::new(contract_address, selector!("relay_to_l1"))
+                    .execute(0x123, array![42].span())
+                    .unwrap();
+
+                spy.assert_emitted_in_order(
+                    @array![
+                        SideEffect::MessageToL1(
+                            (contract_address, MessageToL1 { to_address: 0x123.try_into().unwrap(), payload: array![42] })
+                        ),
+                        SideEffect::Event(
+                            (contract_address, Event { keys: array![selector!("AfterMessage")], data: array![42] })
+                        ),
+                    ]
+                );
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "SideEffectsChecker".to_string(),
+            Path::new("tests/data/contracts/side_effects_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn side_effects_of_isolated_l1_handler_are_discarded() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use snforge_std::{
+                declare, ContractClassTrait, DeclareResultTrait, L1HandlerTrait, SideEffectSpyTrait,
+                spy_side_effects
+            };
+
+            #[test]
+            fn side_effects_of_isolated_l1_handler_are_discarded() {
+                let contract = declare("SideEffectsChecker").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![]).unwrap();
+
+                let mut spy = spy_side_effects();
+                let results = L1HandlerTrait::new(contract_address, selector!("relay_to_l1"))
+                    .execute_isolated(0x123, array![array![42].span(), array![43].span()].span());
+
+                assert(results.len() == 2, 'Wrong number of results');
+                assert(spy.get_side_effects().len() == 0, 'Side effects not discarded');
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "SideEffectsChecker".to_string(),
+            Path::new("tests/data/contracts/side_effects_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_passed(&result);
+}
+
+#[test]
+fn side_effects_in_wrong_order() {
+    let test = test_case!(
+        indoc!(
+            r#"
+            use array::ArrayTrait;
+            use starknet::{ContractAddress, EthAddress};
+            use snforge_std::{
+                declare, ContractClassTrait, DeclareResultTrait, Event, MessageToL1, SideEffect,
+                SideEffectSpyTrait, spy_side_effects
+            };
+
+            #[starknet::interface]
+            trait ISideEffectsChecker<TContractState> {
+                fn emit_around_message(
+                    ref self: TContractState, some_data: felt252, payload: Array<felt252>, to_address: EthAddress
+                );
+            }
+
+            #[test]
+            fn side_effects_in_wrong_order() {
+                let contract = declare("SideEffectsChecker").unwrap().contract_class();
+                let (contract_address, _) = contract.deploy(@array![]).unwrap();
+                let dispatcher = ISideEffectsCheckerDispatcher { contract_address };
+
+                let mut spy = spy_side_effects();
+                dispatcher.emit_around_message(42, array![1, 2], 0x123.try_into().unwrap());
+
+                spy.assert_emitted_in_order(
+                    @array![
+                        SideEffect::MessageToL1(
+                            (contract_address, MessageToL1 { to_address: 0x123.try_into().unwrap(), payload: array![1, 2] })
+                        ),
+                        SideEffect::Event(
+                            (contract_address, Event { keys: array![selector!("BeforeMessage")], data: array![42] })
+                        ),
+                        SideEffect::Event(
+                            (contract_address, Event { keys: array![selector!("AfterMessage")], data: array![42] })
+                        ),
+                    ]
+                );
+            }
+        "#
+        ),
+        Contract::from_code_path(
+            "SideEffectsChecker".to_string(),
+            Path::new("tests/data/contracts/side_effects_checker.cairo"),
+        )
+        .unwrap()
+    );
+
+    let result = run_test_case(&test);
+
+    assert_failed(&result);
+    assert_case_output_contains(
+        &result,
+        "side_effects_in_wrong_order",
+        "Emitted side effects do not match the expected sequence",
+    );
+    assert_case_output_contains(
+        &result,
+        "side_effects_in_wrong_order",
+        "expected at 0: message to L1",
+    );
+    assert_case_output_contains(
+        &result,
+        "side_effects_in_wrong_order",
+        "emitted at 1: message to L1",
+    );
+}
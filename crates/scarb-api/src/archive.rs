@@ -0,0 +1,151 @@
+use crate::{
+    parse_starknet_artifacts, sierra_checksum, ArtifactsError, StarknetContractArtifacts,
+    SUPPORTED_ARTIFACTS_VERSIONS,
+};
+use anyhow::{anyhow, Context, Result};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+/// Format of an archive bundling contract artifacts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+}
+
+/// Load contracts listed in the `starknet_artifacts.json` file stored under `manifest_path`
+/// in the archive read from `reader`.
+///
+/// Sierra paths of the manifest are resolved relative to the directory of the manifest within
+/// the archive. Sierra is read directly from the archive and compiled to casm, so no files are
+/// extracted to the disk.
+///
+/// Artifacts are returned together with the normalized path of their sierra file within
+/// the archive, in the same shape as the other loaders return them. The path does not exist
+/// on the disk, so the artifacts can't be merged with [`crate::MergePolicy::PreferNewest`].
+pub fn load_artifacts_from_archive(
+    reader: impl Read + Seek,
+    format: ArchiveFormat,
+    manifest_path: &Utf8Path,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let entries = read_archive_entries(reader, format)?;
+
+    let manifest_path = normalize_entry_path(manifest_path);
+    let manifest = archive_entry(&entries, &manifest_path)?;
+    let starknet_artifacts =
+        parse_starknet_artifacts(&manifest_path, manifest, SUPPORTED_ARTIFACTS_VERSIONS)?;
+    let base_path = manifest_path.parent().unwrap_or(Utf8Path::new(""));
+
+    starknet_artifacts
+        .contracts
+        .into_iter()
+        .map(|contract| {
+            let sierra_path = normalize_entry_path(&base_path.join(&contract.artifacts.sierra));
+            let sierra = archive_entry(&entries, &sierra_path)?;
+
+            if let Some(expected_checksum) = &contract.artifacts.sierra_checksum {
+                let checksum = sierra_checksum(sierra);
+                if !checksum.eq_ignore_ascii_case(expected_checksum) {
+                    return Err(ArtifactsError::ChecksumMismatch {
+                        sierra_path,
+                        checksum,
+                        expected_checksum: expected_checksum.clone(),
+                    }
+                    .into());
+                }
+            }
+
+            let artifacts = StarknetContractArtifacts::from_sierra(sierra).with_context(|| {
+                format!(
+                    "Failed to compile contract = {} from the archive",
+                    contract.contract_name
+                )
+            })?;
+
+            Ok((contract.contract_name, (artifacts, sierra_path)))
+        })
+        .collect()
+}
+
+/// Raw contents of all files in the archive by their normalized paths.
+/// Only the manifest and the sierra files it references are decoded, so the archive may bundle
+/// arbitrary binary files alongside the artifacts.
+fn read_archive_entries(
+    reader: impl Read + Seek,
+    format: ArchiveFormat,
+) -> Result<HashMap<Utf8PathBuf, Vec<u8>>> {
+    let mut entries = HashMap::new();
+
+    match format {
+        ArchiveFormat::Tar => {
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries().context("Failed to read tar archive")? {
+                let mut entry = entry.context("Failed to read tar archive entry")?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+
+                let path = Utf8PathBuf::try_from(entry.path()?.into_owned())?;
+                let mut contents = Vec::new();
+                entry
+                    .read_to_end(&mut contents)
+                    .with_context(|| format!("Failed to read {path} from tar archive"))?;
+
+                entries.insert(normalize_entry_path(&path), contents);
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(reader).context("Failed to read zip archive")?;
+            for index in 0..archive.len() {
+                let mut file = archive
+                    .by_index(index)
+                    .context("Failed to read zip archive entry")?;
+                if !file.is_file() {
+                    continue;
+                }
+                // Entries escaping the archive root can't be referenced by the manifest
+                let Some(path) = file.enclosed_name().map(|path| path.to_path_buf()) else {
+                    continue;
+                };
+
+                let path = Utf8PathBuf::try_from(path)?;
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)
+                    .with_context(|| format!("Failed to read {path} from zip archive"))?;
+
+                entries.insert(normalize_entry_path(&path), contents);
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn archive_entry<'a>(
+    entries: &'a HashMap<Utf8PathBuf, Vec<u8>>,
+    path: &Utf8Path,
+) -> Result<&'a str> {
+    let contents = entries
+        .get(path)
+        .ok_or_else(|| anyhow!("File = {path} not found in the archive"))?;
+
+    std::str::from_utf8(contents)
+        .with_context(|| format!("File = {path} in the archive is not valid UTF-8"))
+}
+
+/// Archives created with e.g. `tar -cf bundle.tar ./target` prefix entries with `./`,
+/// and manifests may refer to sierra files with `..`
+fn normalize_entry_path(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalized = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::Normal(name) => normalized.push(name),
+            Utf8Component::ParentDir => {
+                normalized.pop();
+            }
+            Utf8Component::CurDir | Utf8Component::RootDir | Utf8Component::Prefix(_) => {}
+        }
+    }
+    normalized
+}
@@ -0,0 +1,171 @@
+use crate::{
+    load_contracts_artifacts, minify_contracts_sierra, package_artifacts_data, CasmCache,
+    MergePolicy, StarknetContractArtifacts, SUPPORTED_ARTIFACTS_VERSIONS,
+};
+use anyhow::{bail, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use scarb_metadata::{Metadata, PackageId};
+use std::collections::HashMap;
+use std::time::Duration;
+use universal_sierra_compiler_api::CompilationOptions;
+
+/// Options of loading contract artifacts of a package with [`load_artifacts`].
+/// Options which are not set keep the behaviour of
+/// [`crate::get_contracts_artifacts_and_source_sierra_paths`].
+#[derive(Clone, Default)]
+pub struct ArtifactLoadOptions<'a> {
+    profile: Option<&'a str>,
+    use_test_target_contracts: bool,
+    require_test_targets: bool,
+    target_dir: Option<&'a Utf8Path>,
+    merge_policy: MergePolicy,
+    progress: Option<&'a dyn Fn(&str, usize, usize)>,
+    pub(crate) casm_cache: Option<&'a CasmCache>,
+    pub(crate) compilation_options: CompilationOptions,
+    pub(crate) minify_sierra: bool,
+    additional_artifacts_versions: Vec<u32>,
+}
+
+impl<'a> ArtifactLoadOptions<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Profile whose artifacts are loaded, defaults to the profile from metadata
+    pub fn profile(&mut self, profile: &'a str) -> &mut Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Load contracts from the artifacts of test targets instead of the `starknet-contract` target
+    pub fn use_test_target_contracts(&mut self, use_test_target_contracts: bool) -> &mut Self {
+        self.use_test_target_contracts = use_test_target_contracts;
+        self
+    }
+
+    /// Load contracts from the artifacts of test targets and fail if there are none,
+    /// instead of returning no contracts
+    pub fn require_test_targets(&mut self) -> &mut Self {
+        self.use_test_target_contracts = true;
+        self.require_test_targets = true;
+        self
+    }
+
+    /// Look the artifacts up in `target_dir` instead of the target directory derived from metadata
+    pub fn target_dir(&mut self, target_dir: &'a Utf8Path) -> &mut Self {
+        self.target_dir = Some(target_dir);
+        self
+    }
+
+    /// Resolve contracts present in more than one test target according to `merge_policy`
+    pub fn merge_policy(&mut self, merge_policy: MergePolicy) -> &mut Self {
+        self.merge_policy = merge_policy;
+        self
+    }
+
+    /// Call `progress` after each contract is loaded with its name, 1-based index
+    /// and the total number of contracts
+    pub fn progress(&mut self, progress: &'a dyn Fn(&str, usize, usize)) -> &mut Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Reuse casm stored in `casm_cache` for sierra that was compiled before
+    pub fn casm_cache(&mut self, casm_cache: &'a CasmCache) -> &mut Self {
+        self.casm_cache = Some(casm_cache);
+        self
+    }
+
+    /// Compile sierra to casm with `compilation_options` instead of the
+    /// `universal-sierra-compiler` defaults
+    pub fn compilation_options(&mut self, compilation_options: CompilationOptions) -> &mut Self {
+        self.compilation_options = compilation_options;
+        self
+    }
+
+    /// Keep sierra in memory without insignificant whitespace,
+    /// see [`crate::load_artifacts_from_manifest_with_sierra_minification`]
+    pub fn minify_sierra(&mut self, minify_sierra: bool) -> &mut Self {
+        self.minify_sierra = minify_sierra;
+        self
+    }
+
+    /// Also accept artifacts in `version` besides [`SUPPORTED_ARTIFACTS_VERSIONS`].
+    /// Loading them is best effort, fields unknown to this crate are ignored,
+    /// which allows using a newer Scarb before this crate supports its artifacts
+    pub fn accept_artifacts_version(&mut self, version: u32) -> &mut Self {
+        self.additional_artifacts_versions.push(version);
+        self
+    }
+
+    /// [`SUPPORTED_ARTIFACTS_VERSIONS`] followed by the versions accepted additionally
+    pub(crate) fn accepted_artifacts_versions(&self) -> Vec<u32> {
+        SUPPORTED_ARTIFACTS_VERSIONS
+            .iter()
+            .chain(&self.additional_artifacts_versions)
+            .copied()
+            .collect()
+    }
+}
+
+/// Get the map with `StarknetContractArtifacts` for the given package, loaded according to `options`
+pub fn load_artifacts(
+    metadata: &Metadata,
+    package: &PackageId,
+    options: &ArtifactLoadOptions,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    load_artifacts_timed(metadata, package, options, None)
+}
+
+/// Same as [`load_artifacts`], but also returns the time spent loading each contract, which is
+/// dominated by compiling its sierra to casm.
+/// Contracts loaded from more than one test target report the sum of their loading times.
+pub fn load_artifacts_with_timings(
+    metadata: &Metadata,
+    package: &PackageId,
+    options: &ArtifactLoadOptions,
+) -> Result<(
+    HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    HashMap<String, Duration>,
+)> {
+    let mut timings = HashMap::new();
+    let contracts = load_artifacts_timed(metadata, package, options, Some(&mut timings))?;
+
+    Ok((contracts, timings))
+}
+
+fn load_artifacts_timed(
+    metadata: &Metadata,
+    package: &PackageId,
+    options: &ArtifactLoadOptions,
+    timings: Option<&mut HashMap<String, Duration>>,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let artifacts_data = package_artifacts_data(
+        metadata,
+        package,
+        options.profile,
+        options.use_test_target_contracts,
+        options.target_dir,
+    )?;
+
+    if options.require_test_targets && artifacts_data.is_empty() {
+        bail!("No test targets artifacts found for package = {package}, did you run `scarb build --test`?");
+    }
+
+    let mut contracts = load_contracts_artifacts(
+        &artifacts_data,
+        &options.accepted_artifacts_versions(),
+        options.merge_policy,
+        options.progress.unwrap_or(&|_, _, _| {}),
+        options.casm_cache,
+        &options.compilation_options,
+        timings,
+    )?;
+
+    if options.minify_sierra {
+        minify_contracts_sierra(&mut contracts)?;
+    }
+
+    Ok(contracts)
+}
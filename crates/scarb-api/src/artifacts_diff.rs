@@ -0,0 +1,102 @@
+use crate::StarknetContractArtifacts;
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use std::collections::HashMap;
+
+/// Difference between two sets of contract artifacts, keyed by contract name
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct ArtifactsDiff {
+    /// Contracts present only in the new set
+    pub added: Vec<String>,
+    /// Contracts present only in the old set
+    pub removed: Vec<String>,
+    /// Contracts present in both sets with differing sierra or casm
+    pub changed: Vec<String>,
+}
+
+impl ArtifactsDiff {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two maps returned by [`crate::get_contracts_artifacts_and_source_sierra_paths`].
+/// Contract names in every field of the result are sorted alphabetically.
+pub fn diff_artifacts(
+    old: &HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    new: &HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+) -> Result<ArtifactsDiff> {
+    let mut diff = ArtifactsDiff::default();
+
+    for (name, (new_artifacts, _)) in new {
+        match old.get(name) {
+            None => diff.added.push(name.clone()),
+            Some((old_artifacts, _)) => {
+                if old_artifacts.sierra()? != new_artifacts.sierra()?
+                    || old_artifacts.casm != new_artifacts.casm
+                {
+                    diff.changed.push(name.clone());
+                }
+            }
+        }
+    }
+
+    diff.removed = old
+        .keys()
+        .filter(|name| !new.contains_key(*name))
+        .cloned()
+        .collect();
+
+    diff.added.sort();
+    diff.removed.sort();
+    diff.changed.sort();
+
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sierra: &str, casm: &str) -> (StarknetContractArtifacts, Utf8PathBuf) {
+        (
+            StarknetContractArtifacts::new(sierra.to_string(), casm.to_string()),
+            Utf8PathBuf::from("path"),
+        )
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed() {
+        let old = HashMap::from([
+            ("Same".to_string(), entry("s", "c")),
+            ("Removed".to_string(), entry("s", "c")),
+            ("ChangedSierra".to_string(), entry("s", "c")),
+            ("ChangedCasm".to_string(), entry("s", "c")),
+        ]);
+        let new = HashMap::from([
+            ("Same".to_string(), entry("s", "c")),
+            ("Added".to_string(), entry("s", "c")),
+            ("ChangedSierra".to_string(), entry("s2", "c")),
+            ("ChangedCasm".to_string(), entry("s", "c2")),
+        ]);
+
+        let diff = diff_artifacts(&old, &new).unwrap();
+
+        assert_eq!(
+            diff,
+            ArtifactsDiff {
+                added: vec!["Added".to_string()],
+                removed: vec!["Removed".to_string()],
+                changed: vec!["ChangedCasm".to_string(), "ChangedSierra".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn diff_of_identical_maps_is_empty() {
+        let map = HashMap::from([("Same".to_string(), entry("s", "c"))]);
+
+        assert!(diff_artifacts(&map, &map).unwrap().is_empty());
+    }
+}
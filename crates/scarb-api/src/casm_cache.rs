@@ -0,0 +1,171 @@
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::fs;
+use universal_sierra_compiler_api::{universal_sierra_compiler_version, CompilationOptions};
+
+/// Name of the directory in the target directory where compiled casm is cached
+pub const CASM_CACHE_DIR: &str = "snforge-casm-cache";
+
+/// On-disk cache of casm compiled from sierra.
+/// Entries are keyed by the hash of sierra and compilation options, so changing any of them results
+/// in recompilation. Each entry records the `universal-sierra-compiler` version which compiled it,
+/// and is recompiled when a different version is installed, even if sierra is unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CasmCache {
+    dir: Utf8PathBuf,
+}
+
+impl CasmCache {
+    #[must_use]
+    pub fn new(dir: Utf8PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Cache stored in [`CASM_CACHE_DIR`] of the given target directory
+    #[must_use]
+    pub fn in_target_dir(target_dir: &Utf8Path) -> Self {
+        Self::new(target_dir.join(CASM_CACHE_DIR))
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Utf8Path {
+        &self.dir
+    }
+
+    /// Remove all cached entries
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .with_context(|| format!("Failed to remove casm cache at {}", self.dir))?;
+        }
+        Ok(())
+    }
+
+    /// Get casm cached for `sierra`, or run `compile` and store its result in the cache
+    pub fn get_or_compile(
+        &self,
+        sierra: &str,
+        compilation_options: &CompilationOptions,
+        compile: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        let usc_version = universal_sierra_compiler_version()?;
+        self.get_or_compile_with_usc_version(sierra, &usc_version, compilation_options, compile)
+    }
+
+    fn get_or_compile_with_usc_version(
+        &self,
+        sierra: &str,
+        usc_version: &str,
+        compilation_options: &CompilationOptions,
+        compile: impl FnOnce() -> Result<String>,
+    ) -> Result<String> {
+        let path = self
+            .dir
+            .join(format!("{}.json", cache_key(sierra, compilation_options)));
+
+        // Entries which can't be read are treated as missing and overwritten
+        if let Some(entry) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str::<CacheEntry>(&contents).ok())
+        {
+            if entry.usc_version == usc_version {
+                return Ok(entry.casm);
+            }
+        }
+
+        let entry = CacheEntry {
+            usc_version: usc_version.to_string(),
+            casm: compile()?,
+        };
+
+        // Failing to store the entry only makes the next run slower, so the error is ignored.
+        // The entry is written to a temporary file first, so other processes never read a partial one
+        let temp_path = path.with_extension(format!("json.{}.tmp", std::process::id()));
+        let _ = serde_json::to_string(&entry)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| {
+                fs::create_dir_all(&self.dir)?;
+                fs::write(&temp_path, contents)
+            })
+            .and_then(|()| fs::rename(&temp_path, &path));
+
+        Ok(entry.casm)
+    }
+}
+
+/// Casm stored in the cache with the version of `universal-sierra-compiler` which compiled it
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    usc_version: String,
+    casm: String,
+}
+
+fn cache_key(sierra: &str, compilation_options: &CompilationOptions) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(sierra.as_bytes());
+    for arg in compilation_options.usc_args() {
+        hasher.update([0]);
+        hasher.update(arg.as_bytes());
+    }
+
+    base16ct::lower::encode_string(&hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::TempDir;
+    use std::cell::Cell;
+
+    #[test]
+    fn cache_key_depends_on_all_inputs() {
+        let options = CompilationOptions::default();
+        let other_options = CompilationOptions {
+            add_pythonic_hints: true,
+            ..CompilationOptions::default()
+        };
+        let key = cache_key("sierra", &options);
+
+        assert_eq!(key, cache_key("sierra", &options));
+        assert_ne!(key, cache_key("other sierra", &options));
+        assert_ne!(key, cache_key("sierra", &other_options));
+    }
+
+    #[test]
+    fn recompiles_only_when_usc_version_changes() {
+        let temp = TempDir::new().unwrap();
+        let cache = CasmCache::new(Utf8PathBuf::from_path_buf(temp.to_path_buf()).unwrap());
+        let options = CompilationOptions::default();
+        let compilations = Cell::new(0);
+        let compile = |casm: &str| {
+            compilations.set(compilations.get() + 1);
+            Ok(casm.to_string())
+        };
+
+        let casm = cache
+            .get_or_compile_with_usc_version("sierra", "1.0.0", &options, || compile("casm 1"))
+            .unwrap();
+        assert_eq!(casm, "casm 1");
+        assert_eq!(compilations.get(), 1);
+
+        let casm = cache
+            .get_or_compile_with_usc_version("sierra", "1.0.0", &options, || compile("casm 2"))
+            .unwrap();
+        assert_eq!(casm, "casm 1");
+        assert_eq!(compilations.get(), 1);
+
+        let casm = cache
+            .get_or_compile_with_usc_version("sierra", "2.0.0", &options, || compile("casm 3"))
+            .unwrap();
+        assert_eq!(casm, "casm 3");
+        assert_eq!(compilations.get(), 2);
+
+        let casm = cache
+            .get_or_compile_with_usc_version("sierra", "2.0.0", &options, || compile("casm 4"))
+            .unwrap();
+        assert_eq!(casm, "casm 3");
+        assert_eq!(compilations.get(), 2);
+    }
+}
@@ -0,0 +1,235 @@
+use crate::StarknetContractArtifacts;
+use anyhow::{Context, Result};
+use starknet::core::types::contract::AbiEntry;
+use starknet::core::types::Felt;
+use starknet::core::utils::get_selector_from_name;
+use std::collections::HashMap;
+
+/// Kind of a contract entry point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryPointKind {
+    External,
+    Constructor,
+    L1Handler,
+}
+
+/// Entry point of a contract identified by its selector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub name: String,
+    pub kind: EntryPointKind,
+}
+
+/// Parameter of a contract entry point as declared in the ABI
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiParam {
+    pub name: String,
+    /// Fully qualified Cairo type of the parameter, e.g. `core::felt252`
+    pub ty: String,
+}
+
+/// Map selectors of all entry points of the contract to their names and kinds.
+/// External functions declared in interfaces are included together with the ones declared
+/// directly in the contract.
+pub fn entry_point_selectors(
+    artifacts: &StarknetContractArtifacts,
+) -> Result<HashMap<Felt, EntryPoint>> {
+    let abi: Vec<AbiEntry> =
+        serde_json::from_str(artifacts.abi()?).context("Failed to parse contract ABI")?;
+
+    let mut selectors = HashMap::new();
+    for abi_entry in abi {
+        match abi_entry {
+            AbiEntry::Interface(abi_interface) => {
+                for abi_entry in abi_interface.items {
+                    add_entry_point(abi_entry, &mut selectors)?;
+                }
+            }
+            _ => add_entry_point(abi_entry, &mut selectors)?,
+        }
+    }
+    Ok(selectors)
+}
+
+/// Parameters of the contract constructor in the order of declaration.
+/// Returns `None` if the contract does not define a constructor.
+pub fn constructor_signature(
+    artifacts: &StarknetContractArtifacts,
+) -> Result<Option<Vec<AbiParam>>> {
+    let constructor = artifacts
+        .parse_sierra()?
+        .abi
+        .iter()
+        .find_map(|abi_entry| match abi_entry {
+            AbiEntry::Constructor(abi_constructor) => Some(abi_constructor),
+            _ => None,
+        });
+
+    Ok(constructor.map(|abi_constructor| {
+        abi_constructor
+            .inputs
+            .iter()
+            .map(|input| AbiParam {
+                name: input.name.clone(),
+                ty: input.r#type.clone(),
+            })
+            .collect()
+    }))
+}
+
+fn add_entry_point(abi_entry: AbiEntry, selectors: &mut HashMap<Felt, EntryPoint>) -> Result<()> {
+    let (name, kind) = match abi_entry {
+        AbiEntry::Function(abi_function) => (abi_function.name, EntryPointKind::External),
+        AbiEntry::L1Handler(abi_function) => (abi_function.name, EntryPointKind::L1Handler),
+        AbiEntry::Constructor(abi_constructor) => {
+            (abi_constructor.name, EntryPointKind::Constructor)
+        }
+        _ => return Ok(()),
+    };
+    let selector = get_selector_from_name(&name)
+        .with_context(|| format!("Failed to compute selector of entry point = {name}"))?;
+    selectors.insert(selector, EntryPoint { name, kind });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn selectors_of_all_entry_point_kinds() {
+        let sierra = indoc!(
+            r#"
+            {
+                "abi": [
+                    { "type": "impl", "name": "HelloImpl", "interface_name": "package::IHello" },
+                    {
+                        "type": "interface",
+                        "name": "package::IHello",
+                        "items": [
+                            {
+                                "type": "function",
+                                "name": "increase_balance",
+                                "inputs": [{ "name": "amount", "type": "core::felt252" }],
+                                "outputs": [],
+                                "state_mutability": "external"
+                            }
+                        ]
+                    },
+                    {
+                        "type": "function",
+                        "name": "get_balance",
+                        "inputs": [],
+                        "outputs": [{ "type": "core::felt252" }],
+                        "state_mutability": "view"
+                    },
+                    {
+                        "type": "constructor",
+                        "name": "constructor",
+                        "inputs": []
+                    },
+                    {
+                        "type": "l1_handler",
+                        "name": "process_l1_message",
+                        "inputs": [{ "name": "from_address", "type": "core::felt252" }],
+                        "outputs": [],
+                        "state_mutability": "external"
+                    }
+                ]
+            }
+            "#
+        );
+        let artifacts = StarknetContractArtifacts::new(sierra, "");
+
+        let selectors = entry_point_selectors(&artifacts).unwrap();
+
+        let expected = [
+            ("increase_balance", EntryPointKind::External),
+            ("get_balance", EntryPointKind::External),
+            ("constructor", EntryPointKind::Constructor),
+            ("process_l1_message", EntryPointKind::L1Handler),
+        ];
+        assert_eq!(selectors.len(), expected.len());
+        for (name, kind) in expected {
+            assert_eq!(
+                selectors[&get_selector_from_name(name).unwrap()],
+                EntryPoint {
+                    name: name.to_string(),
+                    kind,
+                }
+            );
+        }
+    }
+
+    fn sierra_class_with_abi(abi: &str) -> String {
+        format!(
+            r#"{{
+                "sierra_program": [],
+                "sierra_program_debug_info": {{
+                    "type_names": [],
+                    "libfunc_names": [],
+                    "user_func_names": []
+                }},
+                "contract_class_version": "0.1.0",
+                "entry_points_by_type": {{ "EXTERNAL": [], "L1_HANDLER": [], "CONSTRUCTOR": [] }},
+                "abi": {abi}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn constructor_signature_lists_parameters() {
+        let sierra = sierra_class_with_abi(indoc!(
+            r#"
+            [
+                {
+                    "type": "constructor",
+                    "name": "constructor",
+                    "inputs": [
+                        { "name": "owner", "type": "core::starknet::contract_address::ContractAddress" },
+                        { "name": "initial_supply", "type": "core::integer::u256" }
+                    ]
+                }
+            ]
+            "#
+        ));
+        let artifacts = StarknetContractArtifacts::new(sierra, "");
+
+        let signature = constructor_signature(&artifacts).unwrap();
+
+        assert_eq!(
+            signature,
+            Some(vec![
+                AbiParam {
+                    name: "owner".to_string(),
+                    ty: "core::starknet::contract_address::ContractAddress".to_string(),
+                },
+                AbiParam {
+                    name: "initial_supply".to_string(),
+                    ty: "core::integer::u256".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn constructor_signature_without_constructor() {
+        let sierra = sierra_class_with_abi(indoc!(
+            r#"
+            [
+                {
+                    "type": "function",
+                    "name": "get_balance",
+                    "inputs": [],
+                    "outputs": [{ "type": "core::felt252" }],
+                    "state_mutability": "view"
+                }
+            ]
+            "#
+        ));
+        let artifacts = StarknetContractArtifacts::new(sierra, "");
+
+        assert_eq!(constructor_signature(&artifacts).unwrap(), None);
+    }
+}
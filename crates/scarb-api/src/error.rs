@@ -0,0 +1,78 @@
+use camino::Utf8PathBuf;
+use starknet::core::types::Felt;
+use std::io;
+use thiserror::Error;
+
+/// Error thrown while loading contract artifacts.
+/// Loading functions return it wrapped in [`anyhow::Error`], it can be recovered with
+/// [`anyhow::Error::downcast_ref`] and leads to the underlying error through `source()`.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ArtifactsError {
+    /// Failed to read a file with artifacts.
+    #[error("Failed to read {path:?} contents")]
+    Read {
+        path: Utf8PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// `starknet_artifacts.json` file has unexpected contents.
+    #[error("Failed to parse {path:?} contents. Make sure you have enabled sierra code generation in Scarb.toml")]
+    Parse {
+        path: Utf8PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// `starknet_artifacts.json` file uses a schema version that is not accepted.
+    /// Other versions can be accepted with [`crate::ArtifactLoadOptions::accept_artifacts_version`].
+    #[error("Unsupported version = {version} of {path:?}, supported versions = [{}]. \
+        Make sure your Scarb version is supported", .supported_versions.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    UnsupportedVersion {
+        path: Utf8PathBuf,
+        version: u32,
+        supported_versions: Vec<u32>,
+    },
+    /// `starknet_artifacts.json` file lists more than one contract with the same name.
+    #[error("Contract name = {contract_name} is listed more than once in {path}, ids = [{}]", .ids.join(", "))]
+    DuplicatedContractName {
+        path: Utf8PathBuf,
+        contract_name: String,
+        ids: Vec<String>,
+    },
+    /// Sierra files listed in `starknet_artifacts.json` file do not exist or cannot be read.
+    #[error("Sierra files listed in {path} cannot be read: [{}]", .sierra_paths.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    MissingSierraFiles {
+        path: Utf8PathBuf,
+        sierra_paths: Vec<Utf8PathBuf>,
+    },
+    /// Sierra file differs from the one `starknet_artifacts.json` file was generated for.
+    #[error("Checksum of {sierra_path} = {checksum} does not match the one from starknet_artifacts.json = {expected_checksum}. \
+        Artifacts may be stale, try rebuilding the project")]
+    ChecksumMismatch {
+        sierra_path: Utf8PathBuf,
+        checksum: String,
+        expected_checksum: String,
+    },
+    /// Class hash computed from sierra differs from the expected one.
+    #[error("Class hash of sierra = {class_hash:#x} does not match the expected class hash = {expected_class_hash:#x}")]
+    ClassHashMismatch {
+        class_hash: Felt,
+        expected_class_hash: Felt,
+    },
+    /// `universal-sierra-compiler` binary is not installed.
+    #[error("Failed to compile sierra to casm. Cannot find `universal-sierra-compiler` binary. \
+        Make sure you have USC installed https://github.com/software-mansion/universal-sierra-compiler \
+        and added to PATH (or set at UNIVERSAL_SIERRA_COMPILER env var)")]
+    CompilerNotFound,
+    /// `universal-sierra-compiler` ran, but failed to compile sierra of the contract.
+    #[error("Failed to compile contract = {contract} to casm:\n{stderr}")]
+    CompilationFailed { contract: String, stderr: String },
+    /// Compiling sierra to casm failed for another reason, e.g. `universal-sierra-compiler`
+    /// could not be started.
+    #[error("Failed to compile {sierra_path} to casm")]
+    Compilation {
+        sierra_path: Utf8PathBuf,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
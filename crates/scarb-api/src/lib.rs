@@ -1,15 +1,43 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
-use scarb_metadata::{CompilationUnitMetadata, Metadata, PackageId};
-use semver::VersionReq;
-use serde::Deserialize;
+use scarb_metadata::{CompilationUnitMetadata, Metadata, PackageId, TargetMetadata};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use starknet::core::types::contract::SierraClass;
+use starknet::core::types::Felt;
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs;
-use universal_sierra_compiler_api::{compile_sierra_at_path, SierraType};
-
+use std::future::Future;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use universal_sierra_compiler_api::{
+    compile_sierra_at_path_with_options, compile_sierra_from_str, CompilationOptions,
+    CompilerError, SierraType,
+};
+
+pub use archive::{load_artifacts_from_archive, ArchiveFormat};
+pub use artifact_load_options::{load_artifacts, load_artifacts_with_timings, ArtifactLoadOptions};
+pub use artifacts_diff::{diff_artifacts, ArtifactsDiff};
+pub use casm_cache::{CasmCache, CASM_CACHE_DIR};
 pub use command::*;
-
+pub use entry_points::{
+    constructor_signature, entry_point_selectors, AbiParam, EntryPoint, EntryPointKind,
+};
+pub use error::ArtifactsError;
+
+mod archive;
+mod artifact_load_options;
+mod artifacts_diff;
+mod casm_cache;
 mod command;
+mod entry_points;
+mod error;
 pub mod metadata;
 pub mod version;
 
@@ -32,197 +60,1678 @@ struct StarknetContract {
 #[derive(Deserialize, Debug, PartialEq, Clone)]
 struct StarknetContractArtifactPaths {
     sierra: Utf8PathBuf,
+    /// Hex encoded SHA3-256 of the sierra file, verified on load if present
+    #[serde(default)]
+    sierra_checksum: Option<String>,
 }
 
-/// Contains compiled Starknet artifacts
-#[derive(Debug, PartialEq, Clone)]
+/// Contains compiled Starknet artifacts.
+/// Contents are reference counted, so cloning the artifacts does not copy the code.
+///
+/// Sierra of artifacts loaded from the build output is snapshotted from its file on the first
+/// access only, see [`Self::sierra`]. Casm is compiled when the artifacts are loaded, so if the
+/// file is rebuilt in between, sierra may come from a newer build than casm.
+#[derive(Debug, Clone)]
 pub struct StarknetContractArtifacts {
-    /// Compiled sierra code
-    pub sierra: String,
-    /// Compiled casm code
-    pub casm: String,
+    sierra: OnceLock<Arc<str>>,
+    sierra_path: Option<Utf8PathBuf>,
+    abi: OnceLock<Arc<str>>,
+    sierra_class: OnceLock<Arc<SierraClass>>,
+    test_type: Option<TestType>,
+    build_origin: Option<BuildOrigin>,
+    /// Compiled casm code, empty if the artifacts were loaded without casm
+    pub casm: Arc<str>,
+}
+
+/// Compares casm and the source of sierra without reading any files: paths of the sierra files,
+/// and sierra contents when both artifacts already hold them
+impl PartialEq for StarknetContractArtifacts {
+    fn eq(&self, other: &Self) -> bool {
+        self.casm == other.casm
+            && self.sierra_path == other.sierra_path
+            && match (self.sierra.get(), other.sierra.get()) {
+                (Some(sierra), Some(other_sierra)) => sierra == other_sierra,
+                _ => true,
+            }
+    }
 }
 
 impl StarknetContractArtifacts {
+    /// Create artifacts from sierra and casm that are already held in memory
+    #[must_use]
+    pub fn new(sierra: impl Into<Arc<str>>, casm: impl Into<Arc<str>>) -> Self {
+        Self {
+            sierra: OnceLock::from(sierra.into()),
+            sierra_path: None,
+            abi: OnceLock::new(),
+            sierra_class: OnceLock::new(),
+            test_type: None,
+            build_origin: None,
+            casm: casm.into(),
+        }
+    }
+
+    /// Create artifacts from sierra held in memory, compiling it to casm
+    pub fn from_sierra(sierra: impl Into<Arc<str>>) -> Result<Self> {
+        let sierra = sierra.into();
+        let casm = compile_sierra_from_str(&sierra, &SierraType::Contract)?;
+
+        Ok(Self::new(sierra, casm))
+    }
+
+    /// Test type of the `scarb build --test` target the artifacts come from,
+    /// `None` for artifacts of the standard build
+    #[must_use]
+    pub fn test_type(&self) -> Option<TestType> {
+        self.test_type
+    }
+
+    /// Kind of Scarb build the artifacts come from, `None` for artifacts which were not loaded
+    /// from the build output of a package, e.g. created from sierra held in memory
+    #[must_use]
+    pub fn build_origin(&self) -> Option<BuildOrigin> {
+        self.build_origin
+    }
+
+    /// Whether sierra was compiled to casm, `false` for artifacts loaded
+    /// with [`load_artifacts_from_manifest_without_casm`]
+    #[must_use]
+    pub fn has_casm(&self) -> bool {
+        !self.casm.is_empty()
+    }
+
+    /// Compiled sierra code. It is read from the source sierra file on the first access
+    /// and the same contents are returned on the next ones, even if the file changed since.
+    pub fn sierra(&self) -> Result<&str> {
+        if let Some(sierra) = self.sierra.get() {
+            return Ok(sierra.as_ref());
+        }
+
+        let sierra_path = self
+            .sierra_path
+            .as_ref()
+            .context("Sierra is neither loaded nor has a source file")?;
+        let sierra = fs::read_to_string(sierra_path)
+            .with_context(|| format!("Failed to read sierra from {sierra_path}"))?;
+
+        Ok(self.sierra.get_or_init(|| sierra.into()).as_ref())
+    }
+
+    /// ABI of the contract serialized as JSON. It is extracted from sierra on the first access.
+    pub fn abi(&self) -> Result<&str> {
+        if let Some(abi) = self.abi.get() {
+            return Ok(abi.as_ref());
+        }
+
+        let abi = abi_from_sierra(self.sierra()?)?;
+
+        Ok(self.abi.get_or_init(|| abi.into()).as_ref())
+    }
+
+    /// Sierra parsed into a contract class. It is parsed on the first access.
+    pub fn parse_sierra(&self) -> Result<&SierraClass> {
+        if let Some(sierra_class) = self.sierra_class.get() {
+            return Ok(sierra_class.as_ref());
+        }
+
+        let sierra_class: SierraClass = serde_json::from_str(self.sierra()?)
+            .context("Failed to parse sierra contract class")?;
+
+        Ok(self
+            .sierra_class
+            .get_or_init(|| Arc::new(sierra_class))
+            .as_ref())
+    }
+
     fn from_scarb_contract_artifact(
         starknet_contract: &StarknetContract,
         base_path: &Utf8Path,
+        compilation_options: &CompilationOptions,
+        casm_cache: Option<&CasmCache>,
     ) -> Result<Self> {
         let sierra_path = base_path.join(starknet_contract.artifacts.sierra.clone());
-        let sierra = fs::read_to_string(sierra_path)?;
+        let sierra = OnceLock::new();
+
+        if let Some(expected_checksum) = &starknet_contract.artifacts.sierra_checksum {
+            let sierra_contents = read_artifacts_file(&sierra_path)?;
+            let checksum = sierra_checksum(&sierra_contents);
+            if !checksum.eq_ignore_ascii_case(expected_checksum) {
+                return Err(ArtifactsError::ChecksumMismatch {
+                    sierra_path,
+                    checksum,
+                    expected_checksum: expected_checksum.clone(),
+                }
+                .into());
+            }
+            sierra.get_or_init(|| sierra_contents.into());
+        }
 
-        let casm = compile_sierra_at_path(
-            starknet_contract.artifacts.sierra.as_str(),
-            Some(base_path.as_std_path()),
-            &SierraType::Contract,
-        )?;
+        let compile = || -> Result<String> {
+            compile_sierra_at_path_with_options(
+                starknet_contract.artifacts.sierra.as_str(),
+                Some(base_path.as_std_path()),
+                &SierraType::Contract,
+                compilation_options,
+            )
+            .map_err(|err| match err.downcast_ref::<CompilerError>() {
+                Some(CompilerError::NotFound) => ArtifactsError::CompilerNotFound.into(),
+                Some(CompilerError::CompilationFailed { stderr }) => {
+                    ArtifactsError::CompilationFailed {
+                        contract: starknet_contract.contract_name.clone(),
+                        stderr: stderr.clone(),
+                    }
+                    .into()
+                }
+                None => ArtifactsError::Compilation {
+                    sierra_path: sierra_path.clone(),
+                    source: err.into(),
+                }
+                .into(),
+            })
+        };
+        // Casm emitted by Scarb is compiled with the default configuration only
+        let sibling_casm = if *compilation_options == CompilationOptions::default() {
+            fresh_sibling_casm(&sierra_path)
+        } else {
+            None
+        };
+        let casm = match (sibling_casm, casm_cache) {
+            (Some(casm), _) => casm,
+            (None, Some(casm_cache)) => {
+                let sierra_contents = match sierra.get() {
+                    Some(sierra_contents) => Arc::clone(sierra_contents),
+                    None => {
+                        let sierra_contents: Arc<str> = read_artifacts_file(&sierra_path)?.into();
+                        Arc::clone(sierra.get_or_init(|| sierra_contents))
+                    }
+                };
+                casm_cache.get_or_compile(&sierra_contents, compilation_options, compile)?
+            }
+            (None, None) => compile()?,
+        };
+
+        Ok(Self {
+            sierra,
+            sierra_path: Some(sierra_path),
+            abi: OnceLock::new(),
+            sierra_class: OnceLock::new(),
+            test_type: None,
+            build_origin: None,
+            casm: casm.into(),
+        })
+    }
+
+    /// Artifacts with sierra read lazily from its source file and without casm
+    fn from_scarb_contract_artifact_without_casm(
+        starknet_contract: &StarknetContract,
+        base_path: &Utf8Path,
+    ) -> Self {
+        Self {
+            sierra: OnceLock::new(),
+            sierra_path: Some(base_path.join(starknet_contract.artifacts.sierra.clone())),
+            abi: OnceLock::new(),
+            sierra_class: OnceLock::new(),
+            test_type: None,
+            build_origin: None,
+            casm: "".into(),
+        }
+    }
+
+    /// Replace sierra with the same contract class serialized without insignificant whitespace
+    fn minify_sierra(&mut self) -> Result<()> {
+        let sierra: serde_json::Value = serde_json::from_str(self.sierra()?)
+            .context("Failed to parse sierra contract class")?;
+        self.sierra = OnceLock::from(Arc::from(serde_json::to_string(&sierra)?));
+
+        Ok(())
+    }
+
+    /// Load artifacts of `contract_name` listed in the `starknet_artifacts.json` file at
+    /// `contracts_path`, according to the casm cache, compilation options, sierra minification
+    /// and accepted artifacts versions of `options`.
+    /// Same as loading it with [`load_artifacts_from_manifest_with_options`], but reads the
+    /// manifest and compiles sierra to casm on the blocking thread pool, so it does not stall
+    /// the async executor. Must be awaited within a Tokio runtime.
+    pub fn from_scarb_contract_artifact_async(
+        contracts_path: &Utf8Path,
+        contract_name: &str,
+        options: &ArtifactLoadOptions,
+    ) -> impl Future<Output = Result<Self>> + Send + 'static {
+        // Options are copied before the loading starts, so the future does not borrow them
+        let contracts_path = contracts_path.to_path_buf();
+        let contract_name = contract_name.to_string();
+        let accepted_versions = options.accepted_artifacts_versions();
+        let compilation_options = options.compilation_options.clone();
+        let casm_cache = options.casm_cache.cloned();
+        let minify_sierra = options.minify_sierra;
+
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let base_path = contracts_path.parent().ok_or_else(|| {
+                    anyhow!("Failed to get parent for path = {}", &contracts_path)
+                })?;
+                let artifacts =
+                    artifacts_for_package_accepting(&contracts_path, &accepted_versions)?;
+                let contract = artifacts
+                    .contracts
+                    .iter()
+                    .find(|contract| contract.contract_name == contract_name)
+                    .ok_or_else(|| {
+                        anyhow!("Contract = {contract_name} not found in {contracts_path}")
+                    })?;
+
+                let (mut contract_artifacts, _) = load_contract_artifacts_and_source_sierra_path(
+                    contract,
+                    base_path,
+                    &compilation_options,
+                    casm_cache.as_ref(),
+                )?;
+                if minify_sierra {
+                    contract_artifacts.minify_sierra()?;
+                }
+
+                Ok(contract_artifacts)
+            })
+            .await
+            .context("Failed to join the task loading contract artifacts")?
+        }
+    }
+
+    /// Same as [`Self::from_scarb_contract_artifact`], but runs the compilation to casm on
+    /// the blocking thread pool, so it does not stall the async executor
+    async fn from_starknet_contract_async(
+        starknet_contract: StarknetContract,
+        base_path: Utf8PathBuf,
+        compilation_options: CompilationOptions,
+        casm_cache: Option<CasmCache>,
+    ) -> Result<Self> {
+        tokio::task::spawn_blocking(move || {
+            Self::from_scarb_contract_artifact(
+                &starknet_contract,
+                &base_path,
+                &compilation_options,
+                casm_cache.as_ref(),
+            )
+        })
+        .await
+        .context("Failed to join the task compiling sierra to casm")?
+    }
+}
+
+/// Path of the casm file Scarb emits next to the sierra file when `casm = true` is set
+/// for the `starknet-contract` target
+fn sibling_casm_path(sierra_path: &Utf8Path) -> Option<Utf8PathBuf> {
+    let contract_stem = sierra_path
+        .file_name()?
+        .strip_suffix(".contract_class.json")?;
+
+    Some(sierra_path.with_file_name(format!("{contract_stem}.compiled_contract_class.json")))
+}
 
-        Ok(Self { sierra, casm })
+/// Casm emitted by Scarb next to the sierra file, `None` if it is missing
+/// or older than the sierra, which means it is stale
+fn fresh_sibling_casm(sierra_path: &Utf8Path) -> Option<String> {
+    let casm_path = sibling_casm_path(sierra_path)?;
+    let modified = |path: &Utf8Path| fs::metadata(path).and_then(|metadata| metadata.modified());
+
+    if modified(&casm_path).ok()? < modified(sierra_path).ok()? {
+        return None;
+    }
+
+    fs::read_to_string(casm_path).ok()
+}
+
+fn sierra_checksum(sierra: &str) -> String {
+    base16ct::lower::encode_string(&Sha3_256::digest(sierra.as_bytes()))
+}
+
+/// Verify that the class hash of the sierra contract class is equal to `expected_class_hash`,
+/// fails with [`ArtifactsError::ClassHashMismatch`] otherwise
+pub fn verify_sierra_class_hash(sierra: &str, expected_class_hash: Felt) -> Result<()> {
+    let sierra_class: SierraClass =
+        serde_json::from_str(sierra).context("Failed to parse sierra contract class")?;
+    let class_hash = sierra_class
+        .class_hash()
+        .context("Failed to compute class hash of sierra contract class")?;
+
+    if class_hash != expected_class_hash {
+        return Err(ArtifactsError::ClassHashMismatch {
+            class_hash,
+            expected_class_hash,
+        }
+        .into());
     }
+
+    Ok(())
+}
+
+/// Extract the ABI serialized as JSON from the sierra contract class
+pub fn abi_from_sierra(sierra: &str) -> Result<String> {
+    let sierra_class: serde_json::Value =
+        serde_json::from_str(sierra).context("Failed to parse sierra contract class")?;
+
+    Ok(sierra_class
+        .get("abi")
+        .map_or_else(|| "[]".to_string(), ToString::to_string))
 }
 
+/// Versions of `starknet_artifacts.json` schema this crate knows how to read
+pub const SUPPORTED_ARTIFACTS_VERSIONS: &[u32] = &[1];
+
 /// Get deserialized contents of `starknet_artifacts.json` file generated by Scarb
 ///
 /// # Arguments
 ///
 /// * `path` - A path to `starknet_artifacts.json` file.
 fn artifacts_for_package(path: &Utf8Path) -> Result<StarknetArtifacts> {
-    let starknet_artifacts =
-        fs::read_to_string(path).with_context(|| format!("Failed to read {path:?} contents"))?;
+    Ok(artifacts_for_package_accepting(
+        path,
+        SUPPORTED_ARTIFACTS_VERSIONS,
+    )?)
+}
+
+/// Same as [`artifacts_for_package`], but accepts files with any of `accepted_versions`
+fn artifacts_for_package_accepting(
+    path: &Utf8Path,
+    accepted_versions: &[u32],
+) -> Result<StarknetArtifacts, ArtifactsError> {
+    let starknet_artifacts = read_artifacts_file(path)?;
+    parse_starknet_artifacts(path, &starknet_artifacts, accepted_versions)
+}
+
+/// Parse `contents` of `starknet_artifacts.json` file, `path` is used in error messages only
+fn parse_starknet_artifacts(
+    path: &Utf8Path,
+    contents: &str,
+    accepted_versions: &[u32],
+) -> Result<StarknetArtifacts, ArtifactsError> {
+    verify_artifacts_version(path, contents, accepted_versions)?;
     let starknet_artifacts: StarknetArtifacts =
-        serde_json::from_str(starknet_artifacts.as_str())
-            .with_context(|| format!("Failed to parse {path:?} contents. Make sure you have enabled sierra code generation in Scarb.toml"))?;
+        serde_json::from_str(contents).map_err(|source| ArtifactsError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    verify_unique_contract_names(path, &starknet_artifacts)?;
     Ok(starknet_artifacts)
 }
 
+/// Checked before parsing the whole file, so a schema change reports the version mismatch
+/// instead of a confusing parsing error
+fn verify_artifacts_version(
+    path: &Utf8Path,
+    contents: &str,
+    accepted_versions: &[u32],
+) -> Result<(), ArtifactsError> {
+    #[derive(Deserialize)]
+    struct VersionOnly {
+        version: u32,
+    }
+
+    // Files without a version are reported by the full parsing
+    let Ok(VersionOnly { version }) = serde_json::from_str(contents) else {
+        return Ok(());
+    };
+    if !accepted_versions.contains(&version) {
+        return Err(ArtifactsError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version,
+            supported_versions: accepted_versions.to_vec(),
+        });
+    }
+    Ok(())
+}
+
+fn read_artifacts_file(path: &Utf8Path) -> Result<String, ArtifactsError> {
+    fs::read_to_string(path).map_err(|source| ArtifactsError::Read {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Contracts are identified by their names, so a name listed twice in one file would make
+/// one of the contracts silently overwrite the other
+fn verify_unique_contract_names(
+    path: &Utf8Path,
+    artifacts: &StarknetArtifacts,
+) -> Result<(), ArtifactsError> {
+    let mut ids_by_name: HashMap<&str, Vec<&str>> = HashMap::new();
+    for contract in &artifacts.contracts {
+        ids_by_name
+            .entry(&contract.contract_name)
+            .or_default()
+            .push(&contract.id);
+    }
+
+    if let Some((name, ids)) = ids_by_name
+        .into_iter()
+        .filter(|(_, ids)| ids.len() > 1)
+        .min_by_key(|(name, _)| *name)
+    {
+        return Err(ArtifactsError::DuplicatedContractName {
+            path: path.to_path_buf(),
+            contract_name: name.to_string(),
+            ids: ids.into_iter().map(ToString::to_string).collect(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Kind of Scarb build contract artifacts were produced by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildOrigin {
+    /// `starknet-contract` target built by `scarb build`
+    Standard,
+    /// Test target built by `scarb build --test`, whose contracts may include test-only code,
+    /// so they should not be declared on a live network
+    Test,
+}
+
+impl BuildOrigin {
+    #[must_use]
+    pub fn from_test_type(test_type: Option<TestType>) -> Self {
+        match test_type {
+            Some(_) => BuildOrigin::Test,
+            None => BuildOrigin::Standard,
+        }
+    }
+}
+
+/// Kind of test target built by `scarb build --test`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TestType {
+    Unit,
+    Integration,
+}
+
+impl TestType {
+    /// Map the value of the `test-type` param used by Scarb, `None` if the value is not known.
+    /// Surrounding whitespace and letter case are ignored.
+    #[must_use]
+    pub fn from_scarb_str(test_type: &str) -> Option<Self> {
+        match test_type.trim().to_ascii_lowercase().as_str() {
+            "unit" => Some(TestType::Unit),
+            "integration" => Some(TestType::Integration),
+            _ => None,
+        }
+    }
+
+    /// Infer the kind of test target from the `_unittest` or `_integrationtest` suffix of its name
+    #[must_use]
+    pub fn from_target_name(target_name: &str) -> Option<Self> {
+        [TestType::Unit, TestType::Integration]
+            .into_iter()
+            .find(|test_type| target_name.ends_with(&format!("_{}test", test_type.as_scarb_str())))
+    }
+
+    /// Kind of the test target read from its `test-type` param. Some Scarb versions do not set
+    /// the param, in which case the kind is inferred from the target name instead
+    #[must_use]
+    pub fn from_target(target: &TargetMetadata) -> Option<Self> {
+        match target.params.get("test-type").and_then(|v| v.as_str()) {
+            Some(test_type) => TestType::from_scarb_str(test_type),
+            None => TestType::from_target_name(&target.name),
+        }
+    }
+
+    /// Value of the `test-type` param used by Scarb for this kind of test target
+    #[must_use]
+    pub fn as_scarb_str(self) -> &'static str {
+        match self {
+            TestType::Unit => "unit",
+            TestType::Integration => "integration",
+        }
+    }
+}
+
+/// Location of a `starknet_artifacts.json` file generated by Scarb
+#[derive(Debug, PartialEq, Clone)]
+pub struct ContractArtifactData {
+    /// Path to the `starknet_artifacts.json` file
+    pub path: Utf8PathBuf,
+    /// Type of the test target the artifacts were built with (`integration` or `unit`),
+    /// `None` if they were built with the `starknet-contract` target
+    pub test_type: Option<TestType>,
+}
+
+fn starknet_artifacts_data(
+    target_dir: &Utf8Path,
+    target_name: &str,
+    current_profile: &str,
+    test_type: Option<TestType>,
+) -> Option<ContractArtifactData> {
+    let path = target_dir
+        .join(current_profile)
+        .join(starknet_artifacts_file_name(target_name, test_type));
+
+    if path.exists() {
+        Some(ContractArtifactData { path, test_type })
+    } else {
+        None
+    }
+}
+
+fn starknet_artifacts_file_name(target_name: &str, test_type: Option<TestType>) -> String {
+    if let Some(test_type) = test_type {
+        test_artifacts_file_name(&format!("{target_name}_{}test", test_type.as_scarb_str()))
+    } else {
+        artifacts_file_name(target_name)
+    }
+}
+
+/// Name of the `starknet_artifacts.json` file generated by `scarb build` for the given target
+#[must_use]
+pub fn artifacts_file_name(target_name: &str) -> String {
+    format!("{target_name}.starknet_artifacts.json")
+}
+
+/// Name of the `starknet_artifacts.json` file generated by `scarb build --test` for the given
+/// test target, e.g. `package_unittest`
+#[must_use]
+pub fn test_artifacts_file_name(test_target_name: &str) -> String {
+    format!("{test_target_name}.test.starknet_artifacts.json")
+}
+
 /// Try getting the path to `starknet_artifacts.json` file that is generated by `scarb build` or `scarb build --test` commands.
 /// If contract artifacts are produced as part of the test target and exist in both `unittest` and `integrationtest`, then the path to `integrationtest` will be returned.
 /// If the file is not present, `None` is returned.
-fn get_starknet_artifacts_path(
+#[must_use]
+pub fn get_starknet_artifacts_path(
     target_dir: &Utf8Path,
     target_name: &str,
     current_profile: &str,
     use_test_target_contracts: bool,
-) -> Option<Utf8PathBuf> {
-    let starknet_artifacts_file_name = |test_type: Option<&str>| -> String {
-        if let Some(test_type) = test_type {
-            format!("{target_name}_{test_type}test.test.starknet_artifacts.json")
+) -> Option<ContractArtifactData> {
+    if use_test_target_contracts {
+        let artifacts_data = get_starknet_artifacts_paths_from_test_targets(
+            target_dir,
+            target_name,
+            current_profile,
+            None,
+        );
+        select_base_artifact(&artifacts_data).cloned()
+    } else {
+        starknet_artifacts_data(target_dir, target_name, current_profile, None)
+    }
+}
+
+/// Same as [`get_starknet_artifacts_path`], but if the file is not present directly in the profile
+/// directory, it is also searched for in its subdirectories, up to `max_depth` levels deep.
+/// Only files named exactly as the ones generated by Scarb for `target_name` are matched.
+#[must_use]
+pub fn get_starknet_artifacts_path_recursive(
+    target_dir: &Utf8Path,
+    target_name: &str,
+    current_profile: &str,
+    use_test_target_contracts: bool,
+    max_depth: usize,
+) -> Option<ContractArtifactData> {
+    get_starknet_artifacts_path(
+        target_dir,
+        target_name,
+        current_profile,
+        use_test_target_contracts,
+    )
+    .or_else(|| {
+        let test_types = if use_test_target_contracts {
+            vec![Some(TestType::Integration), Some(TestType::Unit)]
         } else {
-            format!("{target_name}.starknet_artifacts.json")
-        }
-    };
+            vec![None]
+        };
+        let profile_dir = target_dir.join(current_profile);
+
+        test_types.into_iter().find_map(|test_type| {
+            let file_name = starknet_artifacts_file_name(target_name, test_type);
+            find_in_subdirectories(&profile_dir, &file_name, max_depth)
+                .map(|path| ContractArtifactData { path, test_type })
+        })
+    })
+}
 
-    let check_path = |file_name: &str| -> Option<Utf8PathBuf> {
-        let path = target_dir.join(current_profile).join(file_name);
+/// Breadth-first search for a file named `file_name` in subdirectories of `dir`
+fn find_in_subdirectories(
+    dir: &Utf8Path,
+    file_name: &str,
+    max_depth: usize,
+) -> Option<Utf8PathBuf> {
+    let mut current_level = vec![dir.to_path_buf()];
 
-        if path.exists() {
-            Some(path)
-        } else {
-            None
+    for _ in 0..max_depth {
+        let mut subdirectories: Vec<Utf8PathBuf> = current_level
+            .iter()
+            .filter_map(|dir| dir.read_dir_utf8().ok())
+            .flatten()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|file_type| file_type.is_dir()))
+            .map(camino::Utf8DirEntry::into_path)
+            .collect();
+        subdirectories.sort();
+
+        if let Some(path) = subdirectories
+            .iter()
+            .map(|subdirectory| subdirectory.join(file_name))
+            .find(|path| path.is_file())
+        {
+            return Some(path);
         }
-    };
 
-    if use_test_target_contracts {
-        ["integration", "unit"]
-            .iter()
-            .find_map(|test_type| check_path(&starknet_artifacts_file_name(Some(test_type))))
-    } else {
-        check_path(&starknet_artifacts_file_name(None))
+        current_level = subdirectories;
     }
+
+    None
+}
+
+/// Get paths to all `starknet_artifacts.json` files generated by `scarb build --test` command.
+/// `integrationtest` artifacts, if present, come before `unittest` ones.
+/// If `test_type_filter` is set, only artifacts of the given test type are returned.
+#[must_use]
+pub fn get_starknet_artifacts_paths_from_test_targets(
+    target_dir: &Utf8Path,
+    target_name: &str,
+    current_profile: &str,
+    test_type_filter: Option<TestType>,
+) -> Vec<ContractArtifactData> {
+    [TestType::Integration, TestType::Unit]
+        .into_iter()
+        .filter(|test_type| test_type_filter.is_none() || test_type_filter == Some(*test_type))
+        .filter_map(|test_type| {
+            starknet_artifacts_data(target_dir, target_name, current_profile, Some(test_type))
+        })
+        .collect()
+}
+
+/// Select artifacts whose contracts take precedence over the ones from other test targets:
+/// `integrationtest` artifacts if present, otherwise the first ones.
+#[must_use]
+pub fn select_base_artifact(
+    artifacts_data: &[ContractArtifactData],
+) -> Option<&ContractArtifactData> {
+    artifacts_data
+        .iter()
+        .find(|artifact_data| artifact_data.test_type == Some(TestType::Integration))
+        .or_else(|| artifacts_data.first())
+}
+
+/// Strategy of resolving a contract present in artifacts of more than one test target
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// Keep the contract from the artifacts loaded first (`integrationtest` before `unittest`)
+    #[default]
+    KeepBase,
+    /// Keep the contract whose sierra file was modified most recently
+    PreferNewest,
+    /// Fail if sierra or casm of the contract differs between test targets
+    ErrorOnConflict,
 }
 
-/// Get the map with `StarknetContractArtifacts` for the given package
+/// Get the map with `StarknetContractArtifacts` for the given package.
+/// Same as [`load_artifacts`] with the default options.
 pub fn get_contracts_artifacts_and_source_sierra_paths(
     metadata: &Metadata,
     package: &PackageId,
     profile: Option<&str>,
     use_test_target_contracts: bool,
 ) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
-    let target_name = target_name_for_package(metadata, package)?;
-    let target_dir = target_dir_for_workspace(metadata);
-    let maybe_contracts_path = get_starknet_artifacts_path(
-        &target_dir,
-        &target_name,
-        profile.unwrap_or(metadata.current_profile.as_str()),
-        use_test_target_contracts,
-    );
-
-    let map = match maybe_contracts_path {
-        Some(contracts_path) => load_contracts_artifacts_and_source_sierra_paths(&contracts_path)?,
-        None => HashMap::default(),
-    };
-
-    Ok(map)
+    load_artifacts(
+        metadata,
+        package,
+        &default_load_options(profile, use_test_target_contracts),
+    )
 }
 
-fn load_contracts_artifacts_and_source_sierra_paths(
-    contracts_path: &Utf8PathBuf,
-) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
-    let base_path = contracts_path
-        .parent()
-        .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &contracts_path))?;
-    let artifacts = artifacts_for_package(contracts_path)?;
-    let mut map = HashMap::new();
-
-    for ref contract in artifacts.contracts {
-        let name = contract.contract_name.clone();
-        let contract_artifacts =
-            StarknetContractArtifacts::from_scarb_contract_artifact(contract, base_path)?;
-
-        let sierra_path = base_path.join(contract.artifacts.sierra.clone());
-
-        map.insert(name.clone(), (contract_artifacts, sierra_path));
+fn default_load_options(
+    profile: Option<&str>,
+    use_test_target_contracts: bool,
+) -> ArtifactLoadOptions<'_> {
+    let mut options = ArtifactLoadOptions::new();
+    if let Some(profile) = profile {
+        options.profile(profile);
     }
-    Ok(map)
+    options.use_test_target_contracts(use_test_target_contracts);
+    options
 }
 
-fn compilation_unit_for_package<'a>(
-    metadata: &'a Metadata,
+/// Same as [`get_contracts_artifacts_and_source_sierra_paths`], but returns the contracts
+/// sorted by name, so iterating them gives the same order on every run
+pub fn get_contracts_artifacts_sorted(
+    metadata: &Metadata,
     package: &PackageId,
-) -> Result<&'a CompilationUnitMetadata> {
+    profile: Option<&str>,
+    use_test_target_contracts: bool,
+) -> Result<Vec<(String, (StarknetContractArtifacts, Utf8PathBuf))>> {
+    let mut contracts: Vec<_> = get_contracts_artifacts_and_source_sierra_paths(
+        metadata,
+        package,
+        profile,
+        use_test_target_contracts,
+    )?
+    .into_iter()
+    .collect();
+    contracts.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    Ok(contracts)
+}
+
+/// Get the maps with `StarknetContractArtifacts` for all members of the workspace.
+/// Failure to load artifacts of one package is reported under its id and does not stop
+/// loading the other packages
+#[must_use]
+pub fn get_workspace_contracts_artifacts(
+    metadata: &Metadata,
+    profile: Option<&str>,
+    use_test_target_contracts: bool,
+) -> HashMap<PackageId, Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>>> {
     metadata
-        .compilation_units
+        .workspace
+        .members
         .iter()
-        .filter(|unit| unit.package == *package)
-        .min_by_key(|unit| match unit.target.kind.as_str() {
-            name @ "starknet-contract" => (0, name),
-            name @ "lib" => (1, name),
-            name => (2, name),
+        .map(|package| {
+            let contracts = get_contracts_artifacts_and_source_sierra_paths(
+                metadata,
+                package,
+                profile,
+                use_test_target_contracts,
+            )
+            .with_context(|| format!("Failed to load contracts of package = {package}"));
+
+            (package.clone(), contracts)
         })
-        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))
+        .collect()
 }
 
-/// Get the target name for the given package
-pub fn target_name_for_package(metadata: &Metadata, package: &PackageId) -> Result<String> {
-    let compilation_unit = compilation_unit_for_package(metadata, package)?;
-    Ok(compilation_unit.target.name.clone())
+/// Artifact files resolved for a package, serializable for consumption by external tools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedArtifacts {
+    pub package: PackageId,
+    /// Path to the `starknet_artifacts.json` file contracts are primarily loaded from,
+    /// `None` if no artifacts were found
+    pub manifest: Option<Utf8PathBuf>,
+    /// Contracts sorted by name
+    pub contracts: Vec<ResolvedContract>,
 }
 
-#[must_use]
-pub fn target_dir_for_workspace(metadata: &Metadata) -> Utf8PathBuf {
-    metadata
-        .target_dir
-        .clone()
-        .unwrap_or_else(|| metadata.workspace.root.join("target"))
+/// Contract listed in a `starknet_artifacts.json` file with its resolved sierra path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResolvedContract {
+    pub name: String,
+    pub sierra_path: Utf8PathBuf,
 }
 
-/// Get a name of the given package
-pub fn name_for_package(metadata: &Metadata, package: &PackageId) -> Result<String> {
-    let package = metadata
-        .get_package(package)
-        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))?;
+/// Resolve paths of artifact files of the given package the same way
+/// [`get_contracts_artifacts_and_source_sierra_paths`] does, without reading sierra
+/// or compiling it to casm
+pub fn resolve_artifacts(
+    metadata: &Metadata,
+    package: &PackageId,
+    profile: Option<&str>,
+    use_test_target_contracts: bool,
+) -> Result<ResolvedArtifacts> {
+    let artifacts_data =
+        package_artifacts_data(metadata, package, profile, use_test_target_contracts, None)?;
+    let base_artifact_data = select_base_artifact(&artifacts_data);
+
+    let mut contracts: Vec<ResolvedContract> = vec![];
+    for artifact_data in base_artifact_data.into_iter().chain(
+        artifacts_data
+            .iter()
+            .filter(|artifact_data| Some(*artifact_data) != base_artifact_data),
+    ) {
+        let base_path = artifact_data
+            .path
+            .parent()
+            .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &artifact_data.path))?;
+
+        for contract in artifacts_for_package(&artifact_data.path)?.contracts {
+            if contracts
+                .iter()
+                .all(|resolved| resolved.name != contract.contract_name)
+            {
+                contracts.push(ResolvedContract {
+                    name: contract.contract_name,
+                    sierra_path: base_path.join(contract.artifacts.sierra),
+                });
+            }
+        }
+    }
+    contracts.sort_by(|a, b| a.name.cmp(&b.name));
 
-    Ok(package.name.clone())
+    Ok(ResolvedArtifacts {
+        package: package.clone(),
+        manifest: base_artifact_data.map(|artifact_data| artifact_data.path.clone()),
+        contracts,
+    })
 }
 
-/// Checks if the specified package has version compatible with the specified requirement
-pub fn package_matches_version_requirement(
+/// Names of contracts listed in the artifacts of each test target of the given package, sorted.
+/// Unlike [`get_contracts_artifacts_and_source_sierra_paths`], contracts present in more than one
+/// test target are listed under each of them. Only `starknet_artifacts.json` files are read.
+pub fn get_contract_names_by_test_type(
     metadata: &Metadata,
-    name: &str,
-    version_req: &VersionReq,
-) -> Result<bool> {
-    let mut packages = metadata
-        .packages
-        .iter()
-        .filter(|package| package.name == name);
-
-    match (packages.next(), packages.next()) {
-        (Some(package), None) => Ok(version_req.matches(&package.version)),
-        (None, None) => Err(anyhow!("Package {name} is not present in dependencies.")),
-        _ => Err(anyhow!("Package {name} is duplicated in dependencies")),
+    package: &PackageId,
+    profile: Option<&str>,
+) -> Result<HashMap<TestType, Vec<String>>> {
+    let artifacts_data = package_artifacts_data(metadata, package, profile, true, None)?;
+
+    let mut contract_names = HashMap::new();
+    for artifact_data in artifacts_data {
+        let Some(test_type) = artifact_data.test_type else {
+            continue;
+        };
+
+        let mut names: Vec<_> = artifacts_for_package(&artifact_data.path)?
+            .contracts
+            .into_iter()
+            .map(|contract| contract.contract_name)
+            .collect();
+        names.sort();
+
+        contract_names.insert(test_type, names);
     }
+
+    Ok(contract_names)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::metadata::MetadataCommandExt;
+/// `target_dir` overrides the target directory derived from `metadata`
+fn package_artifacts_data(
+    metadata: &Metadata,
+    package: &PackageId,
+    profile: Option<&str>,
+    use_test_target_contracts: bool,
+    target_dir: Option<&Utf8Path>,
+) -> Result<Vec<ContractArtifactData>> {
+    let target_name = target_name_for_package(metadata, package)?;
+    let target_dir = match target_dir {
+        Some(target_dir) => target_dir.to_path_buf(),
+        None => existing_target_dir_for_workspace(metadata)?,
+    };
+    let profile_subdir = profile_subdir(metadata);
+    let current_profile = profile.unwrap_or(profile_subdir.as_str());
+
+    let artifacts_data = if use_test_target_contracts {
+        get_starknet_artifacts_paths_from_test_targets(
+            &target_dir,
+            &target_name,
+            current_profile,
+            None,
+        )
+    } else {
+        get_starknet_artifacts_path(&target_dir, &target_name, current_profile, false)
+            .into_iter()
+            .collect()
+    };
+
+    Ok(artifacts_data)
+}
+
+fn load_contracts_artifacts(
+    artifacts_data: &[ContractArtifactData],
+    accepted_versions: &[u32],
+    merge_policy: MergePolicy,
+    progress: &dyn Fn(&str, usize, usize),
+    casm_cache: Option<&CasmCache>,
+    compilation_options: &CompilationOptions,
+    mut timings: Option<&mut HashMap<String, Duration>>,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let Some(base_artifact_data) = select_base_artifact(artifacts_data) else {
+        return Ok(HashMap::new());
+    };
+
+    // Each manifest is parsed once, both to count all contracts up front and to load them
+    let mut manifests = artifacts_data
+        .iter()
+        .map(|artifact_data| {
+            Ok((
+                artifact_data,
+                read_manifest(&artifact_data.path, accepted_versions)?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let total = manifests
+        .iter()
+        .map(|(_, (_, artifacts))| artifacts.contracts.len())
+        .sum::<usize>();
+    let mut loaded = 0;
+    let mut on_contract_loaded = |name: &str, elapsed: Duration| {
+        loaded += 1;
+        progress(name, loaded, total);
+        if let Some(timings) = timings.as_deref_mut() {
+            *timings.entry(name.to_string()).or_default() += elapsed;
+        }
+    };
+
+    let base_index = manifests
+        .iter()
+        .position(|(artifact_data, _)| std::ptr::eq(*artifact_data, base_artifact_data))
+        .expect("Base artifact should be one of the artifacts");
+    let (_, base_manifest) = manifests.remove(base_index);
+
+    let mut map = load_artifacts_with_test_type(
+        base_artifact_data,
+        base_manifest,
+        &mut on_contract_loaded,
+        casm_cache,
+        compilation_options,
+    )?;
+
+    for (artifact_data, manifest) in manifests {
+        let contracts = load_artifacts_with_test_type(
+            artifact_data,
+            manifest,
+            &mut on_contract_loaded,
+            casm_cache,
+            compilation_options,
+        )?;
+        merge_contracts_artifacts(&mut map, contracts, merge_policy)?;
+    }
+
+    Ok(map)
+}
+
+fn load_artifacts_with_test_type(
+    artifact_data: &ContractArtifactData,
+    (base_path, artifacts): (Utf8PathBuf, StarknetArtifacts),
+    on_contract_loaded: &mut dyn FnMut(&str, Duration),
+    casm_cache: Option<&CasmCache>,
+    compilation_options: &CompilationOptions,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let mut contracts = load_parsed_artifacts_reporting(
+        artifacts,
+        base_path,
+        compilation_options,
+        on_contract_loaded,
+        casm_cache,
+    )?;
+
+    for (artifacts, _) in contracts.values_mut() {
+        artifacts.test_type = artifact_data.test_type;
+        artifacts.build_origin = Some(BuildOrigin::from_test_type(artifact_data.test_type));
+    }
+
+    Ok(contracts)
+}
+
+fn merge_contracts_artifacts(
+    base: &mut HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    other: HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    merge_policy: MergePolicy,
+) -> Result<()> {
+    for (name, contract) in other {
+        match base.entry(name) {
+            Entry::Vacant(entry) => {
+                entry.insert(contract);
+            }
+            Entry::Occupied(mut entry) => match merge_policy {
+                MergePolicy::KeepBase => {}
+                MergePolicy::PreferNewest => {
+                    let modified = |path: &Utf8Path| {
+                        fs::metadata(path)
+                            .and_then(|metadata| metadata.modified())
+                            .with_context(|| format!("Failed to read modification time of {path}"))
+                    };
+
+                    if modified(&contract.1)? > modified(&entry.get().1)? {
+                        entry.insert(contract);
+                    }
+                }
+                MergePolicy::ErrorOnConflict => {
+                    // Same sierra may be compiled to different casm, e.g. with other options
+                    if entry.get().0.casm != contract.0.casm
+                        || entry.get().0.sierra()? != contract.0.sierra()?
+                    {
+                        bail!(
+                            "Contract = {} differs between test targets artifacts",
+                            entry.key()
+                        );
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Load artifacts of all contracts listed in the `starknet_artifacts.json` file at `contracts_path`,
+/// without the need for Scarb metadata. Sierra paths are resolved relative to the file's directory.
+pub fn load_artifacts_from_manifest(
+    contracts_path: &Utf8Path,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    load_artifacts_from_manifest_with_options(contracts_path, &CompilationOptions::default())
+}
+
+/// Same as [`load_artifacts_from_manifest`], but compiles sierra to casm with the given
+/// `compilation_options` instead of the `universal-sierra-compiler` defaults.
+pub fn load_artifacts_from_manifest_with_options(
+    contracts_path: &Utf8Path,
+    compilation_options: &CompilationOptions,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let (base_path, artifacts) = read_manifest(contracts_path, SUPPORTED_ARTIFACTS_VERSIONS)?;
+
+    load_parsed_artifacts_reporting(
+        artifacts,
+        base_path,
+        compilation_options,
+        &mut |_, _| {},
+        None,
+    )
+}
+
+/// Same as [`load_artifacts_from_manifest_with_options`], but when `minify_sierra` is set, sierra
+/// of the contracts is kept in memory without insignificant whitespace, which lowers memory usage
+/// for large contracts. Minified sierra compiles to the same casm and has the same class hash,
+/// but is not byte-exact with the sierra file.
+pub fn load_artifacts_from_manifest_with_sierra_minification(
+    contracts_path: &Utf8Path,
+    compilation_options: &CompilationOptions,
+    minify_sierra: bool,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let mut contracts =
+        load_artifacts_from_manifest_with_options(contracts_path, compilation_options)?;
+
+    if minify_sierra {
+        minify_contracts_sierra(&mut contracts)?;
+    }
+
+    Ok(contracts)
+}
+
+fn minify_contracts_sierra(
+    contracts: &mut HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+) -> Result<()> {
+    for (name, (artifacts, _)) in contracts {
+        artifacts
+            .minify_sierra()
+            .with_context(|| format!("Failed to minify sierra of contract = {name}"))?;
+    }
+
+    Ok(())
+}
+
+/// Same as [`load_artifacts_from_manifest`], but skips compiling sierra to casm, which leaves
+/// `casm` of the artifacts empty. Meant for consumers that only need sierra or the ABI.
+pub fn load_artifacts_from_manifest_without_casm(
+    contracts_path: &Utf8Path,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let base_path = contracts_path
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &contracts_path))?;
+    let artifacts = artifacts_for_package(contracts_path)?;
+
+    Ok(artifacts
+        .contracts
+        .iter()
+        .map(|contract| {
+            let artifacts = StarknetContractArtifacts::from_scarb_contract_artifact_without_casm(
+                contract, base_path,
+            );
+            let sierra_path = base_path.join(contract.artifacts.sierra.clone());
+
+            (contract.contract_name.clone(), (artifacts, sierra_path))
+        })
+        .collect())
+}
+
+/// Number of contracts listed in the `starknet_artifacts.json` file at `contracts_path`.
+/// Neither sierra nor casm of the contracts is read.
+pub fn count_contracts(contracts_path: &Utf8Path) -> Result<usize> {
+    Ok(artifacts_for_package(contracts_path)?.contracts.len())
+}
+
+/// Parse the `starknet_artifacts.json` file at `contracts_path`, returning it together with
+/// the directory sierra paths listed in it are relative to
+fn read_manifest(
+    contracts_path: &Utf8Path,
+    accepted_versions: &[u32],
+) -> Result<(Utf8PathBuf, StarknetArtifacts)> {
+    let base_path = contracts_path
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &contracts_path))?
+        .to_path_buf();
+    let artifacts = artifacts_for_package_accepting(contracts_path, accepted_versions)?;
+
+    Ok((base_path, artifacts))
+}
+
+fn load_parsed_artifacts_reporting(
+    artifacts: StarknetArtifacts,
+    base_path: Utf8PathBuf,
+    compilation_options: &CompilationOptions,
+    on_contract_loaded: &mut dyn FnMut(&str, Duration),
+    casm_cache: Option<&CasmCache>,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let mut contracts = iter_parsed_contract_artifacts(
+        artifacts,
+        base_path,
+        compilation_options.clone(),
+        casm_cache,
+    );
+    let mut map = HashMap::new();
+
+    loop {
+        let start = Instant::now();
+        let Some(contract) = contracts.next() else {
+            break;
+        };
+        let (name, contract_artifacts) = contract?;
+        on_contract_loaded(&name, start.elapsed());
+        map.insert(name, contract_artifacts);
+    }
+
+    Ok(map)
+}
+
+/// Iterate over artifacts of contracts listed in the `starknet_artifacts.json` file at
+/// `contracts_path`. Sierra of a contract is read and compiled to casm only once the iterator
+/// reaches it, so contracts can be processed one at a time without loading all of them up front.
+pub fn iter_contract_artifacts(
+    contracts_path: &Utf8Path,
+) -> Result<impl Iterator<Item = Result<(String, (StarknetContractArtifacts, Utf8PathBuf))>>> {
+    let (base_path, artifacts) = read_manifest(contracts_path, SUPPORTED_ARTIFACTS_VERSIONS)?;
+
+    Ok(iter_parsed_contract_artifacts(
+        artifacts,
+        base_path,
+        CompilationOptions::default(),
+        None,
+    ))
+}
+
+fn iter_parsed_contract_artifacts<'a>(
+    artifacts: StarknetArtifacts,
+    base_path: Utf8PathBuf,
+    compilation_options: CompilationOptions,
+    casm_cache: Option<&'a CasmCache>,
+) -> impl Iterator<Item = Result<(String, (StarknetContractArtifacts, Utf8PathBuf))>> + 'a {
+    artifacts.contracts.into_iter().map(move |contract| {
+        let contract_artifacts = load_contract_artifacts_and_source_sierra_path(
+            &contract,
+            &base_path,
+            &compilation_options,
+            casm_cache,
+        )?;
+
+        Ok((contract.contract_name, contract_artifacts))
+    })
+}
+
+/// Async version of [`load_artifacts_from_manifest_with_options`]. Sierra is compiled to casm on
+/// the blocking thread pool, so loading does not stall the async executor.
+/// Contracts are loaded in parallel, using as many tasks as there are available cores.
+/// Must be called from within a Tokio runtime.
+pub async fn load_artifacts_from_manifest_async(
+    contracts_path: &Utf8Path,
+    compilation_options: &CompilationOptions,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let loading = {
+        let mut options = ArtifactLoadOptions::new();
+        options.compilation_options(compilation_options.clone());
+        load_artifacts_from_manifest_with_max_parallelism(contracts_path, &options, None)
+    };
+
+    loading.await
+}
+
+/// Same as [`load_artifacts_from_manifest_async`], but loads the contracts according to `options`
+/// and at most `max_parallelism` of them are loaded at once, which bounds the number of
+/// concurrently running `universal-sierra-compiler` processes and open files.
+/// Defaults to the number of available cores if `max_parallelism` is `None`.
+/// Of `options`, the ones applying to a single `starknet_artifacts.json` file are used:
+/// the casm cache, compilation options, sierra minification and accepted artifacts versions.
+pub fn load_artifacts_from_manifest_with_max_parallelism(
+    contracts_path: &Utf8Path,
+    options: &ArtifactLoadOptions,
+    max_parallelism: Option<NonZeroUsize>,
+) -> impl Future<Output = Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>>>
+       + Send
+       + 'static {
+    // Options are copied before the loading starts, so the future does not borrow them
+    let contracts_path = contracts_path.to_path_buf();
+    let accepted_versions = options.accepted_artifacts_versions();
+    let compilation_options = options.compilation_options.clone();
+    let casm_cache = options.casm_cache.cloned();
+    let minify_sierra = options.minify_sierra;
+
+    async move {
+        let contracts = load_contracts_in_parallel(
+            &contracts_path,
+            &accepted_versions,
+            &compilation_options,
+            casm_cache.as_ref(),
+            max_parallelism,
+        )
+        .await;
+
+        let mut contracts = contracts?;
+        if minify_sierra {
+            minify_contracts_sierra(&mut contracts)?;
+        }
+
+        Ok(contracts)
+    }
+}
+
+async fn load_contracts_in_parallel(
+    contracts_path: &Utf8Path,
+    accepted_versions: &[u32],
+    compilation_options: &CompilationOptions,
+    casm_cache: Option<&CasmCache>,
+    max_parallelism: Option<NonZeroUsize>,
+) -> Result<HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>> {
+    let base_path = contracts_path
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &contracts_path))?;
+    let artifacts = artifacts_for_package_accepting(contracts_path, accepted_versions)?;
+    let max_parallelism = max_parallelism
+        .unwrap_or_else(|| thread::available_parallelism().unwrap_or(NonZeroUsize::MIN));
+    let semaphore = Arc::new(Semaphore::new(max_parallelism.get()));
+    let mut tasks = JoinSet::new();
+
+    for contract in artifacts.contracts {
+        let semaphore = Arc::clone(&semaphore);
+        let base_path = base_path.to_path_buf();
+        let compilation_options = compilation_options.clone();
+        let casm_cache = casm_cache.cloned();
+
+        tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .context("Failed to acquire permit for loading contract artifacts")?;
+            let name = contract.contract_name.clone();
+            let sierra_path = base_path.join(contract.artifacts.sierra.clone());
+            let contract_artifacts = StarknetContractArtifacts::from_starknet_contract_async(
+                contract,
+                base_path,
+                compilation_options,
+                casm_cache,
+            )
+            .await
+            .with_context(|| format!("Failed to load artifacts of contract = {name}"))?;
+
+            Ok::<_, anyhow::Error>((name, (contract_artifacts, sierra_path)))
+        });
+    }
+
+    let mut map = HashMap::new();
+    while let Some(result) = tasks.join_next().await {
+        let (name, contract_artifacts) =
+            result.context("Failed to join the task loading contract artifacts")??;
+        map.insert(name, contract_artifacts);
+    }
+    Ok(map)
+}
+
+/// Get the absolute path to the sierra file of `contract_name` listed in the `starknet_artifacts.json`
+/// file at `contracts_path`. Only the manifest is read, the sierra itself is neither loaded nor compiled.
+pub fn sierra_path_for_contract(
+    contracts_path: &Utf8Path,
+    contract_name: &str,
+) -> Result<Utf8PathBuf> {
+    let contracts_path = contracts_path
+        .canonicalize_utf8()
+        .with_context(|| format!("Failed to canonicalize path = {contracts_path}"))?;
+    let base_path = contracts_path
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent for path = {}", &contracts_path))?;
+    let artifacts = artifacts_for_package(&contracts_path)?;
+
+    let contract = artifacts
+        .contracts
+        .iter()
+        .find(|contract| contract.contract_name == contract_name)
+        .ok_or_else(|| anyhow!("Contract = {contract_name} not found in {contracts_path}"))?;
+
+    Ok(base_path.join(&contract.artifacts.sierra))
+}
+
+/// Check that the sierra file of every contract listed in the `starknet_artifacts.json` file at
+/// `contracts_path` exists and can be read, without loading any of them. Fails with
+/// [`ArtifactsError::MissingSierraFiles`] listing all sierra files which cannot be read,
+/// so a broken artifacts directory can be fixed at once before a long run.
+/// Other variants are returned if the `starknet_artifacts.json` file itself cannot be read.
+pub fn validate_sierra_paths(contracts_path: &Utf8Path) -> Result<(), ArtifactsError> {
+    let contracts_path =
+        contracts_path
+            .canonicalize_utf8()
+            .map_err(|source| ArtifactsError::Read {
+                path: contracts_path.to_path_buf(),
+                source,
+            })?;
+    let base_path = contracts_path
+        .parent()
+        .expect("Canonicalized path of a file should have a parent");
+    let artifacts = artifacts_for_package_accepting(&contracts_path, SUPPORTED_ARTIFACTS_VERSIONS)?;
+
+    let missing: Vec<_> = artifacts
+        .contracts
+        .iter()
+        .map(|contract| base_path.join(&contract.artifacts.sierra))
+        .filter(|sierra_path| fs::File::open(sierra_path).is_err())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(ArtifactsError::MissingSierraFiles {
+            path: contracts_path,
+            sierra_paths: missing,
+        });
+    }
+
+    Ok(())
+}
+
+/// Load artifacts of all contracts listed in the `starknet_artifacts.json` file at `contracts_path`.
+/// Unlike [`get_contracts_artifacts_and_source_sierra_paths`], a contract that fails to load
+/// does not abort loading the others; its name is returned together with the error instead.
+/// An error is returned only if the `starknet_artifacts.json` file itself cannot be read.
+/// Of `options`, the ones applying to a single `starknet_artifacts.json` file are used:
+/// the casm cache, compilation options, sierra minification and accepted artifacts versions.
+#[allow(clippy::type_complexity)]
+pub fn load_artifacts_lenient(
+    contracts_path: &Utf8Path,
+    options: &ArtifactLoadOptions,
+) -> Result<(
+    HashMap<String, (StarknetContractArtifacts, Utf8PathBuf)>,
+    Vec<(String, anyhow::Error)>,
+)> {
+    let (base_path, artifacts) =
+        read_manifest(contracts_path, &options.accepted_artifacts_versions())?;
+    let mut map = HashMap::new();
+    let mut failures = vec![];
+
+    for contract in &artifacts.contracts {
+        let name = contract.contract_name.clone();
+
+        let loaded = load_contract_artifacts_and_source_sierra_path(
+            contract,
+            &base_path,
+            &options.compilation_options,
+            options.casm_cache,
+        )
+        .and_then(|(mut contract_artifacts, sierra_path)| {
+            if options.minify_sierra {
+                contract_artifacts.minify_sierra()?;
+            }
+            Ok((contract_artifacts, sierra_path))
+        });
+
+        match loaded {
+            Ok(contract_artifacts) => {
+                map.insert(name, contract_artifacts);
+            }
+            Err(err) => failures.push((name, err)),
+        }
+    }
+    Ok((map, failures))
+}
+
+fn load_contract_artifacts_and_source_sierra_path(
+    contract: &StarknetContract,
+    base_path: &Utf8Path,
+    compilation_options: &CompilationOptions,
+    casm_cache: Option<&CasmCache>,
+) -> Result<(StarknetContractArtifacts, Utf8PathBuf)> {
+    let contract_artifacts = StarknetContractArtifacts::from_scarb_contract_artifact(
+        contract,
+        base_path,
+        compilation_options,
+        casm_cache,
+    )
+    .with_context(|| {
+        format!(
+            "Failed to load artifacts of contract = {}",
+            contract.contract_name
+        )
+    })?;
+    let sierra_path = base_path.join(contract.artifacts.sierra.clone());
+
+    Ok((contract_artifacts, sierra_path))
+}
+
+fn compilation_unit_priority(unit: &CompilationUnitMetadata) -> (u8, &str) {
+    match unit.target.kind.as_str() {
+        name @ "starknet-contract" => (0, name),
+        name @ "lib" => (1, name),
+        name => (2, name),
+    }
+}
+
+/// Get all compilation units of the given package, sorted by target kind priority:
+/// `starknet-contract` first, then `lib`, then the remaining kinds alphabetically
+#[must_use]
+pub fn compilation_units_for_package<'a>(
+    metadata: &'a Metadata,
+    package: &PackageId,
+) -> Vec<&'a CompilationUnitMetadata> {
+    let mut units: Vec<_> = metadata
+        .compilation_units
+        .iter()
+        .filter(|unit| unit.package == *package)
+        .collect();
+    units.sort_by(|a, b| compilation_unit_priority(a).cmp(&compilation_unit_priority(b)));
+    units
+}
+
+fn compilation_unit_for_package<'a>(
+    metadata: &'a Metadata,
+    package: &PackageId,
+) -> Result<&'a CompilationUnitMetadata> {
+    compilation_units_for_package(metadata, package)
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))
+}
+
+/// Get the target name for the given package
+pub fn target_name_for_package(metadata: &Metadata, package: &PackageId) -> Result<String> {
+    let compilation_unit = compilation_unit_for_package(metadata, package)?;
+    Ok(compilation_unit.target.name.clone())
+}
+
+/// Get the target name of the given kind (e.g. `lib`, `starknet-contract`) for the given package
+pub fn target_name_for_package_with_kind(
+    metadata: &Metadata,
+    package: &PackageId,
+    kind: &str,
+) -> Result<String> {
+    let compilation_unit = compilation_units_for_package(metadata, package)
+        .into_iter()
+        .find(|unit| unit.target.kind == kind)
+        .ok_or_else(|| anyhow!("Failed to find target of kind = {kind} for package = {package}"))?;
+    Ok(compilation_unit.target.name.clone())
+}
+
+#[must_use]
+pub fn target_dir_for_workspace(metadata: &Metadata) -> Utf8PathBuf {
+    metadata
+        .target_dir
+        .clone()
+        .unwrap_or_else(|| metadata.workspace.root.join("target"))
+}
+
+/// Subdirectory of the target directory with the build output of the active profile,
+/// e.g. `release` for `scarb --release build`
+#[must_use]
+pub fn profile_subdir(metadata: &Metadata) -> Utf8PathBuf {
+    Utf8PathBuf::from(&metadata.current_profile)
+}
+
+/// Directory with the build output of the active profile, e.g. `target/dev`
+#[must_use]
+pub fn profile_target_dir(metadata: &Metadata) -> Utf8PathBuf {
+    target_dir_for_workspace(metadata).join(profile_subdir(metadata))
+}
+
+/// Same as [`target_dir_for_workspace`], but fails if the directory does not exist,
+/// which means the workspace has not been built yet
+pub fn existing_target_dir_for_workspace(metadata: &Metadata) -> Result<Utf8PathBuf> {
+    let target_dir = target_dir_for_workspace(metadata);
+
+    if !target_dir.is_dir() {
+        bail!("No build output found, target directory = {target_dir} does not exist. Run `scarb build` first");
+    }
+
+    Ok(target_dir)
+}
+
+/// Basic information about a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: Version,
+    /// Cairo edition of the package, `None` if not reported by Scarb
+    pub edition: Option<String>,
+}
+
+/// Get name, version and Cairo edition of the given package
+pub fn package_info(metadata: &Metadata, package: &PackageId) -> Result<PackageInfo> {
+    let package = metadata
+        .get_package(package)
+        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))?;
+
+    Ok(PackageInfo {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        edition: package.edition.clone(),
+    })
+}
+
+/// Get a name of the given package
+pub fn name_for_package(metadata: &Metadata, package: &PackageId) -> Result<String> {
+    Ok(package_info(metadata, package)?.name)
+}
+
+/// Find the workspace member whose `starknet_artifacts.json` files in `target_dir` list a contract
+/// named `contract_name`. Artifacts of the current profile generated by both `scarb build` and
+/// `scarb build --test` are searched. Fails if no member or more than one member lists the contract.
+pub fn package_for_contract(
+    metadata: &Metadata,
+    target_dir: &Utf8Path,
+    contract_name: &str,
+) -> Result<PackageId> {
+    let profile_subdir = profile_subdir(metadata);
+    let current_profile = profile_subdir.as_str();
+    let mut packages = vec![];
+
+    for package in &metadata.workspace.members {
+        let Ok(target_name) = target_name_for_package(metadata, package) else {
+            continue;
+        };
+
+        let artifacts_data =
+            starknet_artifacts_data(target_dir, &target_name, current_profile, None)
+                .into_iter()
+                .chain(get_starknet_artifacts_paths_from_test_targets(
+                    target_dir,
+                    &target_name,
+                    current_profile,
+                    None,
+                ));
+
+        for artifact_data in artifacts_data {
+            let artifacts = artifacts_for_package(&artifact_data.path)?;
+
+            if artifacts
+                .contracts
+                .iter()
+                .any(|contract| contract.contract_name == contract_name)
+            {
+                packages.push(package.clone());
+                break;
+            }
+        }
+    }
+
+    match packages.as_slice() {
+        [] => bail!("No package in the workspace has a contract named = {contract_name}"),
+        [package] => Ok(package.clone()),
+        _ => {
+            let names = packages
+                .iter()
+                .map(|package| name_for_package(metadata, package))
+                .collect::<Result<Vec<_>>>()?;
+            bail!(
+                "Contract name = {contract_name} is ambiguous, it is defined in packages = [{}]",
+                names.join(", ")
+            )
+        }
+    }
+}
+
+/// Get a root directory of the given package, containing its manifest and sources
+pub fn source_root_for_package(metadata: &Metadata, package: &PackageId) -> Result<Utf8PathBuf> {
+    let package = metadata
+        .get_package(package)
+        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))?;
+
+    Ok(package.root.clone())
+}
+
+/// Check that sierra code generation is not disabled for `[[target.starknet-contract]]` of the given package.
+/// Scarb generates sierra by default, so only an explicit `sierra = false` is reported.
+pub fn verify_sierra_enabled(metadata: &Metadata, package: &PackageId) -> Result<()> {
+    let package = metadata
+        .get_package(package)
+        .ok_or_else(|| anyhow!("Failed to find metadata for package = {package}"))?;
+
+    for target in package
+        .targets
+        .iter()
+        .filter(|target| target.kind == "starknet-contract")
+    {
+        if target
+            .params
+            .get("sierra")
+            .and_then(serde_json::Value::as_bool)
+            == Some(false)
+        {
+            bail!(
+                "Sierra code generation is disabled for target = {} of package = {}. \
+                Set `sierra = true` in the [[target.starknet-contract]] section of Scarb.toml",
+                target.name,
+                package.name
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks if the specified package has version compatible with the specified requirement
+pub fn package_matches_version_requirement(
+    metadata: &Metadata,
+    name: &str,
+    version_req: &VersionReq,
+) -> Result<bool> {
+    Ok(version_req.matches(package_version(metadata, name)?))
+}
+
+/// Resolved version of the specified package, fails if the package is not a dependency
+/// or is present more than once
+pub fn package_version<'a>(metadata: &'a Metadata, name: &str) -> Result<&'a Version> {
+    let mut packages = metadata
+        .packages
+        .iter()
+        .filter(|package| package.name == name);
+
+    match (packages.next(), packages.next()) {
+        (Some(package), None) => Ok(&package.version),
+        (None, None) => Err(anyhow!("Package {name} is not present in dependencies.")),
+        _ => Err(anyhow!("Package {name} is duplicated in dependencies")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::MetadataCommandExt;
     use assert_fs::fixture::{FileWriteStr, PathChild, PathCopy};
     use assert_fs::prelude::FileTouch;
     use assert_fs::TempDir;
     use camino::Utf8PathBuf;
     use indoc::{formatdoc, indoc};
+    use std::collections::HashSet;
     use std::str::FromStr;
+    use universal_sierra_compiler_api::GasMetering;
 
     fn setup_package(package_name: &str) -> TempDir {
         let temp = TempDir::new().unwrap();
@@ -277,17 +1786,41 @@ mod tests {
         temp
     }
 
-    #[test]
-    fn get_starknet_artifacts_path_for_standard_build() {
-        let temp = setup_package("basic_package");
+    /// `setup_package` followed by `scarb build` called with `build_args`
+    fn setup_built_package(package_name: &str, build_args: &[&str]) -> TempDir {
+        let temp = setup_package(package_name);
 
         ScarbCommand::new_with_stdio()
             .current_dir(temp.path())
             .arg("build")
+            .args(build_args)
             .run()
             .unwrap();
 
-        let path = get_starknet_artifacts_path(
+        temp
+    }
+
+    /// Same as `setup_built_package`, but also returns metadata of the built package
+    fn setup_built_package_with_metadata(
+        package_name: &str,
+        build_args: &[&str],
+    ) -> (TempDir, Metadata) {
+        let temp = setup_built_package(package_name, build_args);
+
+        let metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .manifest_path(temp.join("Scarb.toml"))
+            .run()
+            .unwrap();
+
+        (temp, metadata)
+    }
+
+    #[test]
+    fn get_starknet_artifacts_path_for_standard_build() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let path = get_starknet_artifacts_path(
             &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
             "basic_package",
             "dev",
@@ -296,23 +1829,17 @@ mod tests {
         .unwrap();
 
         assert_eq!(
-            path,
+            path.path,
             temp.path()
                 .join("target/dev/basic_package.starknet_artifacts.json")
         );
+        assert_eq!(path.test_type, None);
     }
 
     #[test]
     #[cfg_attr(not(feature = "scarb_2_8_3"), ignore)]
     fn get_starknet_artifacts_path_for_test_build() {
-        let temp = setup_package("basic_package");
-
-        ScarbCommand::new_with_stdio()
-            .current_dir(temp.path())
-            .arg("build")
-            .arg("--test")
-            .run()
-            .unwrap();
+        let temp = setup_built_package("basic_package", &["--test"]);
 
         let path = get_starknet_artifacts_path(
             &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
@@ -323,10 +1850,11 @@ mod tests {
         .unwrap();
 
         assert_eq!(
-            path,
+            path.path,
             temp.path()
                 .join("target/dev/basic_package_unittest.test.starknet_artifacts.json")
         );
+        assert_eq!(path.test_type, Some(TestType::Unit));
     }
 
     #[test]
@@ -354,257 +1882,1633 @@ mod tests {
             .run()
             .unwrap();
 
-        let path = get_starknet_artifacts_path(
-            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
-            "basic_package",
-            "dev",
-            true,
+        let path = get_starknet_artifacts_path(
+            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
+            "basic_package",
+            "dev",
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(
+            path.path,
+            temp.path()
+                .join("target/dev/basic_package_integrationtest.test.starknet_artifacts.json")
+        );
+        assert_eq!(path.test_type, Some(TestType::Integration));
+
+        let target_dir = Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap();
+        let unit_paths = get_starknet_artifacts_paths_from_test_targets(
+            &target_dir,
+            "basic_package",
+            "dev",
+            Some(TestType::Unit),
+        );
+        assert_eq!(unit_paths.len(), 1);
+        assert_eq!(unit_paths[0].test_type, Some(TestType::Unit));
+
+        let all_paths = get_starknet_artifacts_paths_from_test_targets(
+            &target_dir,
+            "basic_package",
+            "dev",
+            None,
+        );
+        assert_eq!(all_paths.len(), 2);
+    }
+
+    #[test]
+    fn package_matches_version_requirement_test() {
+        let temp = setup_package("basic_package");
+
+        let manifest_path = temp.child("Scarb.toml");
+        manifest_path
+            .write_str(&formatdoc!(
+                r#"
+                [package]
+                name = "version_checker"
+                version = "0.1.0"
+
+                [[target.starknet-contract]]
+                sierra = true
+
+                [dependencies]
+                starknet = "2.5.4"
+                "#,
+            ))
+            .unwrap();
+
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
+
+        assert!(package_matches_version_requirement(
+            &scarb_metadata,
+            "starknet",
+            &VersionReq::parse("2.5").unwrap(),
+        )
+        .unwrap());
+
+        assert!(package_matches_version_requirement(
+            &scarb_metadata,
+            "not_existing",
+            &VersionReq::parse("2.5").unwrap(),
+        )
+        .is_err());
+
+        assert!(!package_matches_version_requirement(
+            &scarb_metadata,
+            "starknet",
+            &VersionReq::parse("2.8").unwrap(),
+        )
+        .unwrap());
+
+        assert_eq!(
+            package_version(&scarb_metadata, "starknet").unwrap(),
+            &Version::new(2, 5, 4)
+        );
+    }
+
+    #[test]
+    fn get_starknet_artifacts_path_for_project_with_different_package_and_target_name() {
+        let temp = setup_package("basic_package");
+
+        let snforge_std_path = Utf8PathBuf::from_str("../../snforge_std")
+            .unwrap()
+            .canonicalize_utf8()
+            .unwrap()
+            .to_string()
+            .replace('\\', "/");
+
+        let scarb_path = temp.child("Scarb.toml");
+        scarb_path
+            .write_str(&formatdoc!(
+                r#"
+                [package]
+                name = "basic_package"
+                version = "0.1.0"
+
+                [dependencies]
+                starknet = "2.4.0"
+                snforge_std = {{ path = "{}" }}
+
+                [[target.starknet-contract]]
+                name = "essa"
+                sierra = true
+                "#,
+                snforge_std_path
+            ))
+            .unwrap();
+
+        ScarbCommand::new_with_stdio()
+            .current_dir(temp.path())
+            .arg("build")
+            .run()
+            .unwrap();
+
+        let path = get_starknet_artifacts_path(
+            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
+            "essa",
+            "dev",
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            path.path,
+            temp.path().join("target/dev/essa.starknet_artifacts.json")
+        );
+    }
+
+    #[test]
+    fn get_starknet_artifacts_path_for_project_without_starknet_target() {
+        let temp = setup_package("empty_lib");
+
+        let manifest_path = temp.child("Scarb.toml");
+        manifest_path
+            .write_str(indoc!(
+                r#"
+            [package]
+            name = "empty_lib"
+            version = "0.1.0"
+            "#,
+            ))
+            .unwrap();
+
+        ScarbCommand::new_with_stdio()
+            .current_dir(temp.path())
+            .arg("build")
+            .run()
+            .unwrap();
+
+        let path = get_starknet_artifacts_path(
+            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
+            "empty_lib",
+            "dev",
+            false,
+        );
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn get_starknet_artifacts_path_for_project_without_scarb_build() {
+        let temp = setup_package("basic_package");
+
+        let path = get_starknet_artifacts_path(
+            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
+            "basic_package",
+            "dev",
+            false,
+        );
+        assert!(path.is_none());
+    }
+
+    #[test]
+    fn parsing_starknet_artifacts() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = temp
+            .path()
+            .join("target/dev/basic_package.starknet_artifacts.json");
+        let artifacts_path = Utf8PathBuf::from_path_buf(artifacts_path).unwrap();
+
+        let artifacts = artifacts_for_package(&artifacts_path).unwrap();
+
+        assert!(!artifacts.contracts.is_empty());
+    }
+
+    #[test]
+    fn parsing_starknet_artifacts_on_invalid_file() {
+        let temp = TempDir::new().unwrap();
+        temp.copy_from("../../", &[".tool-versions"]).unwrap();
+        let path = temp.child("wrong.json");
+        path.touch().unwrap();
+        path.write_str("\"aa\": {}").unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+
+        let result = artifacts_for_package(&artifacts_path);
+        let err = result.unwrap_err();
+
+        assert!(err.to_string().contains(&format!("Failed to parse {artifacts_path:?} contents. Make sure you have enabled sierra code generation in Scarb.toml")));
+    }
+
+    #[test]
+    fn parsing_starknet_artifacts_with_duplicated_contract_name() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("duplicated.starknet_artifacts.json");
+        path.write_str(indoc!(
+            r#"
+            {
+                "version": 1,
+                "contracts": [
+                    {
+                        "id": "first",
+                        "package_name": "package",
+                        "contract_name": "ERC20",
+                        "artifacts": { "sierra": "package_ERC20.contract_class.json" }
+                    },
+                    {
+                        "id": "second",
+                        "package_name": "package",
+                        "contract_name": "ERC20",
+                        "artifacts": { "sierra": "other_ERC20.contract_class.json" }
+                    }
+                ]
+            }
+            "#
+        ))
+        .unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+
+        let err = artifacts_for_package(&artifacts_path).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Contract name = ERC20 is listed more than once in {artifacts_path}, ids = [first, second]"
+            )
+        );
+    }
+
+    #[test]
+    fn parsing_starknet_artifacts_with_unsupported_version() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("future.starknet_artifacts.json");
+        path.write_str(indoc!(
+            r#"
+            {
+                "version": 99,
+                "contracts": [
+                    {
+                        "id": "first",
+                        "package_name": "package",
+                        "contract_name": "ERC20",
+                        "module_path": "package::ERC20",
+                        "new_field": {},
+                        "artifacts": { "sierra": "package_ERC20.contract_class.json" }
+                    }
+                ]
+            }
+            "#
+        ))
+        .unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+
+        let err = artifacts_for_package(&artifacts_path).unwrap_err();
+        match err.downcast_ref::<ArtifactsError>() {
+            Some(ArtifactsError::UnsupportedVersion {
+                version,
+                supported_versions,
+                ..
+            }) => {
+                assert_eq!(*version, 99);
+                assert!(!supported_versions.contains(&99));
+            }
+            other => panic!("Unexpected error = {other:?}"),
+        }
+
+        let artifacts = artifacts_for_package_accepting(&artifacts_path, &[1, 99]).unwrap();
+
+        assert_eq!(artifacts.version, 99);
+        assert_eq!(artifacts.contracts[0].contract_name, "ERC20");
+    }
+
+    #[test]
+    fn validate_sierra_paths_lists_all_missing_files() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("package.starknet_artifacts.json");
+        path.write_str(indoc!(
+            r#"
+            {
+                "version": 1,
+                "contracts": [
+                    {
+                        "id": "first",
+                        "package_name": "package",
+                        "contract_name": "ERC20",
+                        "artifacts": { "sierra": "package_ERC20.contract_class.json" }
+                    },
+                    {
+                        "id": "second",
+                        "package_name": "package",
+                        "contract_name": "HelloStarknet",
+                        "artifacts": { "sierra": "package_HelloStarknet.contract_class.json" }
+                    },
+                    {
+                        "id": "third",
+                        "package_name": "package",
+                        "contract_name": "Counter",
+                        "artifacts": { "sierra": "package_Counter.contract_class.json" }
+                    }
+                ]
+            }
+            "#
+        ))
+        .unwrap();
+        temp.child("package_HelloStarknet.contract_class.json")
+            .touch()
+            .unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+        let base_path = artifacts_path.canonicalize_utf8().unwrap();
+        let base_path = base_path.parent().unwrap();
+
+        match validate_sierra_paths(&artifacts_path).unwrap_err() {
+            ArtifactsError::MissingSierraFiles { sierra_paths, .. } => assert_eq!(
+                sierra_paths,
+                vec![
+                    base_path.join("package_ERC20.contract_class.json"),
+                    base_path.join("package_Counter.contract_class.json"),
+                ]
+            ),
+            other => panic!("Unexpected error = {other:?}"),
+        }
+
+        temp.child("package_ERC20.contract_class.json")
+            .touch()
+            .unwrap();
+        temp.child("package_Counter.contract_class.json")
+            .touch()
+            .unwrap();
+        validate_sierra_paths(&artifacts_path).unwrap();
+    }
+
+    #[test]
+    fn artifacts_errors_keep_source_chain() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.child("wrong.json");
+        path.write_str("\"aa\": {}").unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+
+        let err = artifacts_for_package(&artifacts_path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArtifactsError>(),
+            Some(ArtifactsError::Parse { path, .. }) if *path == artifacts_path
+        ));
+        assert!(err.root_cause().is::<serde_json::Error>());
+
+        let missing_path = artifacts_path.with_file_name("missing.json");
+        let err = artifacts_for_package(&missing_path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArtifactsError>(),
+            Some(ArtifactsError::Read { .. })
+        ));
+        assert!(err.root_cause().is::<std::io::Error>());
+    }
+
+    #[test]
+    fn verify_sierra_class_hash_mismatch() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let sierra =
+            fs::read_to_string(temp.join("target/dev/basic_package_ERC20.contract_class.json"))
+                .unwrap();
+        let class_hash = serde_json::from_str::<SierraClass>(&sierra)
+            .unwrap()
+            .class_hash()
+            .unwrap();
+
+        verify_sierra_class_hash(&sierra, class_hash).unwrap();
+
+        let err = verify_sierra_class_hash(&sierra, Felt::ONE).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArtifactsError>(),
+            Some(ArtifactsError::ClassHashMismatch { class_hash: actual, expected_class_hash })
+                if *actual == class_hash && *expected_class_hash == Felt::ONE
+        ));
+        assert!(verify_sierra_class_hash("{}", class_hash).is_err());
+    }
+
+    #[test]
+    fn get_contracts() {
+        let (temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let contracts =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
+                .unwrap();
+
+        assert!(contracts.contains_key("ERC20"));
+        assert!(contracts.contains_key("HelloStarknet"));
+
+        let sierra_contents_erc20 =
+            fs::read_to_string(temp.join("target/dev/basic_package_ERC20.contract_class.json"))
+                .unwrap();
+
+        let contract = contracts.get("ERC20").unwrap();
+        assert_eq!(&sierra_contents_erc20, contract.0.sierra().unwrap());
+        assert!(!contract.0.casm.is_empty());
+        assert!(contract.0.abi().unwrap().contains("\"type\""));
+
+        let sierra_contents_erc20 = fs::read_to_string(
+            temp.join("target/dev/basic_package_HelloStarknet.contract_class.json"),
+        )
+        .unwrap();
+        let contract = contracts.get("HelloStarknet").unwrap();
+        assert_eq!(&sierra_contents_erc20, contract.0.sierra().unwrap());
+        assert!(!contract.0.casm.is_empty());
+        assert_eq!(contract.0.test_type(), None);
+    }
+
+    #[test]
+    fn get_workspace_contracts_per_package() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let workspace_contracts = get_workspace_contracts_artifacts(&metadata, None, false);
+
+        assert_eq!(workspace_contracts.len(), metadata.workspace.members.len());
+
+        let package = &metadata.workspace.members[0];
+        let contracts = workspace_contracts[package].as_ref().unwrap();
+        assert!(contracts.contains_key("ERC20"));
+        assert!(contracts.contains_key("HelloStarknet"));
+    }
+
+    #[test]
+    fn resolve_artifacts_serializes_paths() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let resolved = resolve_artifacts(&metadata, &package.id, None, false).unwrap();
+
+        let target_dir = target_dir_for_workspace(&metadata).join("dev");
+        assert_eq!(
+            resolved.manifest,
+            Some(target_dir.join("basic_package.starknet_artifacts.json"))
+        );
+        let erc20 = resolved
+            .contracts
+            .iter()
+            .find(|contract| contract.name == "ERC20")
+            .unwrap();
+        assert_eq!(
+            erc20.sierra_path,
+            target_dir.join("basic_package_ERC20.contract_class.json")
+        );
+
+        let json = serde_json::to_value(&resolved).unwrap();
+        assert_eq!(json["contracts"][0]["name"], resolved.contracts[0].name);
+        assert!(json["manifest"].is_string());
+    }
+
+    #[test]
+    fn get_contracts_from_explicit_target_dir() {
+        let (temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let custom_target_dir =
+            Utf8PathBuf::from_path_buf(temp.join("downloaded_artifacts")).unwrap();
+        fs::rename(target_dir_for_workspace(&metadata), &custom_target_dir).unwrap();
+
+        let package = metadata.packages.first().unwrap();
+        let contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().target_dir(&custom_target_dir),
+        )
+        .unwrap();
+        assert!(contracts.contains_key("ERC20"));
+        assert!(contracts["ERC20"].1.starts_with(&custom_target_dir));
+
+        assert!(get_contracts_artifacts_and_source_sierra_paths(
+            &metadata,
+            &package.id,
+            None,
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn get_contracts_sorted_by_name() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let contracts =
+            get_contracts_artifacts_sorted(&metadata, &package.id, None, false).unwrap();
+        let names: Vec<_> = contracts.iter().map(|(name, _)| name.as_str()).collect();
+
+        let mut expected: Vec<_> =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
+                .unwrap()
+                .into_keys()
+                .collect();
+        expected.sort();
+
+        assert_eq!(names, expected);
+        assert!(names.contains(&"ERC20"));
+        assert!(names.contains(&"HelloStarknet"));
+    }
+
+    #[test]
+    fn get_contracts_from_test_targets_keep_test_type() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &["--test"]);
+
+        let package = metadata.packages.first().unwrap();
+        let contracts =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, true)
+                .unwrap();
+
+        let contract = contracts.get("ERC20").unwrap();
+        assert_eq!(contract.0.test_type(), Some(TestType::Unit));
+    }
+
+    #[test]
+    fn get_contract_names_per_test_type() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &["--test"]);
+
+        let package = metadata.packages.first().unwrap();
+        let contract_names = get_contract_names_by_test_type(&metadata, &package.id, None).unwrap();
+
+        assert_eq!(
+            contract_names,
+            HashMap::from([(
+                TestType::Unit,
+                vec!["ERC20".to_string(), "HelloStarknet".to_string()]
+            )])
+        );
+    }
+
+    #[test]
+    fn get_contracts_build_origin() {
+        let temp = setup_built_package("basic_package", &[]);
+        ScarbCommand::new_with_stdio()
+            .current_dir(temp.path())
+            .arg("build")
+            .arg("--test")
+            .run()
+            .unwrap();
+
+        let metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .manifest_path(temp.join("Scarb.toml"))
+            .run()
+            .unwrap();
+
+        let package = metadata.packages.first().unwrap();
+        for (use_test_target_contracts, build_origin) in
+            [(false, BuildOrigin::Standard), (true, BuildOrigin::Test)]
+        {
+            let contracts = get_contracts_artifacts_and_source_sierra_paths(
+                &metadata,
+                &package.id,
+                None,
+                use_test_target_contracts,
+            )
+            .unwrap();
+
+            assert!(!contracts.is_empty());
+            assert!(contracts
+                .values()
+                .all(|(artifacts, _)| artifacts.build_origin() == Some(build_origin)));
+        }
+
+        let artifacts = StarknetContractArtifacts::new("{}", "");
+        assert_eq!(artifacts.build_origin(), None);
+    }
+
+    #[test]
+    fn get_contracts_with_casm_cache() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let casm_cache = CasmCache::in_target_dir(&target_dir_for_workspace(&metadata));
+
+        let contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().casm_cache(&casm_cache),
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_dir(casm_cache.dir()).unwrap().count(),
+            contracts.len()
+        );
+
+        let cached_contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().casm_cache(&casm_cache),
+        )
+        .unwrap();
+        assert_eq!(
+            cached_contracts.get("ERC20").unwrap().0.casm,
+            contracts.get("ERC20").unwrap().0.casm
+        );
+
+        casm_cache.clear().unwrap();
+        assert!(!casm_cache.dir().exists());
+    }
+
+    #[test]
+    fn get_contracts_reports_progress() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let reported = std::cell::RefCell::new(vec![]);
+        let progress = |name: &str, index: usize, total: usize| {
+            reported.borrow_mut().push((name.to_string(), index, total));
+        };
+        let contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().progress(&progress),
+        )
+        .unwrap();
+
+        let reported = reported.into_inner();
+        assert_eq!(reported.len(), contracts.len());
+        assert!(reported
+            .iter()
+            .enumerate()
+            .all(|(i, (name, index, total))| *index == i + 1
+                && *total == contracts.len()
+                && contracts.contains_key(name)));
+    }
+
+    #[test]
+    fn get_contracts_with_timings() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let (contracts, timings) =
+            load_artifacts_with_timings(&metadata, &package.id, &ArtifactLoadOptions::new())
+                .unwrap();
+
+        assert_eq!(contracts.len(), timings.len());
+        assert!(contracts.keys().all(|name| timings.contains_key(name)));
+    }
+
+    #[test]
+    fn load_artifacts_accepting_artifacts_version() {
+        let (temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let artifacts_path = temp.join("target/dev/basic_package.starknet_artifacts.json");
+        let mut artifacts: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&artifacts_path).unwrap()).unwrap();
+        artifacts["version"] = 99.into();
+        fs::write(&artifacts_path, artifacts.to_string()).unwrap();
+
+        let package = metadata.packages.first().unwrap();
+        assert!(load_artifacts(&metadata, &package.id, &ArtifactLoadOptions::new()).is_err());
+
+        let contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().accept_artifacts_version(99),
+        )
+        .unwrap();
+        assert!(contracts.contains_key("ERC20"));
+    }
+
+    #[test]
+    fn load_artifacts_with_options() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+
+        let package = metadata.packages.first().unwrap();
+        let loaded = std::cell::Cell::new(0);
+        let progress = |_: &str, _: usize, _: usize| loaded.set(loaded.get() + 1);
+        let contracts = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new()
+                .profile("dev")
+                .progress(&progress)
+                .minify_sierra(true),
+        )
+        .unwrap();
+
+        let default_contracts =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
+                .unwrap();
+        assert_eq!(loaded.get(), contracts.len());
+        assert_eq!(
+            contracts.keys().collect::<HashSet<_>>(),
+            default_contracts.keys().collect::<HashSet<_>>()
+        );
+        for (name, (artifacts, sierra_path)) in &contracts {
+            let (default_artifacts, default_sierra_path) = &default_contracts[name];
+            assert_eq!(artifacts.casm, default_artifacts.casm);
+            assert_eq!(sierra_path, default_sierra_path);
+        }
+
+        let err = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().require_test_targets(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("No test targets artifacts found for package"));
+    }
+
+    #[test]
+    fn get_contracts_without_build_output() {
+        let temp = setup_package("basic_package");
+
+        let metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .manifest_path(temp.join("Scarb.toml"))
+            .run()
+            .unwrap();
+        let package = metadata.packages.first().unwrap();
+        let _ = fs::remove_dir_all(target_dir_for_workspace(&metadata));
+
+        let err =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
+                .unwrap_err();
+        assert!(err.to_string().starts_with("No build output found"));
+
+        fs::create_dir_all(target_dir_for_workspace(&metadata)).unwrap();
+
+        let contracts =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
+                .unwrap();
+        assert!(contracts.is_empty());
+    }
+
+    #[test]
+    fn get_contracts_strict_without_test_targets() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+        let package = metadata.packages.first().unwrap();
+
+        let contracts =
+            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, true)
+                .unwrap();
+        assert!(contracts.is_empty());
+
+        let err = load_artifacts(
+            &metadata,
+            &package.id,
+            ArtifactLoadOptions::new().require_test_targets(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("did you run `scarb build --test`?"));
+    }
+
+    #[test]
+    fn get_package_for_contract() {
+        let (_temp, metadata) = setup_built_package_with_metadata("basic_package", &[]);
+        let target_dir = target_dir_for_workspace(&metadata);
+
+        let package = package_for_contract(&metadata, &target_dir, "ERC20").unwrap();
+        assert_eq!(package, metadata.workspace.members[0]);
+
+        let err = package_for_contract(&metadata, &target_dir, "NonExistent").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No package in the workspace has a contract named = NonExistent"
+        );
+    }
+
+    #[test]
+    fn test_type_round_trips_scarb_str() {
+        for test_type in [TestType::Unit, TestType::Integration] {
+            assert_eq!(
+                TestType::from_scarb_str(test_type.as_scarb_str()),
+                Some(test_type)
+            );
+        }
+        assert_eq!(TestType::from_scarb_str("unknown"), None);
+    }
+
+    #[test]
+    fn test_type_from_scarb_str_ignores_case_and_whitespace() {
+        assert_eq!(
+            TestType::from_scarb_str("Integration"),
+            Some(TestType::Integration)
+        );
+        assert_eq!(
+            TestType::from_scarb_str(" integration\n"),
+            Some(TestType::Integration)
+        );
+        assert_eq!(TestType::from_scarb_str("UNIT "), Some(TestType::Unit));
+    }
+
+    #[test]
+    fn test_type_from_target() {
+        let target = |name: &str, params: serde_json::Value| -> TargetMetadata {
+            serde_json::from_value(serde_json::json!({
+                "kind": "test",
+                "name": name,
+                "source_path": "src/lib.cairo",
+                "params": params,
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(
+            TestType::from_target(&target(
+                "package_unittest",
+                serde_json::json!({ "test-type": "integration" })
+            )),
+            Some(TestType::Integration)
+        );
+        assert_eq!(
+            TestType::from_target(&target("package_unittest", serde_json::json!({}))),
+            Some(TestType::Unit)
+        );
+        assert_eq!(
+            TestType::from_target(&target("package_integrationtest", serde_json::json!({}))),
+            Some(TestType::Integration)
+        );
+        assert_eq!(
+            TestType::from_target(&target("package", serde_json::json!({}))),
+            None
+        );
+    }
+
+    #[test]
+    fn get_starknet_artifacts_path_from_nested_directory() {
+        let temp = TempDir::new().unwrap();
+        temp.child("target/dev/unrelated.starknet_artifacts.json")
+            .touch()
+            .unwrap();
+        temp.child("target/dev/nested/deeper/package.starknet_artifacts.json")
+            .touch()
+            .unwrap();
+        let target_dir = Utf8PathBuf::from_path_buf(temp.join("target")).unwrap();
+
+        assert!(get_starknet_artifacts_path(&target_dir, "package", "dev", false).is_none());
+        assert!(
+            get_starknet_artifacts_path_recursive(&target_dir, "package", "dev", false, 1)
+                .is_none()
+        );
+
+        let artifacts_data =
+            get_starknet_artifacts_path_recursive(&target_dir, "package", "dev", false, 2).unwrap();
+        assert_eq!(
+            artifacts_data.path,
+            target_dir.join("dev/nested/deeper/package.starknet_artifacts.json")
+        );
+        assert_eq!(artifacts_data.test_type, None);
+    }
+
+    #[test]
+    fn artifacts_file_names() {
+        assert_eq!(
+            artifacts_file_name("basic_package"),
+            "basic_package.starknet_artifacts.json"
+        );
+        assert_eq!(
+            test_artifacts_file_name("basic_package_unittest"),
+            "basic_package_unittest.test.starknet_artifacts.json"
+        );
+    }
+
+    #[test]
+    fn select_base_artifact_prefers_integration() {
+        let artifact_data = |test_type: Option<TestType>| ContractArtifactData {
+            path: Utf8PathBuf::from(format!("{test_type:?}.json")),
+            test_type,
+        };
+
+        let unit = artifact_data(Some(TestType::Unit));
+        let integration = artifact_data(Some(TestType::Integration));
+
+        assert_eq!(
+            select_base_artifact(&[unit.clone(), integration.clone()]),
+            Some(&integration)
+        );
+        assert_eq!(select_base_artifact(&[unit.clone()]), Some(&unit));
+        assert_eq!(select_base_artifact(&[]), None);
+    }
+
+    #[test]
+    fn cloned_artifacts_share_contents() {
+        let artifacts = StarknetContractArtifacts::new(r#"{"abi": []}"#, "casm");
+        let cloned = artifacts.clone();
+
+        assert!(Arc::ptr_eq(&artifacts.casm, &cloned.casm));
+        assert!(std::ptr::eq(
+            artifacts.sierra().unwrap(),
+            cloned.sierra().unwrap()
+        ));
+    }
+
+    #[test]
+    fn abi_from_sierra_extracts_abi() {
+        let sierra = r#"{"sierra_program": [], "abi": [{"type": "function", "name": "foo"}]}"#;
+
+        let abi: serde_json::Value =
+            serde_json::from_str(&abi_from_sierra(sierra).unwrap()).unwrap();
+
+        assert_eq!(
+            abi,
+            serde_json::json!([{"type": "function", "name": "foo"}])
+        );
+    }
+
+    #[test]
+    fn load_artifacts_lenient_reports_broken_contracts() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        fs::remove_file(temp.join("target/dev/basic_package_ERC20.contract_class.json")).unwrap();
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let (contracts, failures) =
+            load_artifacts_lenient(&artifacts_path, &ArtifactLoadOptions::new()).unwrap();
+
+        assert!(contracts.contains_key("HelloStarknet"));
+        assert!(!contracts.contains_key("ERC20"));
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "ERC20");
+    }
+
+    #[test]
+    fn load_artifacts_lenient_uses_casm_cache() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+        let casm_cache =
+            CasmCache::new(Utf8PathBuf::from_path_buf(temp.join("casm_cache")).unwrap());
+
+        let (contracts, failures) = load_artifacts_lenient(
+            &artifacts_path,
+            ArtifactLoadOptions::new().casm_cache(&casm_cache),
+        )
+        .unwrap();
+
+        assert!(failures.is_empty());
+        assert_eq!(
+            fs::read_dir(casm_cache.dir()).unwrap().count(),
+            contracts.len()
+        );
+    }
+
+    #[test]
+    fn loading_invalid_sierra_reports_compilation_failure() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        fs::write(
+            temp.join("target/dev/basic_package_ERC20.contract_class.json"),
+            "{}",
+        )
+        .unwrap();
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let err = load_artifacts_from_manifest(&artifacts_path).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArtifactsError>(),
+            Some(ArtifactsError::CompilationFailed { contract, .. }) if contract == "ERC20"
+        ));
+    }
+
+    #[test]
+    fn loading_contract_with_mismatched_checksum_fails() {
+        let temp = TempDir::new().unwrap();
+        temp.child("contract.json").write_str("{}").unwrap();
+        let base_path = Utf8PathBuf::from_path_buf(temp.to_path_buf()).unwrap();
+
+        let contract = StarknetContract {
+            id: "1".to_string(),
+            package_name: "package".to_string(),
+            contract_name: "Contract".to_string(),
+            artifacts: StarknetContractArtifactPaths {
+                sierra: Utf8PathBuf::from("contract.json"),
+                sierra_checksum: Some("abcd".to_string()),
+            },
+        };
+
+        let err = StarknetContractArtifacts::from_scarb_contract_artifact(
+            &contract,
+            &base_path,
+            &CompilationOptions::default(),
+            None,
+        )
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("does not match the one from starknet_artifacts.json = abcd"));
+    }
+
+    #[test]
+    fn artifacts_with_unreadable_sierra_are_compared_by_path() {
+        let contract = |sierra: &str| StarknetContract {
+            id: "1".to_string(),
+            package_name: "package".to_string(),
+            contract_name: "Contract".to_string(),
+            artifacts: StarknetContractArtifactPaths {
+                sierra: Utf8PathBuf::from(sierra),
+                sierra_checksum: None,
+            },
+        };
+        let base_path = Utf8Path::new("/missing");
+        let artifacts = |sierra: &str| {
+            StarknetContractArtifacts::from_scarb_contract_artifact_without_casm(
+                &contract(sierra),
+                base_path,
+            )
+        };
+
+        assert_eq!(artifacts("a.json"), artifacts("a.json"));
+        assert_ne!(artifacts("a.json"), artifacts("b.json"));
+    }
+
+    #[test]
+    fn merge_contracts_artifacts_with_policies() {
+        let contract = |sierra: &str| {
+            (
+                StarknetContractArtifacts::new(sierra.to_string(), String::new()),
+                Utf8PathBuf::new(),
+            )
+        };
+        let base = HashMap::from([("A".to_string(), contract("base"))]);
+        let other = HashMap::from([
+            ("A".to_string(), contract("other")),
+            ("B".to_string(), contract("other")),
+        ]);
+
+        let mut merged = base.clone();
+        merge_contracts_artifacts(&mut merged, other.clone(), MergePolicy::KeepBase).unwrap();
+        assert_eq!(merged.get("A").unwrap().0.sierra().unwrap(), "base");
+        assert_eq!(merged.get("B").unwrap().0.sierra().unwrap(), "other");
+
+        let mut merged = base.clone();
+        let err = merge_contracts_artifacts(&mut merged, other, MergePolicy::ErrorOnConflict)
+            .unwrap_err();
+        assert!(err.to_string().contains("Contract = A differs"));
+
+        let mut merged = base.clone();
+        merge_contracts_artifacts(&mut merged, base.clone(), MergePolicy::ErrorOnConflict).unwrap();
+
+        let other_casm = HashMap::from([(
+            "A".to_string(),
+            (
+                StarknetContractArtifacts::new("base", "other casm"),
+                Utf8PathBuf::new(),
+            ),
+        )]);
+        let mut merged = base.clone();
+        let err = merge_contracts_artifacts(&mut merged, other_casm, MergePolicy::ErrorOnConflict)
+            .unwrap_err();
+        assert!(err.to_string().contains("Contract = A differs"));
+    }
+
+    #[test]
+    fn merge_contracts_artifacts_preferring_newest() {
+        let temp = TempDir::new().unwrap();
+        let sierra_file = |name: &str| {
+            let path = Utf8PathBuf::from_path_buf(temp.child(name).to_path_buf()).unwrap();
+            fs::write(&path, name).unwrap();
+            path
+        };
+        let older_path = sierra_file("older.contract_class.json");
+        let newer_path = sierra_file("newer.contract_class.json");
+        let older_modified = fs::metadata(&older_path).unwrap().modified().unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&newer_path)
+            .unwrap()
+            .set_modified(older_modified + std::time::Duration::from_secs(10))
+            .unwrap();
+
+        let contract = |sierra: &str, path: &Utf8PathBuf| {
+            (
+                StarknetContractArtifacts::new(sierra.to_string(), String::new()),
+                path.clone(),
+            )
+        };
+
+        let mut merged = HashMap::from([("A".to_string(), contract("older", &older_path))]);
+        let other = HashMap::from([("A".to_string(), contract("newer", &newer_path))]);
+        merge_contracts_artifacts(&mut merged, other, MergePolicy::PreferNewest).unwrap();
+        assert_eq!(merged.get("A").unwrap().0.sierra().unwrap(), "newer");
+        assert_eq!(merged.get("A").unwrap().1, newer_path);
+
+        let mut merged = HashMap::from([("A".to_string(), contract("newer", &newer_path))]);
+        let other = HashMap::from([("A".to_string(), contract("older", &older_path))]);
+        merge_contracts_artifacts(&mut merged, other, MergePolicy::PreferNewest).unwrap();
+        assert_eq!(merged.get("A").unwrap().0.sierra().unwrap(), "newer");
+
+        let mut merged = HashMap::from([("A".to_string(), contract("older", &older_path))]);
+        let missing_path =
+            Utf8PathBuf::from_path_buf(temp.child("missing.json").to_path_buf()).unwrap();
+        let other = HashMap::from([("A".to_string(), contract("missing", &missing_path))]);
+        let err =
+            merge_contracts_artifacts(&mut merged, other, MergePolicy::PreferNewest).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Failed to read modification time of"));
+    }
+
+    #[test]
+    fn load_artifacts_directly_from_manifest() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let contracts = load_artifacts_from_manifest(&artifacts_path).unwrap();
+
+        assert!(contracts.contains_key("ERC20"));
+        assert!(contracts.contains_key("HelloStarknet"));
+        assert_eq!(
+            contracts.get("ERC20").unwrap().1,
+            temp.join("target/dev/basic_package_ERC20.contract_class.json")
+        );
+    }
+
+    #[test]
+    fn count_contracts_in_manifest() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        assert_eq!(count_contracts(&artifacts_path).unwrap(), 2);
+        assert!(count_contracts(&artifacts_path.with_file_name("missing.json")).is_err());
+    }
+
+    #[test]
+    fn load_artifacts_from_manifest_passes_compilation_options() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let default_options = CompilationOptions::default();
+        let contracts =
+            load_artifacts_from_manifest_with_options(&artifacts_path, &default_options).unwrap();
+        assert!(contracts.contains_key("ERC20"));
+    }
+
+    #[test]
+    fn compilation_options_map_to_usc_flags() {
+        assert!(CompilationOptions::default().usc_args().is_empty());
+        assert_eq!(
+            CompilationOptions {
+                add_pythonic_hints: true,
+                gas_metering: GasMetering::Disabled,
+            }
+            .usc_args(),
+            vec!["--add-pythonic-hints", "--no-gas-metering"]
+        );
+    }
+
+    #[test]
+    fn load_artifacts_from_manifest_without_casm_skips_compilation() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let contracts = load_artifacts_from_manifest_without_casm(&artifacts_path).unwrap();
+        let (contract, sierra_path) = contracts.get("ERC20").unwrap();
+
+        assert!(!contract.has_casm());
+        assert_eq!(
+            contract.sierra().unwrap(),
+            fs::read_to_string(sierra_path).unwrap()
+        );
+        assert!(contract.abi().unwrap().contains("\"type\""));
+
+        let (contract, _) = load_artifacts_from_manifest(&artifacts_path)
+            .unwrap()
+            .remove("ERC20")
+            .unwrap();
+        assert!(contract.has_casm());
+    }
+
+    #[test]
+    fn load_artifacts_from_manifest_with_minified_sierra() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let load = |minify_sierra| {
+            load_artifacts_from_manifest_with_sierra_minification(
+                &artifacts_path,
+                &CompilationOptions::default(),
+                minify_sierra,
+            )
+            .unwrap()
+            .remove("ERC20")
+            .unwrap()
+        };
+        let (contract, sierra_path) = load(false);
+        let (minified_contract, _) = load(true);
+
+        assert_eq!(
+            contract.sierra().unwrap(),
+            fs::read_to_string(sierra_path).unwrap()
+        );
+        assert!(minified_contract.sierra().unwrap().len() < contract.sierra().unwrap().len());
+        assert!(!minified_contract.sierra().unwrap().contains('\n'));
+        assert_eq!(minified_contract.casm, contract.casm);
+        assert_eq!(
+            minified_contract
+                .parse_sierra()
+                .unwrap()
+                .class_hash()
+                .unwrap(),
+            contract.parse_sierra().unwrap().class_hash().unwrap()
+        );
+    }
+
+    #[test]
+    fn iter_contract_artifacts_matches_map() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let mut contracts = iter_contract_artifacts(&artifacts_path).unwrap();
+        let (name, _) = contracts.next().unwrap().unwrap();
+        assert!(!name.is_empty());
+
+        let contracts: HashMap<_, _> = iter_contract_artifacts(&artifacts_path)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            contracts,
+            load_artifacts_from_manifest(&artifacts_path).unwrap()
+        );
+
+        assert!(iter_contract_artifacts(&artifacts_path.with_file_name("missing.json")).is_err());
+    }
+
+    #[tokio::test]
+    async fn load_artifacts_from_manifest_async_matches_sync() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let contracts =
+            load_artifacts_from_manifest_async(&artifacts_path, &CompilationOptions::default())
+                .await
+                .unwrap();
+
+        assert_eq!(
+            contracts,
+            load_artifacts_from_manifest(&artifacts_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_artifacts_with_max_parallelism_matches_sync() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let contracts = load_artifacts_from_manifest_with_max_parallelism(
+            &artifacts_path,
+            &ArtifactLoadOptions::new(),
+            Some(NonZeroUsize::MIN),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            contracts,
+            load_artifacts_from_manifest(&artifacts_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_artifacts_with_max_parallelism_uses_casm_cache() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+        let casm_cache =
+            CasmCache::new(Utf8PathBuf::from_path_buf(temp.join("casm_cache")).unwrap());
+
+        let contracts = load_artifacts_from_manifest_with_max_parallelism(
+            &artifacts_path,
+            ArtifactLoadOptions::new().casm_cache(&casm_cache),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert!(contracts.contains_key("ERC20"));
+        assert_eq!(
+            fs::read_dir(casm_cache.dir()).unwrap().count(),
+            contracts.len()
+        );
+    }
+
+    #[tokio::test]
+    async fn load_single_contract_async() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+
+        let artifacts = StarknetContractArtifacts::from_scarb_contract_artifact_async(
+            &artifacts_path,
+            "ERC20",
+            &ArtifactLoadOptions::new(),
+        )
+        .await
+        .unwrap();
+
+        let (expected, _) = load_artifacts_from_manifest(&artifacts_path)
+            .unwrap()
+            .remove("ERC20")
+            .unwrap();
+        assert_eq!(artifacts, expected);
+
+        let err = StarknetContractArtifacts::from_scarb_contract_artifact_async(
+            &artifacts_path,
+            "NonExistent",
+            &ArtifactLoadOptions::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Contract = NonExistent not found in "));
+    }
+
+    #[test]
+    fn get_sierra_path_for_contract() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
         )
         .unwrap();
 
+        let sierra_path = sierra_path_for_contract(&artifacts_path, "ERC20").unwrap();
+
+        assert!(sierra_path.is_absolute());
         assert_eq!(
-            path,
-            temp.path()
-                .join("target/dev/basic_package_integrationtest.test.starknet_artifacts.json")
+            sierra_path.file_name(),
+            Some("basic_package_ERC20.contract_class.json")
         );
+        assert!(sierra_path.is_file());
+
+        let err = sierra_path_for_contract(&artifacts_path, "NonExistent").unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Contract = NonExistent not found in "));
     }
 
     #[test]
-    fn package_matches_version_requirement_test() {
-        let temp = setup_package("basic_package");
+    fn parse_sierra_into_contract_class() {
+        let temp = setup_built_package("basic_package", &[]);
 
-        let manifest_path = temp.child("Scarb.toml");
-        manifest_path
-            .write_str(&formatdoc!(
-                r#"
-                [package]
-                name = "version_checker"
-                version = "0.1.0"
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
+        )
+        .unwrap();
+        let contracts = load_artifacts_from_manifest(&artifacts_path).unwrap();
+        let (artifacts, _) = contracts.get("ERC20").unwrap();
 
-                [[target.starknet-contract]]
-                sierra = true
+        let sierra_class = artifacts.parse_sierra().unwrap();
 
-                [dependencies]
-                starknet = "2.5.4"
-                "#,
-            ))
-            .unwrap();
+        assert!(!sierra_class.entry_points_by_type.external.is_empty());
+        assert!(std::ptr::eq(
+            sierra_class,
+            artifacts.parse_sierra().unwrap()
+        ));
 
-        let scarb_metadata = ScarbCommand::metadata()
-            .inherit_stderr()
-            .current_dir(temp.path())
-            .run()
-            .unwrap();
+        let invalid = StarknetContractArtifacts::new("not a contract class", "");
+        assert!(invalid.parse_sierra().is_err());
+    }
 
-        assert!(package_matches_version_requirement(
-            &scarb_metadata,
-            "starknet",
-            &VersionReq::parse("2.5").unwrap(),
-        )
-        .unwrap());
+    #[test]
+    fn artifacts_from_sierra_in_memory() {
+        let temp = setup_built_package("basic_package", &[]);
 
-        assert!(package_matches_version_requirement(
-            &scarb_metadata,
-            "not_existing",
-            &VersionReq::parse("2.5").unwrap(),
+        let sierra =
+            fs::read_to_string(temp.join("target/dev/basic_package_ERC20.contract_class.json"))
+                .unwrap();
+        let artifacts_path = Utf8PathBuf::from_path_buf(
+            temp.join("target/dev/basic_package.starknet_artifacts.json"),
         )
-        .is_err());
+        .unwrap();
+        let contracts = load_artifacts_from_manifest(&artifacts_path).unwrap();
 
-        assert!(!package_matches_version_requirement(
-            &scarb_metadata,
-            "starknet",
-            &VersionReq::parse("2.8").unwrap(),
-        )
-        .unwrap());
+        let artifacts = StarknetContractArtifacts::from_sierra(sierra).unwrap();
+        let loaded_artifacts = &contracts.get("ERC20").unwrap().0;
+
+        assert_eq!(artifacts.casm, loaded_artifacts.casm);
+        assert_eq!(
+            artifacts.sierra().unwrap(),
+            loaded_artifacts.sierra().unwrap()
+        );
+        // Artifacts loaded from a file are not equal to ones held in memory only
+        assert_ne!(&artifacts, loaded_artifacts);
     }
 
     #[test]
-    fn get_starknet_artifacts_path_for_project_with_different_package_and_target_name() {
-        let temp = setup_package("basic_package");
-
-        let snforge_std_path = Utf8PathBuf::from_str("../../snforge_std")
-            .unwrap()
-            .canonicalize_utf8()
-            .unwrap()
-            .to_string()
-            .replace('\\', "/");
-
-        let scarb_path = temp.child("Scarb.toml");
-        scarb_path
-            .write_str(&formatdoc!(
-                r#"
-                [package]
-                name = "basic_package"
-                version = "0.1.0"
+    fn sibling_casm_present_absent_and_stale() {
+        let temp = TempDir::new().unwrap();
+        let sierra_path = Utf8PathBuf::from_path_buf(
+            temp.child("package_Contract.contract_class.json")
+                .to_path_buf(),
+        )
+        .unwrap();
+        let casm_path = sierra_path.with_file_name("package_Contract.compiled_contract_class.json");
+        fs::write(&sierra_path, "sierra").unwrap();
 
-                [dependencies]
-                starknet = "2.4.0"
-                snforge_std = {{ path = "{}" }}
+        assert_eq!(fresh_sibling_casm(&sierra_path), None);
 
-                [[target.starknet-contract]]
-                name = "essa"
-                sierra = true
-                "#,
-                snforge_std_path
-            ))
-            .unwrap();
+        fs::write(&casm_path, "casm").unwrap();
+        assert_eq!(fresh_sibling_casm(&sierra_path), Some("casm".to_string()));
 
-        ScarbCommand::new_with_stdio()
-            .current_dir(temp.path())
-            .arg("build")
-            .run()
+        let sierra_file = fs::File::options().write(true).open(&sierra_path).unwrap();
+        let casm_modified = fs::metadata(&casm_path).unwrap().modified().unwrap();
+        sierra_file
+            .set_modified(casm_modified + std::time::Duration::from_secs(10))
             .unwrap();
-
-        let path = get_starknet_artifacts_path(
-            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
-            "essa",
-            "dev",
-            false,
-        )
-        .unwrap();
+        assert_eq!(fresh_sibling_casm(&sierra_path), None);
 
         assert_eq!(
-            path,
-            temp.path().join("target/dev/essa.starknet_artifacts.json")
+            sibling_casm_path(Utf8Path::new("package_Contract.sierra.json")),
+            None
         );
     }
 
     #[test]
-    fn get_starknet_artifacts_path_for_project_without_starknet_target() {
-        let temp = setup_package("empty_lib");
+    fn get_profile_subdir() {
+        let temp = setup_package("basic_package");
 
-        let manifest_path = temp.child("Scarb.toml");
-        manifest_path
-            .write_str(indoc!(
-                r#"
-            [package]
-            name = "empty_lib"
-            version = "0.1.0"
-            "#,
-            ))
+        let metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
             .unwrap();
+        assert_eq!(profile_subdir(&metadata), Utf8PathBuf::from("dev"));
 
-        ScarbCommand::new_with_stdio()
+        let metadata = ScarbCommand::metadata()
+            .inherit_stderr()
             .current_dir(temp.path())
-            .arg("build")
+            .env("SCARB_PROFILE", "release")
             .run()
             .unwrap();
-
-        let path = get_starknet_artifacts_path(
-            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
-            "empty_lib",
-            "dev",
-            false,
+        assert_eq!(profile_subdir(&metadata), Utf8PathBuf::from("release"));
+        assert_eq!(
+            profile_target_dir(&metadata),
+            target_dir_for_workspace(&metadata).join("release")
         );
-        assert!(path.is_none());
     }
 
     #[test]
-    fn get_starknet_artifacts_path_for_project_without_scarb_build() {
+    fn get_name_for_package() {
         let temp = setup_package("basic_package");
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
 
-        let path = get_starknet_artifacts_path(
-            &Utf8PathBuf::from_path_buf(temp.to_path_buf().join("target")).unwrap(),
-            "basic_package",
-            "dev",
-            false,
-        );
-        assert!(path.is_none());
+        let package_name =
+            name_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
+
+        assert_eq!(&package_name, "basic_package");
     }
 
     #[test]
-    fn parsing_starknet_artifacts() {
+    fn get_package_info() {
         let temp = setup_package("basic_package");
-
-        ScarbCommand::new_with_stdio()
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
             .current_dir(temp.path())
-            .arg("build")
             .run()
             .unwrap();
 
-        let artifacts_path = temp
-            .path()
-            .join("target/dev/basic_package.starknet_artifacts.json");
-        let artifacts_path = Utf8PathBuf::from_path_buf(artifacts_path).unwrap();
-
-        let artifacts = artifacts_for_package(&artifacts_path).unwrap();
+        let package_info =
+            package_info(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
 
-        assert!(!artifacts.contracts.is_empty());
+        assert_eq!(package_info.name, "basic_package");
+        assert_eq!(package_info.version, Version::new(0, 1, 0));
     }
 
     #[test]
-    fn parsing_starknet_artifacts_on_invalid_file() {
-        let temp = TempDir::new().unwrap();
-        temp.copy_from("../../", &[".tool-versions"]).unwrap();
-        let path = temp.child("wrong.json");
-        path.touch().unwrap();
-        path.write_str("\"aa\": {}").unwrap();
-        let artifacts_path = Utf8PathBuf::from_path_buf(path.to_path_buf()).unwrap();
+    fn get_source_root_for_package() {
+        let temp = setup_package("basic_package");
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
 
-        let result = artifacts_for_package(&artifacts_path);
-        let err = result.unwrap_err();
+        let source_root =
+            source_root_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
 
-        assert!(err.to_string().contains(&format!("Failed to parse {artifacts_path:?} contents. Make sure you have enabled sierra code generation in Scarb.toml")));
+        assert!(source_root.join("Scarb.toml").is_file());
+        assert!(source_root.join("src").is_dir());
     }
 
     #[test]
-    fn get_contracts() {
+    fn verify_sierra_enabled_for_package() {
         let temp = setup_package("basic_package");
-
-        ScarbCommand::new_with_stdio()
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
             .current_dir(temp.path())
-            .arg("build")
             .run()
             .unwrap();
 
-        let metadata = ScarbCommand::metadata()
+        assert!(
+            verify_sierra_enabled(&scarb_metadata, &scarb_metadata.workspace.members[0]).is_ok()
+        );
+    }
+
+    #[test]
+    fn verify_sierra_disabled_for_package() {
+        let temp = setup_package("basic_package");
+        let manifest = fs::read_to_string(temp.join("Scarb.toml")).unwrap();
+        temp.child("Scarb.toml")
+            .write_str(&manifest.replace(
+                "[[target.starknet-contract]]",
+                "[[target.starknet-contract]]\nsierra = false",
+            ))
+            .unwrap();
+        let scarb_metadata = ScarbCommand::metadata()
             .inherit_stderr()
-            .manifest_path(temp.join("Scarb.toml"))
+            .current_dir(temp.path())
             .run()
             .unwrap();
 
-        let package = metadata.packages.first().unwrap();
-        let contracts =
-            get_contracts_artifacts_and_source_sierra_paths(&metadata, &package.id, None, false)
-                .unwrap();
+        let err = verify_sierra_enabled(&scarb_metadata, &scarb_metadata.workspace.members[0])
+            .unwrap_err();
 
-        assert!(contracts.contains_key("ERC20"));
-        assert!(contracts.contains_key("HelloStarknet"));
+        assert!(err
+            .to_string()
+            .contains("Sierra code generation is disabled for target = basic_package"));
+    }
 
-        let sierra_contents_erc20 =
-            fs::read_to_string(temp.join("target/dev/basic_package_ERC20.contract_class.json"))
-                .unwrap();
+    #[test]
+    fn get_target_name_for_package() {
+        let temp = setup_package("basic_package");
+        let scarb_metadata = ScarbCommand::metadata()
+            .inherit_stderr()
+            .current_dir(temp.path())
+            .run()
+            .unwrap();
 
-        let contract = contracts.get("ERC20").unwrap();
-        assert_eq!(&sierra_contents_erc20, &contract.0.sierra);
-        assert!(!contract.0.casm.is_empty());
+        let target_name =
+            target_name_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
 
-        let sierra_contents_erc20 = fs::read_to_string(
-            temp.join("target/dev/basic_package_HelloStarknet.contract_class.json"),
-        )
-        .unwrap();
-        let contract = contracts.get("HelloStarknet").unwrap();
-        assert_eq!(&sierra_contents_erc20, &contract.0.sierra);
-        assert!(!contract.0.casm.is_empty());
+        assert_eq!(target_name, "basic_package");
     }
 
     #[test]
-    fn get_name_for_package() {
+    fn get_target_name_for_package_with_kind() {
         let temp = setup_package("basic_package");
         let scarb_metadata = ScarbCommand::metadata()
             .inherit_stderr()
             .current_dir(temp.path())
             .run()
             .unwrap();
+        let package = &scarb_metadata.workspace.members[0];
 
-        let package_name =
-            name_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
+        let target_name =
+            target_name_for_package_with_kind(&scarb_metadata, package, "starknet-contract")
+                .unwrap();
+        assert_eq!(target_name, "basic_package");
 
-        assert_eq!(&package_name, "basic_package");
+        let err = target_name_for_package_with_kind(&scarb_metadata, package, "non-existent")
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Failed to find target of kind = non-existent"));
     }
 
     #[test]
-    fn get_target_name_for_package() {
+    fn get_compilation_units_for_package() {
         let temp = setup_package("basic_package");
         let scarb_metadata = ScarbCommand::metadata()
             .inherit_stderr()
@@ -612,9 +3516,81 @@ mod tests {
             .run()
             .unwrap();
 
-        let target_name =
-            target_name_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]).unwrap();
+        let units =
+            compilation_units_for_package(&scarb_metadata, &scarb_metadata.workspace.members[0]);
 
-        assert_eq!(target_name, "basic_package");
+        assert!(!units.is_empty());
+        assert_eq!(units[0].target.kind, "starknet-contract");
+        assert!(units
+            .iter()
+            .all(|unit| unit.package == scarb_metadata.workspace.members[0]));
+    }
+
+    #[test]
+    fn load_artifacts_from_tar_and_zip_archives() {
+        let temp = setup_built_package("basic_package", &[]);
+
+        let target_dir = Utf8PathBuf::from_path_buf(temp.join("target/dev")).unwrap();
+        let mut files: Vec<(String, Vec<u8>)> = fs::read_dir(&target_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.is_file())
+            .map(|path| {
+                let name = path.file_name().unwrap().to_str().unwrap().to_string();
+                (name, fs::read(&path).unwrap())
+            })
+            .collect();
+        // Files not referenced by the manifest don't have to be valid UTF-8
+        files.push(("fixture.bin".to_string(), vec![0xff, 0xfe, 0x00, 0x80]));
+
+        let mut tar = tar::Builder::new(Vec::new());
+        for (name, contents) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, format!("./bundle/{name}"), contents.as_slice())
+                .unwrap();
+        }
+        let tar = tar.into_inner().unwrap();
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        for (name, contents) in &files {
+            zip.start_file(format!("bundle/{name}"), zip::write::FileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut zip, contents).unwrap();
+        }
+        let zip = zip.finish().unwrap().into_inner();
+
+        let expected =
+            load_artifacts_from_manifest(&target_dir.join("basic_package.starknet_artifacts.json"))
+                .unwrap();
+        let manifest_path = Utf8Path::new("bundle/basic_package.starknet_artifacts.json");
+
+        for (archive, format) in [(tar, ArchiveFormat::Tar), (zip, ArchiveFormat::Zip)] {
+            let contracts =
+                load_artifacts_from_archive(std::io::Cursor::new(archive), format, manifest_path)
+                    .unwrap();
+
+            assert_eq!(contracts.len(), expected.len());
+            for (name, (artifacts, sierra_path)) in &expected {
+                let (archive_artifacts, archive_sierra_path) = &contracts[name];
+                assert_eq!(archive_artifacts, artifacts);
+                assert_eq!(
+                    archive_sierra_path,
+                    &Utf8Path::new("bundle").join(sierra_path.file_name().unwrap())
+                );
+            }
+        }
+
+        let err = load_artifacts_from_archive(
+            std::io::Cursor::new(Vec::new()),
+            ArchiveFormat::Tar,
+            manifest_path,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains(
+            "File = bundle/basic_package.starknet_artifacts.json not found in the archive"
+        ));
     }
 }
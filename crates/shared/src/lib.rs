@@ -2,7 +2,7 @@ use crate::consts::EXPECTED_RPC_VERSION;
 use crate::print::print_as_warning;
 use crate::rpc::{get_rpc_version, is_expected_version};
 use anyhow::{anyhow, Result};
-use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::jsonrpc::JsonRpcTransport;
 use starknet::providers::JsonRpcClient;
 use std::fmt::Display;
 
@@ -10,13 +10,17 @@ pub mod command;
 pub mod consts;
 pub mod print;
 pub mod rpc;
+pub mod rpc_transport;
 pub mod test_utils;
 pub mod utils;
 
-pub async fn verify_and_warn_if_incompatible_rpc_version(
-    client: &JsonRpcClient<HttpTransport>,
+pub async fn verify_and_warn_if_incompatible_rpc_version<T>(
+    client: &JsonRpcClient<T>,
     url: impl Display,
-) -> Result<()> {
+) -> Result<()>
+where
+    T: JsonRpcTransport + Send + Sync + 'static,
+{
     let node_spec_version = get_rpc_version(client).await?;
     if !is_expected_version(&node_spec_version) {
         print_as_warning(&anyhow!(
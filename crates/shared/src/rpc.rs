@@ -1,8 +1,10 @@
 use crate::consts::EXPECTED_RPC_VERSION;
+use crate::rpc_transport::RpcTransport;
 use anyhow::{Context, Result};
 use semver::{Version, VersionReq};
-use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcTransport};
 use starknet::providers::{JsonRpcClient, Provider};
+use std::collections::HashMap;
 use std::str::FromStr;
 use url::Url;
 
@@ -12,6 +14,17 @@ pub fn create_rpc_client(url: &str) -> Result<JsonRpcClient<HttpTransport>> {
     Ok(client)
 }
 
+/// Same as [`create_rpc_client`], but the client sends `headers` with every request
+/// and connects over WebSocket to `ws://` and `wss://` urls
+pub fn create_rpc_client_with_headers(
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<JsonRpcClient<RpcTransport>> {
+    let parsed_url = Url::parse(url).with_context(|| format!("Failed to parse URL: {url}"))?;
+    let client = JsonRpcClient::new(RpcTransport::new(parsed_url, headers));
+    Ok(client)
+}
+
 #[must_use]
 pub fn is_expected_version(version: &Version) -> bool {
     VersionReq::from_str(EXPECTED_RPC_VERSION)
@@ -19,7 +32,10 @@ pub fn is_expected_version(version: &Version) -> bool {
         .matches(version)
 }
 
-pub async fn get_rpc_version(client: &JsonRpcClient<HttpTransport>) -> Result<Version> {
+pub async fn get_rpc_version<T>(client: &JsonRpcClient<T>) -> Result<Version>
+where
+    T: JsonRpcTransport + Send + Sync + 'static,
+{
     client
         .spec_version()
         .await
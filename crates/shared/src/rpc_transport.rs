@@ -0,0 +1,369 @@
+use async_trait::async_trait;
+use futures::stream::{SplitSink, SplitStream};
+use futures::{SinkExt, StreamExt};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use starknet::providers::jsonrpc::{JsonRpcMethod, JsonRpcResponse, JsonRpcTransport};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+use url::Url;
+
+/// Transport of the JSON-RPC client chosen by the scheme of the endpoint url.
+/// Unlike `HttpTransport` of starknet-rs, it reports why the endpoint failed to serve a request,
+/// see [`RpcTransportError::is_unreachable_endpoint`]
+#[derive(Debug)]
+pub enum RpcTransport {
+    Http(HttpRpcTransport),
+    WebSocket(WebSocketTransport),
+}
+
+impl RpcTransport {
+    /// Transport sending `headers` with every request to `url` over HTTP,
+    /// or with the opening handshake of the connection for `ws://` and `wss://` urls
+    #[must_use]
+    pub fn new(url: Url, headers: &HashMap<String, String>) -> Self {
+        match url.scheme() {
+            "ws" | "wss" => Self::WebSocket(WebSocketTransport::new(url, headers.clone())),
+            _ => Self::Http(HttpRpcTransport::new(url, headers.clone())),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RpcTransportError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("RPC provider rate limited the request")]
+    RateLimited,
+    #[error("RPC provider responded with status = {0}")]
+    ServerError(StatusCode),
+    #[error(transparent)]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    #[error("WebSocket connection was closed before the response was received")]
+    ConnectionClosed,
+    #[error("Header = {0} is not a valid HTTP header")]
+    InvalidHeader(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for RpcTransportError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(Box::new(error))
+    }
+}
+
+impl RpcTransportError {
+    /// Whether the endpoint could not be reached or refused to serve the request,
+    /// so the request may succeed when sent to another endpoint
+    #[must_use]
+    pub fn is_unreachable_endpoint(&self) -> bool {
+        match self {
+            Self::Http(error) => error.is_connect() || error.is_timeout() || error.is_request(),
+            Self::RateLimited
+            | Self::ServerError(_)
+            | Self::WebSocket(_)
+            | Self::ConnectionClosed => true,
+            Self::InvalidHeader(_) | Self::Json(_) => false,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcTransport for RpcTransport {
+    type Error = RpcTransportError;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send + Sync,
+        R: DeserializeOwned,
+    {
+        let id = match self {
+            Self::Http(_) => 1,
+            Self::WebSocket(transport) => transport.next_id.fetch_add(1, Ordering::Relaxed),
+        };
+        let request = serde_json::to_string(&JsonRpcRequest {
+            id,
+            jsonrpc: "2.0",
+            method,
+            params,
+        })?;
+
+        let response = match self {
+            Self::Http(transport) => transport.send(request).await?,
+            Self::WebSocket(transport) => transport.send(id, request).await?,
+        };
+
+        Ok(serde_json::from_str(&response)?)
+    }
+}
+
+/// JSON-RPC transport sending every request in a separate HTTP POST request
+#[derive(Debug)]
+pub struct HttpRpcTransport {
+    client: reqwest::Client,
+    url: Url,
+    headers: HashMap<String, String>,
+}
+
+impl HttpRpcTransport {
+    #[must_use]
+    pub fn new(url: Url, headers: HashMap<String, String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            headers,
+        }
+    }
+
+    async fn send(&self, request: String) -> Result<String, RpcTransportError> {
+        let response = self
+            .headers
+            .iter()
+            .fold(
+                self.client
+                    .post(self.url.clone())
+                    .header(CONTENT_TYPE, "application/json"),
+                |request, (name, value)| request.header(name, value),
+            )
+            .body(request)
+            .send()
+            .await?;
+
+        match response.status() {
+            StatusCode::TOO_MANY_REQUESTS => Err(RpcTransportError::RateLimited),
+            status if status.is_server_error() => Err(RpcTransportError::ServerError(status)),
+            _ => Ok(response.text().await?),
+        }
+    }
+}
+
+/// JSON-RPC transport keeping a single WebSocket connection open for all requests.
+/// The connection is opened on the first request and reopened once it gets closed.
+/// Requests don't wait for responses to the previous ones, responses are matched by their ids.
+pub struct WebSocketTransport {
+    url: Url,
+    headers: HashMap<String, String>,
+    connection: tokio::sync::Mutex<Option<Connection>>,
+    next_id: AtomicU64,
+}
+
+impl WebSocketTransport {
+    #[must_use]
+    pub fn new(url: Url, headers: HashMap<String, String>) -> Self {
+        Self {
+            url,
+            headers,
+            connection: tokio::sync::Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    async fn send(&self, id: u64, request: String) -> Result<String, RpcTransportError> {
+        let response = {
+            let mut connection = self.connection.lock().await;
+            if !connection.as_ref().is_some_and(Connection::is_open) {
+                *connection = Some(Connection::open(&self.url, &self.headers).await?);
+            }
+            let connection = connection
+                .as_mut()
+                .expect("Connection should be opened before sending a request");
+
+            let response = connection.register(id)?;
+            connection.sender.send(Message::Text(request)).await?;
+            response
+        };
+
+        response
+            .await
+            .map_err(|_| RpcTransportError::ConnectionClosed)
+    }
+}
+
+impl Debug for WebSocketTransport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketTransport")
+            .field("url", &self.url)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<P> {
+    id: u64,
+    jsonrpc: &'static str,
+    method: JsonRpcMethod,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct ResponseId {
+    id: Option<u64>,
+}
+
+type WebSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Senders of responses by ids of requests waiting for them,
+/// `None` once the connection is closed
+type PendingResponses = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<String>>>>>;
+
+struct Connection {
+    sender: SplitSink<WebSocket, Message>,
+    pending: PendingResponses,
+}
+
+impl Connection {
+    async fn open(url: &Url, headers: &HashMap<String, String>) -> Result<Self, RpcTransportError> {
+        let mut request = url.as_str().into_client_request()?;
+        for (name, value) in headers {
+            let invalid_header = || RpcTransportError::InvalidHeader(name.clone());
+            request.headers_mut().insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid_header())?,
+                HeaderValue::from_str(value).map_err(|_| invalid_header())?,
+            );
+        }
+
+        let (stream, _) = connect_async(request).await?;
+        let (sender, receiver) = stream.split();
+        let pending: PendingResponses = Arc::new(Mutex::new(Some(HashMap::new())));
+
+        tokio::spawn(read_responses(receiver, pending.clone()));
+
+        Ok(Self { sender, pending })
+    }
+
+    fn is_open(&self) -> bool {
+        self.pending
+            .lock()
+            .expect("Pending responses lock should not be poisoned")
+            .is_some()
+    }
+
+    fn register(&self, id: u64) -> Result<oneshot::Receiver<String>, RpcTransportError> {
+        let (sender, receiver) = oneshot::channel();
+
+        self.pending
+            .lock()
+            .expect("Pending responses lock should not be poisoned")
+            .as_mut()
+            .ok_or(RpcTransportError::ConnectionClosed)?
+            .insert(id, sender);
+
+        Ok(receiver)
+    }
+}
+
+/// Passes responses read from the connection to the requests waiting for them.
+/// Messages without an id of a pending request, e.g. subscription notifications, are skipped
+async fn read_responses(mut receiver: SplitStream<WebSocket>, pending: PendingResponses) {
+    while let Some(Ok(message)) = receiver.next().await {
+        let response = match message {
+            Message::Text(text) => text,
+            Message::Binary(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => text,
+                Err(_) => continue,
+            },
+            Message::Close(_) => break,
+            Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+        };
+
+        let Ok(ResponseId { id: Some(id) }) = serde_json::from_str(&response) else {
+            continue;
+        };
+
+        let sender = pending
+            .lock()
+            .expect("Pending responses lock should not be poisoned")
+            .as_mut()
+            .and_then(|pending| pending.remove(&id));
+
+        if let Some(sender) = sender {
+            // The request may have been cancelled in the meantime
+            let _ = sender.send(response);
+        }
+    }
+
+    // Dropping the senders fails the requests still waiting for responses
+    pending
+        .lock()
+        .expect("Pending responses lock should not be poisoned")
+        .take();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn send_block_number_request(url: &str) -> RpcTransportError {
+        let transport = RpcTransport::new(Url::parse(url).unwrap(), &HashMap::new());
+        let result: Result<JsonRpcResponse<Value>, _> =
+            transport.send_request(JsonRpcMethod::BlockNumber, ()).await;
+
+        let Err(error) = result else {
+            panic!("Request to {url} should fail");
+        };
+        error
+    }
+
+    async fn server_responding_with(response: ResponseTemplate) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(response)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn rate_limited_endpoint_is_unreachable() {
+        let server = server_responding_with(ResponseTemplate::new(429)).await;
+
+        let error = send_block_number_request(&server.uri()).await;
+        assert!(matches!(error, RpcTransportError::RateLimited));
+        assert!(error.is_unreachable_endpoint());
+    }
+
+    #[tokio::test]
+    async fn failing_endpoint_is_unreachable() {
+        let server = server_responding_with(ResponseTemplate::new(503)).await;
+
+        let error = send_block_number_request(&server.uri()).await;
+        assert!(matches!(error, RpcTransportError::ServerError(_)));
+        assert!(error.is_unreachable_endpoint());
+    }
+
+    #[tokio::test]
+    async fn refusing_endpoint_is_unreachable() {
+        let error = send_block_number_request("http://127.0.0.1:1").await;
+        assert!(matches!(error, RpcTransportError::Http(_)));
+        assert!(error.is_unreachable_endpoint());
+    }
+
+    #[tokio::test]
+    async fn invalid_response_is_not_unreachable_endpoint() {
+        let server =
+            server_responding_with(ResponseTemplate::new(200).set_body_string("not json")).await;
+
+        let error = send_block_number_request(&server.uri()).await;
+        assert!(matches!(error, RpcTransportError::Json(_)));
+        assert!(!error.is_unreachable_endpoint());
+    }
+}
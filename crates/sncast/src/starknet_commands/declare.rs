@@ -77,8 +77,10 @@ pub async fn declare(
                 ErrorData::new(declare.contract),
             ))?;
 
-    let contract_definition: SierraClass = serde_json::from_str(&contract_artifacts.sierra)
-        .context("Failed to parse sierra artifact")?;
+    let contract_definition: SierraClass = contract_artifacts
+        .parse_sierra()
+        .context("Failed to parse sierra artifact")?
+        .clone();
     let casm_contract_definition: CompiledClass =
         serde_json::from_str(&contract_artifacts.casm).context("Failed to parse casm artifact")?;
 
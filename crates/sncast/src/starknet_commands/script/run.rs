@@ -27,7 +27,9 @@ use runtime::{
     CheatcodeHandlingResult, EnhancedHintError, ExtendedRuntime, ExtensionLogic, StarknetRuntime,
     SyscallHandlingResult,
 };
-use scarb_api::{package_matches_version_requirement, StarknetContractArtifacts};
+use scarb_api::{
+    package_matches_version_requirement, profile_target_dir, StarknetContractArtifacts,
+};
 use scarb_metadata::{Metadata, PackageMetadata};
 use semver::{Comparator, Op, Version, VersionReq};
 use shared::print::print_as_warning;
@@ -286,7 +288,7 @@ pub fn run(
         .get(SCRIPT_LIB_ARTIFACT_NAME)
         .ok_or(anyhow!("Failed to find script artifact"))?;
 
-    let sierra_program = serde_json::from_str::<VersionedProgram>(&artifact.sierra)
+    let sierra_program = serde_json::from_str::<VersionedProgram>(artifact.sierra()?)
         .with_context(|| "Failed to deserialize Sierra program")?
         .into_v1()
         .with_context(|| "Failed to load Sierra program")?
@@ -420,17 +422,10 @@ fn inject_lib_artifact(
 ) -> Result<HashMap<String, StarknetContractArtifacts>> {
     let sierra_filename = format!("{}.sierra.json", package_metadata.name);
 
-    let target_dir = &metadata
-        .target_dir
-        .clone()
-        .unwrap_or_else(|| metadata.workspace.root.join("target"));
-    // TODO(#2042)
-    let sierra_path = &target_dir.join("dev").join(sierra_filename);
+    let sierra_path = &profile_target_dir(metadata).join(sierra_filename);
 
-    let lib_artifacts = ScriptStarknetContractArtifacts {
-        sierra: fs::read_to_string(sierra_path)?,
-        casm: String::new(),
-    };
+    let lib_artifacts =
+        ScriptStarknetContractArtifacts::new(fs::read_to_string(sierra_path)?, String::new());
 
     artifacts.insert(SCRIPT_LIB_ARTIFACT_NAME.to_string(), lib_artifacts);
     Ok(artifacts.clone())
@@ -1,6 +1,6 @@
 use self::block_id::{BlockId, BlockIdVariants};
 use crate::{
-    args::Arguments,
+    args::{unnamed::UnnamedArgs, Arguments},
     attributes::{AttributeCollector, AttributeInfo, AttributeTypeData},
     branch,
     cairo_expression::CairoExpression,
@@ -69,9 +69,13 @@ fn inline_args(db: &dyn SyntaxGroup, args: &Arguments) -> Result<String, Diagnos
 }
 
 fn from_file_args(db: &dyn SyntaxGroup, args: &Arguments) -> Result<String, Diagnostic> {
-    let &[arg] = args
-        .unnamed_only::<ForkCollector>()?
-        .of_length::<1, ForkCollector>()?;
+    let unnamed_args = args.unnamed_only::<ForkCollector>()?;
+
+    if unnamed_args.len() > 1 {
+        return multiple_from_file_args(db, &unnamed_args);
+    }
+
+    let &[arg] = unnamed_args.of_length::<1, ForkCollector>()?;
 
     let name = String::parse_from_expr::<ForkCollector>(db, arg.1, arg.0.to_string().as_str())?;
 
@@ -82,6 +86,24 @@ fn from_file_args(db: &dyn SyntaxGroup, args: &Arguments) -> Result<String, Diag
     ))
 }
 
+fn multiple_from_file_args(
+    db: &dyn SyntaxGroup,
+    unnamed_args: &UnnamedArgs,
+) -> Result<String, Diagnostic> {
+    let names = unnamed_args
+        .iter()
+        .map(|(position, expr)| {
+            String::parse_from_expr::<ForkCollector>(db, expr, position.to_string().as_str())
+                .map(|name| name.as_cairo_expression())
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .join(", ");
+
+    Ok(format!(
+        r#"snforge_std::_config_types::ForkConfig::Multiple(array![{names}])"#
+    ))
+}
+
 fn overridden_args(db: &dyn SyntaxGroup, args: &Arguments) -> Result<String, Diagnostic> {
     let &[arg] = args.unnamed().of_length::<1, ForkCollector>()?;
 
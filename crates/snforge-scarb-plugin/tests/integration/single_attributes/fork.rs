@@ -118,6 +118,35 @@ fn accepts_string() {
     );
 }
 
+#[test]
+fn accepts_multiple_strings() {
+    let item = TokenStream::new(EMPTY_FN.into());
+    let args = TokenStream::new(r#"("L2", "L3")"#.into());
+
+    let result = fork(args, item);
+
+    assert_diagnostics(&result, &[]);
+
+    assert_output(
+        &result,
+        r#"
+            fn empty_fn() {
+                if snforge_std::_cheatcode::_is_config_run() {
+
+                    let mut data = array![];
+
+                    snforge_std::_config_types::ForkConfig::Multiple(array!["L2", "L3"])
+                        .serialize(ref data);
+
+                    starknet::testing::cheatcode::<'set_config_fork'>(data.span());
+
+                    return;
+                }
+            }
+        "#,
+    );
+}
+
 #[test]
 fn accepts_inline_config() {
     let item = TokenStream::new(EMPTY_FN.into());
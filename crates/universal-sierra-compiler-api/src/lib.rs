@@ -1,14 +1,16 @@
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use cairo_lang_casm::hints::Hint;
 use cairo_lang_sierra::program::Program;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt::Display;
-use std::io::Write;
+use std::io::{self, Write};
 use std::path::Path;
 use std::str::from_utf8;
+use std::sync::OnceLock;
 use tempfile::Builder;
+use thiserror::Error;
 
 pub use command::*;
 use shared::command::CommandExt;
@@ -64,34 +66,158 @@ pub fn compile_sierra(
     )
 }
 
+/// Compile sierra held in memory, e.g. a class fetched over RPC, without a source file.
+/// `universal-sierra-compiler` reads sierra from a file, so it is passed through a temporary one
+/// that is removed right after the compilation.
+pub fn compile_sierra_from_str(sierra: &str, sierra_type: &SierraType) -> Result<String> {
+    let mut temp_sierra_file = Builder::new().tempfile()?;
+    temp_sierra_file.write_all(sierra.as_bytes())?;
+
+    compile_sierra_at_path(temp_sierra_file.path().to_str().unwrap(), None, sierra_type)
+}
+
 pub fn compile_sierra_at_path(
     sierra_file_path: &str,
     current_dir: Option<&Path>,
     sierra_type: &SierraType,
+) -> Result<String> {
+    compile_sierra_at_path_with_options(
+        sierra_file_path,
+        current_dir,
+        sierra_type,
+        &CompilationOptions::default(),
+    )
+}
+
+/// Failure of running `universal-sierra-compiler`, returned wrapped in [`anyhow::Error`]
+/// by the compiling functions, so it can be recovered with [`anyhow::Error::downcast_ref`]
+#[derive(Error, Debug)]
+pub enum CompilerError {
+    /// The binary is not installed
+    #[error(
+        "Cannot find `universal-sierra-compiler` binary. \
+        Make sure you have USC installed https://github.com/software-mansion/universal-sierra-compiler \
+        and added to PATH (or set at UNIVERSAL_SIERRA_COMPILER env var)"
+    )]
+    NotFound,
+    /// The binary ran, but failed to compile sierra
+    #[error(
+        "Error while compiling Sierra. \
+        Make sure you have the latest universal-sierra-compiler binary installed. \
+        Contact us if it doesn't help\n{stderr}"
+    )]
+    CompilationFailed { stderr: String },
+}
+
+/// Options of compiling sierra with `universal-sierra-compiler`, selecting the compilation
+/// configuration expected by the runtime executing the casm.
+/// The default options compile the same way as `universal-sierra-compiler` without any flags.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CompilationOptions {
+    /// Add hints in the Python format next to the Rust ones,
+    /// required by runtimes executing hints with the Python VM
+    pub add_pythonic_hints: bool,
+    /// Whether the compiled casm accounts for gas usage
+    pub gas_metering: GasMetering,
+}
+
+/// Gas accounting of the compiled casm
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GasMetering {
+    /// Gas usage is checked, as required by Starknet
+    #[default]
+    Enabled,
+    /// Gas usage is not checked, for runtimes that execute casm without gas, e.g. by steps only
+    Disabled,
+}
+
+impl CompilationOptions {
+    /// Flags of the `compile-*` subcommand of `universal-sierra-compiler` selecting the options
+    #[must_use]
+    pub fn usc_args(&self) -> Vec<&'static str> {
+        let mut args = vec![];
+        if self.add_pythonic_hints {
+            args.push("--add-pythonic-hints");
+        }
+        if self.gas_metering == GasMetering::Disabled {
+            args.push("--no-gas-metering");
+        }
+        args
+    }
+}
+
+pub fn compile_sierra_at_path_with_options(
+    sierra_file_path: &str,
+    current_dir: Option<&Path>,
+    sierra_type: &SierraType,
+    options: &CompilationOptions,
 ) -> Result<String> {
     let mut usc_command = UniversalSierraCompilerCommand::new();
     if let Some(dir) = current_dir {
+        // A missing directory fails to spawn the process with `NotFound` too,
+        // it must not be reported as a missing binary
+        if !dir.is_dir() {
+            bail!(
+                "Cannot run universal-sierra-compiler in {}, the directory does not exist",
+                dir.display()
+            );
+        }
         usc_command.current_dir(dir);
     }
 
-    let usc_output = usc_command
-        .inherit_stderr()
+    // Stderr is captured, so a compilation failure can be told apart from a missing binary
+    let usc_output = match usc_command
         .args(vec![
             &("compile-".to_string() + &sierra_type.to_string()),
             "--sierra-path",
             sierra_file_path,
         ])
+        .args(options.usc_args())
         .command()
-        .output_checked()
-        .context(
-            "Error while compiling Sierra. \
-            Make sure you have the latest universal-sierra-compiler binary installed. \
-            Contact us if it doesn't help",
-        )?;
+        .output()
+    {
+        Ok(usc_output) => usc_output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(CompilerError::NotFound.into())
+        }
+        Err(err) => return Err(err).context("Failed to run universal-sierra-compiler"),
+    };
+
+    if !usc_output.status.success() {
+        return Err(CompilerError::CompilationFailed {
+            stderr: String::from_utf8_lossy(&usc_output.stderr)
+                .trim()
+                .to_string(),
+        }
+        .into());
+    }
+
+    // Warnings printed by a successful compilation are shown as if stderr was inherited
+    if !usc_output.stderr.is_empty() {
+        io::stderr().write_all(&usc_output.stderr)?;
+    }
 
     Ok(from_utf8(&usc_output.stdout)?.to_string())
 }
 
+/// Version of the `universal-sierra-compiler` binary, as printed by `universal-sierra-compiler --version`
+pub fn universal_sierra_compiler_version() -> Result<String> {
+    static VERSION: OnceLock<String> = OnceLock::new();
+
+    if let Some(version) = VERSION.get() {
+        return Ok(version.clone());
+    }
+
+    let usc_output = UniversalSierraCompilerCommand::new()
+        .arg("--version")
+        .command()
+        .output_checked()
+        .context("Failed to get universal-sierra-compiler version")?;
+    let version = from_utf8(&usc_output.stdout)?.trim().to_string();
+
+    Ok(VERSION.get_or_init(|| version).clone())
+}
+
 pub enum SierraType {
     Contract,
     Raw,
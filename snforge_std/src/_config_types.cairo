@@ -26,7 +26,8 @@ struct OverriddenForkConfig {
 enum ForkConfig {
     Inline: InlineForkConfig,
     Named: ByteArray,
-    Overridden: OverriddenForkConfig
+    Overridden: OverriddenForkConfig,
+    Multiple: Array<ByteArray>
 }
 
 #[derive(Drop, Serde)]
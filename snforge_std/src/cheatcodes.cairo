@@ -10,6 +10,7 @@ mod fork;
 mod storage;
 mod execution_info;
 mod message_to_l1;
+mod side_effects;
 
 /// Enum used to specify how long the target should be cheated for.
 #[derive(Copy, Drop, Serde, PartialEq, Clone, Debug)]
@@ -128,6 +128,21 @@ fn declare(contract: ByteArray) -> Result<DeclareResult, Array<felt252>> {
     Serde::deserialize(ref span).unwrap()
 }
 
+/// Deploys a contract of an already declared class, without declaring it again
+/// `class_hash` - class hash of a contract declared earlier in the test, or present on the fork
+/// `constructor_calldata` - calldata for the constructor, serialized with `Serde`
+/// Returns the address the contract was deployed at and serialized constructor return data, or
+/// panic data if it failed
+fn deploy_from_class_hash(
+    class_hash: ClassHash, constructor_calldata: @Array::<felt252>
+) -> SyscallResult<(ContractAddress, Span<felt252>)> {
+    let mut inputs = _prepare_calldata(@class_hash, constructor_calldata);
+
+    let mut outputs = handle_cheatcode(cheatcode::<'deploy_from_class_hash'>(inputs.span()));
+
+    Serde::deserialize(ref outputs).unwrap()
+}
+
 /// Retrieves a class hash of a contract deployed under the given address
 /// `contract_address` - target contract address
 /// Returns the `ClassHash` under given address
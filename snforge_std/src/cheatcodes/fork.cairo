@@ -1,3 +1,6 @@
+use starknet::{ClassHash, ContractAddress, testing::cheatcode};
+use super::super::_cheatcode::handle_cheatcode;
+
 #[derive(Drop, Copy, Serde)]
 enum BlockTag {
     Latest,
@@ -10,3 +13,49 @@ enum BlockId {
     Hash: felt252,
     Number: u64,
 }
+
+/// Returns the number of the block the test is forked from (also when the fork is configured with
+/// a block tag or hash), or `Option::None` if the test does not use a fork.
+/// After `select_fork`, returns the block number of the selected fork
+fn fork_block_number() -> Option<u64> {
+    let mut outputs = handle_cheatcode(cheatcode::<'fork_block_number'>(array![].span()));
+    Serde::<Option<u64>>::deserialize(ref outputs).unwrap()
+}
+
+/// Fetches class, nonce and the given storage of the contract from the fork node, so they are
+/// served from the fork cache later on. Has no effect on the state seen by the test
+/// `target` - address of the contract whose state should be fetched
+/// `storage_addresses` - storage addresses of the values to fetch
+fn prefetch_fork_state(target: ContractAddress, storage_addresses: Span<felt252>) {
+    let mut inputs = array![target.into()];
+    storage_addresses.serialize(ref inputs);
+
+    handle_cheatcode(cheatcode::<'prefetch_fork_state'>(inputs.span()));
+}
+
+/// Switches the fork that the test reads state from to the one named `name`.
+/// The fork has to be attached to the test with `#[fork("FIRST", "SECOND")]`,
+/// the first of attached forks is the one the test starts with
+fn select_fork(name: ByteArray) {
+    let mut inputs = array![];
+    name.serialize(ref inputs);
+
+    let mut output = handle_cheatcode(cheatcode::<'select_fork'>(inputs.span()));
+    let result = Serde::<Result<(), ByteArray>>::deserialize(ref output).unwrap();
+
+    match result {
+        Result::Ok(()) => {},
+        Result::Err(err) => panic!("{}", err),
+    }
+}
+
+/// Returns whether a class with the given hash is declared in the state the test runs on, that is
+/// on the fork at the forked block or earlier in the test. Answers of the fork node are cached,
+/// so repeated checks of the same class hash do not send requests
+/// `class_hash` - hash of the class to look for
+fn is_class_declared(class_hash: ClassHash) -> bool {
+    let mut outputs = handle_cheatcode(
+        cheatcode::<'is_class_declared'>(array![class_hash.into()].span())
+    );
+    Serde::<bool>::deserialize(ref outputs).unwrap()
+}
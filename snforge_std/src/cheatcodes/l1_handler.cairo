@@ -1,7 +1,8 @@
 use core::array::SpanTrait;
 use core::serde::Serde;
-use starknet::{ContractAddress, testing::cheatcode, SyscallResult};
+use starknet::{ContractAddress, EthAddress, testing::cheatcode, SyscallResult};
 use super::super::_cheatcode::handle_cheatcode;
+use super::contract_class::{declare, ContractClassTrait, DeclareResultTrait};
 
 #[derive(Drop, Clone)]
 struct L1Handler {
@@ -9,11 +10,73 @@ struct L1Handler {
     selector: felt252,
 }
 
+/// Single L1 -> L2 message executed as a part of a batch
+#[derive(Drop, Copy, Serde)]
+struct L1Message {
+    from_address: felt252,
+    payload: Span<felt252>,
+    nonce: Option<felt252>,
+}
+
+/// Block info visible to the L1 handler for the duration of its execution, fields set to
+/// `Option::None` keep the values seen by the contract outside the handler call
+#[derive(Drop, Copy, Serde)]
+struct L1HandlerBlockContext {
+    block_number: Option<u64>,
+    block_timestamp: Option<u64>,
+}
+
+/// Storage slot whose value was changed by the L1 handler
+#[derive(Drop, Copy, Serde, PartialEq, Debug)]
+struct StorageWrite {
+    contract_address: ContractAddress,
+    key: felt252,
+    old_value: felt252,
+    new_value: felt252,
+}
+
+/// Outcome of an L1 handler call whose storage changes can be rolled back after inspecting it
+#[derive(Drop, Clone, Serde)]
+struct L1HandlerExecution {
+    result: SyscallResult<()>,
+    storage_writes: Array<StorageWrite>,
+}
+
 trait L1HandlerTrait {
     fn new(target: ContractAddress, selector: felt252) -> L1Handler;
+    fn for_deployed(deployed: (ContractAddress, Span<felt252>), selector: felt252) -> L1Handler;
     fn execute(
         self: L1Handler, from_address: felt252, payload: Span::<felt252>
     ) -> SyscallResult<()>;
+    fn execute_with_nonce(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>, nonce: felt252
+    ) -> SyscallResult<()>;
+    fn execute_from_eth_address(
+        self: L1Handler, from_address: EthAddress, payload: Span::<felt252>
+    ) -> SyscallResult<()>;
+    fn execute_with_block_context(
+        self: L1Handler,
+        from_address: felt252,
+        payload: Span::<felt252>,
+        block_context: L1HandlerBlockContext
+    ) -> SyscallResult<()>;
+    fn execute_rejected(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<()>;
+    fn execute_batch(
+        self: L1Handler, messages: Span<L1Message>, stop_on_failure: bool
+    ) -> Array<SyscallResult<()>>;
+    fn execute_isolated(
+        self: L1Handler, from_address: felt252, payloads: Span<Span<felt252>>
+    ) -> Array<SyscallResult<()>>;
+    fn execute_tracking_storage(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<Array<StorageWrite>>;
+    fn execute_with_storage_rollback(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> L1HandlerExecution;
+    fn register_message(self: L1Handler, from_address: felt252, payload: Span::<felt252>);
+    fn pending_messages(self: L1Handler, from_address: felt252, payload: Span::<felt252>) -> u32;
 }
 
 impl L1HandlerImpl of L1HandlerTrait {
@@ -25,6 +88,27 @@ impl L1HandlerImpl of L1HandlerTrait {
         L1Handler { target, selector, }
     }
 
+    /// `deployed` - Result of a successful deployment, as returned by `deploy`
+    /// `selector` - Selector of a `#[l1_handler]` function. Can be acquired with
+    /// `selector!("function_handler_name")` macro
+    /// Returns a structure referring to a L1 handler function of the deployed contract.
+    /// Panics if the deployed contract has no L1 handler with the given selector
+    fn for_deployed(deployed: (ContractAddress, Span<felt252>), selector: felt252) -> L1Handler {
+        let (target, _) = deployed;
+
+        let mut output = handle_cheatcode(
+            cheatcode::<'validate_l1_handler'>(array![target.into(), selector].span())
+        );
+        let result = Serde::<Result<(), ByteArray>>::deserialize(ref output).unwrap();
+
+        match result {
+            Result::Ok(()) => {},
+            Result::Err(err) => panic!("{}", err),
+        }
+
+        L1Handler { target, selector, }
+    }
+
     /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function
     /// `self` - `L1Handler` structure referring to a L1 handler function
     /// `from_address` - Ethereum address of the contract that you want to be the message sender
@@ -33,19 +117,277 @@ impl L1HandlerImpl of L1HandlerTrait {
     fn execute(
         self: L1Handler, from_address: felt252, payload: Span::<felt252>
     ) -> SyscallResult<()> {
-        let mut inputs: Array::<felt252> = array![
-            self.target.into(), self.selector, from_address.into(),
-        ];
-        payload.serialize(ref inputs);
+        execute_l1_handler(self, from_address, payload, Option::None, no_block_context())
+    }
+
+    /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function, with the
+    /// transaction nonce visible to the handler set to the given value
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// `nonce` - Nonce of the L1 -> L2 message returned by `get_tx_info` inside the handler
+    /// Returns () or panic data if it failed
+    fn execute_with_nonce(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>, nonce: felt252
+    ) -> SyscallResult<()> {
+        execute_l1_handler(self, from_address, payload, Option::Some(nonce), no_block_context())
+    }
+
+    /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function, with the
+    /// sender given as an Ethereum address
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns () or panic data if it failed
+    fn execute_from_eth_address(
+        self: L1Handler, from_address: EthAddress, payload: Span::<felt252>
+    ) -> SyscallResult<()> {
+        execute_l1_handler(self, from_address.into(), payload, Option::None, no_block_context())
+    }
+
+    /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function, with the
+    /// block number and timestamp visible to the handler overridden for the duration of the call
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// `block_context` - Block info returned by `get_block_info` inside the handler
+    /// Returns () or panic data if it failed
+    fn execute_with_block_context(
+        self: L1Handler,
+        from_address: felt252,
+        payload: Span::<felt252>,
+        block_context: L1HandlerBlockContext
+    ) -> SyscallResult<()> {
+        execute_l1_handler(self, from_address, payload, Option::None, block_context)
+    }
+
+    /// Mocks L1 -> L2 message from Ethereum which is rejected on Starknet, e.g. because it was not
+    /// paid enough fee on L1. No fee is checked, the handler is never executed and the call fails
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns panic data with 'L1 message rejected'
+    fn execute_rejected(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<()> {
+        execute_l1_handler_or_reject(
+            self, from_address, payload, Option::None, no_block_context(), true
+        )
+    }
+
+    /// Mocks a queue of L1 -> L2 messages handled by the given L1 handler function, executed
+    /// in order
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `messages` - Messages to be handled, each one sees the state changes of the previous ones
+    /// `stop_on_failure` - If true, messages following the first failed one are not executed
+    /// Returns results of the executed messages, in order
+    fn execute_batch(
+        self: L1Handler, messages: Span<L1Message>, stop_on_failure: bool
+    ) -> Array<SyscallResult<()>> {
+        let mut inputs: Array::<felt252> = array![self.target.into(), self.selector];
+        messages.serialize(ref inputs);
+        stop_on_failure.serialize(ref inputs);
+
+        let mut outputs = handle_cheatcode(cheatcode::<'l1_handler_execute_batch'>(inputs.span()));
+
+        Serde::<Array<SyscallResult<()>>>::deserialize(ref outputs).unwrap()
+    }
+
+    /// Mocks independent L1 -> L2 messages handled by the given L1 handler function, one per
+    /// payload. Every message is handled on top of the state from before the call, changes made
+    /// to the contracts state by the handler are discarded after each message
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payloads` - The handlers' function arguments serialized with `Serde`, one per message
+    /// Returns results of the messages, in order
+    fn execute_isolated(
+        self: L1Handler, from_address: felt252, payloads: Span<Span<felt252>>
+    ) -> Array<SyscallResult<()>> {
+        let mut inputs: Array::<felt252> = array![self.target.into(), self.selector, from_address];
+        payloads.serialize(ref inputs);
 
-        let mut outputs = handle_cheatcode(cheatcode::<'l1_handler_execute'>(inputs.span()));
-        let exit_code = *outputs.pop_front().unwrap();
+        let mut outputs = handle_cheatcode(
+            cheatcode::<'l1_handler_execute_isolated'>(inputs.span())
+        );
 
-        if exit_code == 0 {
-            SyscallResult::Ok(())
-        } else {
-            let panic_data = Serde::<Array<felt252>>::deserialize(ref outputs).unwrap();
-            SyscallResult::Err(panic_data)
+        Serde::<Array<SyscallResult<()>>>::deserialize(ref outputs).unwrap()
+    }
+
+    /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function and
+    /// records storage slots changed by the handler, including the ones of contracts it called
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns changed storage slots sorted by contract address and key, or panic data if it failed
+    fn execute_tracking_storage(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<Array<StorageWrite>> {
+        let L1HandlerExecution { result, storage_writes } = execute_l1_handler_tracking_storage(
+            self, from_address, payload
+        );
+
+        match result {
+            SyscallResult::Ok(()) => SyscallResult::Ok(storage_writes),
+            SyscallResult::Err(panic_data) => SyscallResult::Err(panic_data),
         }
     }
+
+    /// Mocks L1 -> L2 message from Ethereum handled by the given L1 handler function, keeping
+    /// the storage changes made by the handler so they can be rolled back later, regardless of
+    /// whether the handler succeeded
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns `L1HandlerExecution` with the result of the call
+    fn execute_with_storage_rollback(
+        self: L1Handler, from_address: felt252, payload: Span::<felt252>
+    ) -> L1HandlerExecution {
+        execute_l1_handler_tracking_storage(self, from_address, payload)
+    }
+
+    /// Registers L1 -> L2 message as sent from Ethereum, so it can be consumed by `execute`.
+    /// Once a message is registered, executing messages which were not registered fails
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that sent the message
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    fn register_message(self: L1Handler, from_address: felt252, payload: Span::<felt252>) {
+        let mut inputs: Array::<felt252> = array![self.target.into(), self.selector, from_address];
+        payload.serialize(ref inputs);
+
+        handle_cheatcode(cheatcode::<'l1_handler_register_message'>(inputs.span()));
+    }
+
+    /// `self` - `L1Handler` structure referring to a L1 handler function
+    /// `from_address` - Ethereum address of the contract that sent the message
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns how many times the message was registered and not consumed yet
+    fn pending_messages(self: L1Handler, from_address: felt252, payload: Span::<felt252>) -> u32 {
+        let mut inputs: Array::<felt252> = array![self.target.into(), self.selector, from_address];
+        payload.serialize(ref inputs);
+
+        let mut outputs = handle_cheatcode(
+            cheatcode::<'l1_handler_pending_messages'>(inputs.span())
+        );
+
+        Serde::<u32>::deserialize(ref outputs).unwrap()
+    }
+}
+
+trait L1HandlerExecutionTrait {
+    fn rollback_storage(self: L1HandlerExecution);
+}
+
+impl L1HandlerExecutionImpl of L1HandlerExecutionTrait {
+    /// Restores the values storage slots changed by the L1 handler had before the call.
+    /// Other changes, e.g. contracts deployed by the handler, are kept
+    /// `self` - `L1HandlerExecution` returned by `execute_with_storage_rollback`
+    fn rollback_storage(self: L1HandlerExecution) {
+        let mut inputs: Array::<felt252> = array![];
+        self.storage_writes.serialize(ref inputs);
+
+        handle_cheatcode(cheatcode::<'rollback_storage_writes'>(inputs.span()));
+    }
+}
+
+/// Contract deployed with `deploy_for_l1_handler`, whose L1 handlers can be executed directly
+#[derive(Drop, Copy)]
+struct L1HandlerContract {
+    contract_address: ContractAddress,
+}
+
+trait L1HandlerContractTrait {
+    fn execute_l1_handler(
+        self: L1HandlerContract, selector: felt252, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<()>;
+}
+
+impl L1HandlerContractImpl of L1HandlerContractTrait {
+    /// Mocks L1 -> L2 message from Ethereum handled by an L1 handler function of the contract
+    /// `self` - `L1HandlerContract` returned by `deploy_for_l1_handler`
+    /// `selector` - Selector of a `#[l1_handler]` function. Can be acquired with
+    /// `selector!("function_handler_name")` macro
+    /// `from_address` - Ethereum address of the contract that you want to be the message sender
+    /// `payload` - The handlers' function arguments serialized with `Serde`
+    /// Returns () or panic data if it failed
+    fn execute_l1_handler(
+        self: L1HandlerContract, selector: felt252, from_address: felt252, payload: Span::<felt252>
+    ) -> SyscallResult<()> {
+        L1HandlerTrait::new(self.contract_address, selector).execute(from_address, payload)
+    }
+}
+
+/// Declares and deploys the contract, so its L1 handlers can be executed right away
+/// `contract_name` - Name of the contract to declare
+/// `constructor_calldata` - Constructor arguments serialized with `Serde`
+/// Returns `L1HandlerContract` referring to the deployed contract.
+/// Panics if declaring or deploying the contract fails
+fn deploy_for_l1_handler(
+    contract_name: ByteArray, constructor_calldata: @Array::<felt252>
+) -> L1HandlerContract {
+    let contract = declare(contract_name).unwrap().contract_class();
+    let (contract_address, _) = contract.deploy(constructor_calldata).unwrap();
+
+    L1HandlerContract { contract_address }
+}
+
+/// Executes the L1 handler and returns its result together with the storage slots it changed,
+/// which are returned regardless of whether the handler succeeded
+fn execute_l1_handler_tracking_storage(
+    l1_handler: L1Handler, from_address: felt252, payload: Span::<felt252>
+) -> L1HandlerExecution {
+    let mut inputs: Array::<felt252> = array![
+        l1_handler.target.into(), l1_handler.selector, from_address
+    ];
+    payload.serialize(ref inputs);
+
+    let mut outputs = handle_cheatcode(
+        cheatcode::<'l1_handler_execute_tracking_storage'>(inputs.span())
+    );
+    let (result, storage_writes): (SyscallResult<()>, Array<StorageWrite>) = Serde::deserialize(
+        ref outputs
+    )
+        .unwrap();
+
+    L1HandlerExecution { result, storage_writes }
+}
+
+fn no_block_context() -> L1HandlerBlockContext {
+    L1HandlerBlockContext { block_number: Option::None, block_timestamp: Option::None }
+}
+
+fn execute_l1_handler(
+    l1_handler: L1Handler,
+    from_address: felt252,
+    payload: Span::<felt252>,
+    nonce: Option<felt252>,
+    block_context: L1HandlerBlockContext
+) -> SyscallResult<()> {
+    execute_l1_handler_or_reject(l1_handler, from_address, payload, nonce, block_context, false)
+}
+
+fn execute_l1_handler_or_reject(
+    l1_handler: L1Handler,
+    from_address: felt252,
+    payload: Span::<felt252>,
+    nonce: Option<felt252>,
+    block_context: L1HandlerBlockContext,
+    rejected: bool
+) -> SyscallResult<()> {
+    let mut inputs: Array::<felt252> = array![
+        l1_handler.target.into(), l1_handler.selector, from_address.into(),
+    ];
+    payload.serialize(ref inputs);
+    nonce.serialize(ref inputs);
+    block_context.serialize(ref inputs);
+    rejected.serialize(ref inputs);
+
+    let mut outputs = handle_cheatcode(cheatcode::<'l1_handler_execute'>(inputs.span()));
+    let exit_code = *outputs.pop_front().unwrap();
+
+    if exit_code == 0 {
+        SyscallResult::Ok(())
+    } else {
+        let panic_data = Serde::<Array<felt252>>::deserialize(ref outputs).unwrap();
+        SyscallResult::Err(panic_data)
+    }
 }
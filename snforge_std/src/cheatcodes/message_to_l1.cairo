@@ -89,6 +89,11 @@ impl MessageToL1FilterTraitImpl of MessageToL1FilterTrait {
 trait MessageToL1SpyAssertionsTrait {
     fn assert_sent(ref self: MessageToL1Spy, messages: @Array<(ContractAddress, MessageToL1)>);
     fn assert_not_sent(ref self: MessageToL1Spy, messages: @Array<(ContractAddress, MessageToL1)>);
+    /// Asserts that exactly the given messages were sent (in any order) and no others,
+    /// panicking with the list of missing and unexpected messages otherwise.
+    fn assert_sent_exactly(
+        ref self: MessageToL1Spy, messages: @Array<(ContractAddress, MessageToL1)>
+    );
 }
 
 impl MessageToL1SpyAssertionsTraitImpl of MessageToL1SpyAssertionsTrait {
@@ -123,6 +128,22 @@ impl MessageToL1SpyAssertionsTraitImpl of MessageToL1SpyAssertionsTrait {
             i += 1;
         };
     }
+    fn assert_sent_exactly(
+        ref self: MessageToL1Spy, messages: @Array<(ContractAddress, MessageToL1)>
+    ) {
+        let mut inputs = array![self._message_offset.into()];
+        messages.serialize(ref inputs);
+
+        let mut output = handle_cheatcode(
+            cheatcode::<'assert_messages_to_l1_sent_exactly'>(inputs.span())
+        );
+        let result = Serde::<Result<(), ByteArray>>::deserialize(ref output).unwrap();
+
+        match result {
+            Result::Ok(()) => {},
+            Result::Err(diff) => panic!("{}", diff),
+        }
+    }
 }
 
 fn is_sent(
@@ -0,0 +1,67 @@
+use core::array::ArrayTrait;
+use core::option::OptionTrait;
+use starknet::testing::cheatcode;
+use starknet::ContractAddress;
+use super::super::_cheatcode::handle_cheatcode;
+use super::events::Event;
+use super::message_to_l1::MessageToL1;
+
+/// Creates `SideEffectSpy` instance that spies on all events and messages to L1
+/// emitted after its creation.
+fn spy_side_effects() -> SideEffectSpy {
+    let mut side_effect_offset = handle_cheatcode(
+        cheatcode::<'spy_side_effects'>(array![].span())
+    );
+    let parsed_side_effect_offset: usize = Serde::<usize>::deserialize(ref side_effect_offset)
+        .unwrap();
+
+    SideEffectSpy { _side_effect_offset: parsed_side_effect_offset }
+}
+
+/// Event or message to L1 together with the address of the contract that emitted it.
+#[derive(Drop, Clone, Serde)]
+enum SideEffect {
+    Event: (ContractAddress, Event),
+    MessageToL1: (ContractAddress, MessageToL1),
+}
+
+/// A spy structure allowing to get events and messages to L1 emitted only after its creation,
+/// in the order of emission.
+#[derive(Drop, Serde)]
+struct SideEffectSpy {
+    _side_effect_offset: usize
+}
+
+trait SideEffectSpyTrait {
+    /// Gets all events and messages to L1 given [`SideEffectSpy`] spies for,
+    /// interleaved in the order they were emitted.
+    fn get_side_effects(ref self: SideEffectSpy) -> Array<SideEffect>;
+    /// Asserts that exactly the given side effects were emitted, in the given order,
+    /// panicking with the positions that differ otherwise.
+    fn assert_emitted_in_order(ref self: SideEffectSpy, side_effects: @Array<SideEffect>);
+}
+
+impl SideEffectSpyTraitImpl of SideEffectSpyTrait {
+    fn get_side_effects(ref self: SideEffectSpy) -> Array<SideEffect> {
+        let mut output = handle_cheatcode(
+            cheatcode::<'get_side_effects'>(array![self._side_effect_offset.into()].span())
+        );
+
+        Serde::<Array<SideEffect>>::deserialize(ref output).unwrap()
+    }
+
+    fn assert_emitted_in_order(ref self: SideEffectSpy, side_effects: @Array<SideEffect>) {
+        let mut inputs = array![self._side_effect_offset.into()];
+        side_effects.serialize(ref inputs);
+
+        let mut output = handle_cheatcode(
+            cheatcode::<'assert_side_effects_in_order'>(inputs.span())
+        );
+        let result = Serde::<Result<(), ByteArray>>::deserialize(ref output).unwrap();
+
+        match result {
+            Result::Ok(()) => {},
+            Result::Err(diff) => panic!("{}", diff),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 mod cheatcodes;
 
 use cheatcodes::contract_class::declare;
+use cheatcodes::contract_class::deploy_from_class_hash;
 use cheatcodes::contract_class::get_class_hash;
 use cheatcodes::contract_class::ContractClass;
 use cheatcodes::contract_class::ContractClassTrait;
@@ -9,9 +10,21 @@ use cheatcodes::contract_class::DeclareResultTrait;
 
 use cheatcodes::l1_handler::L1Handler;
 use cheatcodes::l1_handler::L1HandlerTrait;
+use cheatcodes::l1_handler::L1Message;
+use cheatcodes::l1_handler::L1HandlerBlockContext;
+use cheatcodes::l1_handler::StorageWrite;
+use cheatcodes::l1_handler::L1HandlerExecution;
+use cheatcodes::l1_handler::L1HandlerExecutionTrait;
+use cheatcodes::l1_handler::L1HandlerContract;
+use cheatcodes::l1_handler::L1HandlerContractTrait;
+use cheatcodes::l1_handler::deploy_for_l1_handler;
 
 use cheatcodes::fork::BlockTag;
 use cheatcodes::fork::BlockId;
+use cheatcodes::fork::fork_block_number;
+use cheatcodes::fork::prefetch_fork_state;
+use cheatcodes::fork::select_fork;
+use cheatcodes::fork::is_class_declared;
 
 use cheatcodes::events::Event;
 use cheatcodes::events::EventSpy;
@@ -25,6 +38,8 @@ use cheatcodes::message_to_l1::{
     MessageToL1SpyAssertionsTrait,
 };
 
+use cheatcodes::side_effects::{spy_side_effects, SideEffect, SideEffectSpy, SideEffectSpyTrait};
+
 use cheatcodes::storage::store;
 use cheatcodes::storage::load;
 use cheatcodes::storage::map_entry_address;